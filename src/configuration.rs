@@ -1,3 +1,8 @@
 pub mod app_data_pool;
 pub mod config;
+pub mod cookies;
 pub mod jwt;
+pub mod login_alerts;
+pub mod platform;
+pub mod seed;
+pub mod sweeper;
@@ -1,14 +1,22 @@
+use std::sync::Arc;
+
 use mongodb::Database;
 
-use crate::services::Services;
+use crate::{
+    persistence::transaction::Transactions,
+    services::{seed::manifest::Manifest, Services},
+};
 
-use super::jwt::JWT;
+use super::{cookies::Cookies, jwt::JWT};
 
 #[derive(Clone)]
 pub struct AppDataPool {
     pub database: Database,
+    pub transactions: Transactions,
     pub services: Services,
     pub jwt: JWT,
+    pub cookies: Cookies,
+    pub manifest: Option<Arc<Manifest>>,
 }
 
 impl AppDataPool {
@@ -17,13 +25,26 @@ impl AppDataPool {
     /// # Arguments
     ///
     /// * `database` - The `Database` struct that can be used to perform CRUD operations
+    /// * `transactions` - The `Transactions` struct that starts transactions for cascading writes
     /// * `services` - The `Services` struct that contains all available services
     /// * `jwt` - The `JWT` struct that contains JWT configuration
-    pub fn new(database: Database, services: Services, jwt: JWT) -> AppDataPool {
+    /// * `cookies` - The `Cookies` struct that contains the configuration of cookie sessions
+    /// * `manifest` - The seed manifest that was applied at startup, if one was configured
+    pub fn new(
+        database: Database,
+        transactions: Transactions,
+        services: Services,
+        jwt: JWT,
+        cookies: Cookies,
+        manifest: Option<Manifest>,
+    ) -> AppDataPool {
         AppDataPool {
             database,
+            transactions,
             services,
             jwt,
+            cookies,
+            manifest: manifest.map(Arc::new),
         }
     }
 }
@@ -1,8 +1,11 @@
 use config::ConfigError;
-use mongodb::{Client, Database};
+use mongodb::Client;
 use serde::Deserialize;
 
-use super::jwt::JWT;
+use super::{
+    cookies::Cookies, jwt::JWT, login_alerts::LoginAlerts, platform::Platform, seed::Seed,
+    sweeper::Sweeper,
+};
 
 #[derive(Deserialize)]
 pub struct ServerConfig {
@@ -19,7 +22,19 @@ pub struct MongoDB {
     pub password: String,
     pub ssl: bool,
     pub auth_source: String,
+    pub constraint_collection: String,
+    pub access_request_collection: String,
+    pub access_review_collection: String,
+    pub api_key_collection: String,
+    pub session_collection: String,
+    pub login_collection: String,
+    pub event_collection: String,
+    pub group_collection: String,
     pub permission_collection: String,
+    pub policy_collection: String,
+    pub relationship_collection: String,
+    pub namespace_collection: String,
+    pub organization_collection: String,
     pub role_collection: String,
     pub user_collection: String,
 }
@@ -29,6 +44,16 @@ pub struct Config {
     pub server: ServerConfig,
     pub mongodb: MongoDB,
     pub jwt: JWT,
+    #[serde(default)]
+    pub cookies: Cookies,
+    #[serde(default)]
+    pub sweeper: Sweeper,
+    #[serde(default)]
+    pub login_alerts: LoginAlerts,
+    #[serde(default)]
+    pub seed: Seed,
+    #[serde(default)]
+    pub platform: Platform,
 }
 
 impl Config {
@@ -37,14 +62,20 @@ impl Config {
             .add_source(config::Environment::default())
             .build();
 
-        return match c {
-            Ok(r) => r.try_deserialize(),
-            Err(e) => Err(e),
+        let conf: Config = match c {
+            Ok(r) => r.try_deserialize()?,
+            Err(e) => return Err(e),
         };
+
+        if let Err(e) = conf.cookies.validate() {
+            return Err(ConfigError::Message(e));
+        }
+
+        Ok(conf)
     }
 }
 
-pub async fn get_mongo_config(conf: &Config) -> Database {
+pub async fn get_mongo_client(conf: &Config) -> Client {
     let client = Client::with_uri_str(format!(
         "mongodb://{}:{}@{}:{}/?authSource={}&ssl={}",
         conf.mongodb.user,
@@ -56,5 +87,5 @@ pub async fn get_mongo_config(conf: &Config) -> Database {
     ))
     .await;
 
-    client.unwrap().database(&conf.mongodb.database)
+    client.unwrap()
 }
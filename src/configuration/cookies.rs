@@ -0,0 +1,67 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct Cookies {
+    /// Whether clients can ask `authenticate` to store the token in a cookie
+    pub enabled: bool,
+    /// Whether the cookies are only sent over HTTPS. Cookies with `SameSite=None` are always
+    /// secure.
+    pub secure: bool,
+    /// The `SameSite` attribute of the cookies: `Strict`, `Lax` or `None`
+    pub same_site: String,
+    /// Allow cookies that are sent over plain HTTP, for local development only
+    pub allow_insecure: bool,
+    /// A comma-separated list of the origins that may send the cookies, such as
+    /// `https://app.example.com`. Required when cookie sessions are enabled.
+    pub allowed_origins: String,
+}
+
+impl Cookies {
+    /// Reject cookie sessions that would send the token over plain HTTP, unless that was allowed
+    /// explicitly
+    pub fn validate(&self) -> Result<(), String> {
+        if self.enabled && !self.secure && !self.allow_insecure {
+            return Err(String::from(
+                "Cookie sessions must be secure, set allow_insecure to use them over plain HTTP",
+            ));
+        }
+
+        if self.enabled {
+            let origins = self.origins();
+            if origins.is_empty() {
+                return Err(String::from(
+                    "Cookie sessions require allowed_origins, the origins that may send the cookies",
+                ));
+            }
+            if origins.contains(&"*") {
+                return Err(String::from(
+                    "Cookie sessions cannot allow any origin, list the allowed origins instead",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The allowed origins, without the blanks around them
+    pub fn origins(&self) -> Vec<&str> {
+        self.allowed_origins
+            .split(',')
+            .map(|o| o.trim())
+            .filter(|o| !o.is_empty())
+            .collect()
+    }
+}
+
+impl Default for Cookies {
+    fn default() -> Self {
+        Cookies {
+            enabled: false,
+            secure: true,
+            same_site: String::from("Lax"),
+            allow_insecure: false,
+            allowed_origins: String::from(""),
+        }
+    }
+}
@@ -1,7 +1,10 @@
 use serde::Deserialize;
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Deserialize, Clone)]
 pub struct JWT {
     pub secret: String,
     pub expires: i64,
+    /// The number of milliseconds that an impersonation token is valid
+    pub impersonation_expires: i64,
 }
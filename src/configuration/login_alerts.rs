@@ -0,0 +1,21 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct LoginAlerts {
+    /// Path of an optional CSV file with `start,end,country` IPv4 ranges that is used to locate
+    /// the origin of logins
+    pub geoip_database: Option<String>,
+    /// The number of minutes within which successful logins from two different countries are
+    /// flagged as impossible travel
+    pub travel_window: i64,
+}
+
+impl Default for LoginAlerts {
+    fn default() -> Self {
+        LoginAlerts {
+            geoip_database: None,
+            travel_window: 60,
+        }
+    }
+}
@@ -0,0 +1,8 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone, Default)]
+pub struct Platform {
+    /// The username of a user of the default organization that is made a platform administrator
+    /// at startup, so that the first one does not have to be created in the database by hand
+    pub admin: Option<String>,
+}
@@ -0,0 +1,12 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone, Default)]
+pub struct Seed {
+    /// Path of an optional YAML or JSON manifest with the permissions, roles and bootstrap
+    /// administrator of an organization, which is applied at startup
+    pub manifest: Option<String>,
+    /// Delete the roles and permissions of the organization that the manifest does not describe,
+    /// instead of only releasing them from the manifest
+    #[serde(default)]
+    pub prune: bool,
+}
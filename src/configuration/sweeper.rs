@@ -0,0 +1,14 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct Sweeper {
+    /// The number of seconds between two runs of the background sweeper
+    pub interval: u64,
+}
+
+impl Default for Sweeper {
+    fn default() -> Self {
+        Sweeper { interval: 60 }
+    }
+}
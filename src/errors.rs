@@ -1,2 +1 @@
-pub mod bad_request;
-pub mod internal_server_error;
+pub mod app_error;
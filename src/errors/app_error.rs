@@ -0,0 +1,227 @@
+use std::fmt::{Display, Formatter};
+
+use actix_web::{
+    http::{header::CONTENT_TYPE, StatusCode},
+    HttpResponse, ResponseError,
+};
+use chrono::Utc;
+use mongodb::error::{ErrorKind, WriteFailure};
+use serde::Serialize;
+
+use crate::{
+    routes::{
+        authorization::dto::{denial_reason::DenialReason, offending_grant::OffendingGrant},
+        validation::FieldError,
+    },
+    services::relationship::relationship_service::RelationshipError,
+};
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// The errors that a request can fail with. Storage and crypto errors carry internal details,
+/// which are logged but never sent to the client.
+#[derive(Debug)]
+pub enum AppError {
+    Validation(String),
+    InvalidFields(Vec<FieldError>),
+    NotFound(String),
+    Conflict(String),
+    PreconditionFailed(String),
+    Unauthorized(DenialReason),
+    Forbidden(DenialReason, Vec<OffendingGrant>),
+    Storage(String),
+    Crypto(String),
+    Internal(String),
+}
+
+/// An RFC 7807 problem details body
+#[derive(Serialize)]
+struct ProblemDetails<'a> {
+    #[serde(rename(serialize = "type"))]
+    problem_type: &'static str,
+    title: &'static str,
+    status: u16,
+    detail: String,
+    /// Stable, machine-readable code of the error
+    code: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<DenialReason>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    grants: Option<&'a Vec<OffendingGrant>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    errors: Option<&'a Vec<FieldError>>,
+    timestamp: String,
+}
+
+impl AppError {
+    pub fn validation(message: &str) -> Self {
+        AppError::Validation(String::from(message))
+    }
+
+    pub fn not_found(message: &str) -> Self {
+        AppError::NotFound(String::from(message))
+    }
+
+    pub fn conflict(message: &str) -> Self {
+        AppError::Conflict(String::from(message))
+    }
+
+    pub fn precondition_failed(message: &str) -> Self {
+        AppError::PreconditionFailed(String::from(message))
+    }
+
+    pub fn storage(message: &str) -> Self {
+        AppError::Storage(String::from(message))
+    }
+
+    /// Deny a request that tried to grant roles or permissions that the caller does not hold
+    pub fn with_grants(grants: Vec<OffendingGrant>) -> Self {
+        AppError::Forbidden(DenialReason::PrivilegeEscalation, grants)
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Validation(_) | AppError::InvalidFields(_) => "VALIDATION_FAILED",
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::Conflict(_) => "CONFLICT",
+            AppError::PreconditionFailed(_) => "PRECONDITION_FAILED",
+            AppError::Unauthorized(_) => "UNAUTHORIZED",
+            AppError::Forbidden(_, _) => "FORBIDDEN",
+            AppError::Storage(_) => "STORAGE_FAILURE",
+            AppError::Crypto(_) => "CRYPTO_FAILURE",
+            AppError::Internal(_) => "INTERNAL_ERROR",
+        }
+    }
+
+    /// The detail that is sent to the client
+    fn detail(&self) -> String {
+        match self {
+            AppError::Validation(d)
+            | AppError::NotFound(d)
+            | AppError::Conflict(d)
+            | AppError::PreconditionFailed(d) => d.clone(),
+            AppError::InvalidFields(_) => String::from("The request contains invalid fields!"),
+            AppError::Unauthorized(d) | AppError::Forbidden(d, _) => String::from(d.message()),
+            AppError::Storage(_) | AppError::Crypto(_) | AppError::Internal(_) => {
+                String::from("The request could not be processed, please try again later!")
+            }
+        }
+    }
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Storage(d) => write!(f, "Storage failure: {}", d),
+            AppError::Crypto(d) => write!(f, "Crypto failure: {}", d),
+            AppError::Internal(d) => write!(f, "Internal error: {}", d),
+            _ => write!(f, "{}", self.detail()),
+        }
+    }
+}
+
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::Validation(_) | AppError::InvalidFields(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_, _) => StatusCode::FORBIDDEN,
+            AppError::Storage(_) | AppError::Crypto(_) | AppError::Internal(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        if status.is_server_error() {
+            log::error!("{}", self);
+        }
+
+        let (reason, grants) = match self {
+            AppError::Unauthorized(d) => (Some(*d), None),
+            AppError::Forbidden(d, g) => (Some(*d), if g.is_empty() { None } else { Some(g) }),
+            _ => (None, None),
+        };
+
+        let problem = ProblemDetails {
+            problem_type: "about:blank",
+            title: status.canonical_reason().unwrap_or_default(),
+            status: status.as_u16(),
+            detail: self.detail(),
+            code: self.code(),
+            reason,
+            grants,
+            errors: match self {
+                AppError::InvalidFields(d) => Some(d),
+                _ => None,
+            },
+            timestamp: Utc::now().to_rfc3339(),
+        };
+
+        let mut res = HttpResponse::build(status);
+        if let AppError::Unauthorized(_) = self {
+            res.insert_header(("WWW-Authenticate", "Bearer"));
+        }
+        res.insert_header((CONTENT_TYPE, PROBLEM_JSON))
+            .json(problem)
+    }
+}
+
+impl From<DenialReason> for AppError {
+    fn from(reason: DenialReason) -> Self {
+        if reason == DenialReason::StorageFailure {
+            AppError::storage(reason.message())
+        } else if reason.is_authentication_failure() {
+            AppError::Unauthorized(reason)
+        } else {
+            AppError::Forbidden(reason, vec![])
+        }
+    }
+}
+
+/// The code of the error that MongoDB raises when a write violates a unique index
+const DUPLICATE_KEY: i32 = 11000;
+
+impl From<mongodb::error::Error> for AppError {
+    fn from(e: mongodb::error::Error) -> Self {
+        let duplicate = match e.kind.as_ref() {
+            ErrorKind::Write(WriteFailure::WriteError(d)) => d.code == DUPLICATE_KEY,
+            ErrorKind::BulkWrite(d) => d
+                .write_errors
+                .iter()
+                .flatten()
+                .any(|d| d.code == DUPLICATE_KEY),
+            _ => false,
+        };
+        if duplicate {
+            return AppError::conflict("A resource with the same unique fields already exists!");
+        }
+
+        AppError::Storage(e.to_string())
+    }
+}
+
+impl From<RelationshipError> for AppError {
+    fn from(e: RelationshipError) -> Self {
+        match e {
+            RelationshipError::Storage(e) => AppError::from(e),
+            _ => AppError::validation(&e.to_string()),
+        }
+    }
+}
+
+impl From<bcrypt::BcryptError> for AppError {
+    fn from(e: bcrypt::BcryptError) -> Self {
+        AppError::Crypto(e.to_string())
+    }
+}
+
+impl From<jsonwebtoken::errors::Error> for AppError {
+    fn from(e: jsonwebtoken::errors::Error) -> Self {
+        AppError::Crypto(e.to_string())
+    }
+}
@@ -6,27 +6,126 @@ mod persistence;
 mod routes;
 mod services;
 
+use std::time::Duration;
+
 use actix_cors::Cors;
-use actix_web::{web::Data, App, HttpServer};
+use actix_web::{rt, web::Data, App, HttpServer};
 use configuration::{app_data_pool::AppDataPool, config::Config};
 use mongodb::Database;
+use persistence::{
+    organization::model::organization::DEFAULT_ORGANIZATION, transaction::Transactions,
+};
 use routes::Routes;
-use services::Services;
+use services::{
+    seed::{manifest::Manifest, seeder},
+    Services,
+};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv().ok();
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
     let conf: Config = Config::from_env().unwrap();
-    let db: Database = crate::configuration::config::get_mongo_config(&conf).await;
+    let client = crate::configuration::config::get_mongo_client(&conf).await;
+    let db: Database = client.database(&conf.mongodb.database);
+
+    let transactions = Transactions::detect(client, &db).await.unwrap();
+    if !transactions.enabled() {
+        log::warn!("MongoDB does not support transactions, cascading writes are not atomic");
+    }
 
     let services = Services::new(&conf);
-    let pool = AppDataPool::new(db, services, conf.jwt);
+    services.initialize_organizations(&db).await.unwrap();
+    services.initialize_system_permissions(&db).await.unwrap();
+    services
+        .user_service
+        .create_search_index(&db)
+        .await
+        .unwrap();
+    services
+        .user_service
+        .create_unique_indexes(&db)
+        .await
+        .unwrap();
+    services
+        .role_service
+        .create_unique_index(&db)
+        .await
+        .unwrap();
+    services
+        .permission_service
+        .create_unique_index(&db)
+        .await
+        .unwrap();
+
+    let manifest = match &conf.seed.manifest {
+        Some(path) => match Manifest::load(path) {
+            Ok(d) => Some(d),
+            Err(e) => panic!("Unable to load the seed manifest {}: {}", path, e),
+        },
+        None => None,
+    };
+    if let Some(d) = &manifest {
+        let changes = seeder::apply(&services, &db, &transactions, d, conf.seed.prune)
+            .await
+            .unwrap();
+        log::info!(
+            "Applied the seed manifest to organization {} with {} changes",
+            d.organization,
+            changes
+        );
+    }
+
+    if let Some(username) = &conf.platform.admin {
+        let admin = services
+            .user_service
+            .grant_platform_admin(&db, DEFAULT_ORGANIZATION, username)
+            .await
+            .unwrap();
+        if admin.is_none() {
+            log::warn!(
+                "Platform administrator {} does not exist in the default organization",
+                username
+            );
+        }
+    }
+
+    let sweeper_services = services.clone();
+    let sweeper_db = db.clone();
+    let sweeper_interval = conf.sweeper.interval;
+    rt::spawn(async move {
+        let mut interval = rt::time::interval(Duration::from_secs(sweeper_interval));
+        loop {
+            interval.tick().await;
+            if let Err(e) = sweeper_services.sweep(&sweeper_db).await {
+                log::error!("Unable to run the sweeper: {}", e);
+            }
+        }
+    });
+
+    let cookies = conf.cookies.clone();
+    let pool = AppDataPool::new(db, transactions, services, conf.jwt, conf.cookies, manifest);
 
     HttpServer::new(move || {
+        // Browsers only send the session cookie cross-origin to origins that are allowed
+        // explicitly, so a permissive policy is only used for bearer tokens
+        let cors = if cookies.enabled {
+            cookies
+                .origins()
+                .iter()
+                .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin))
+                .allow_any_method()
+                .allow_any_header()
+                .expose_any_header()
+                .supports_credentials()
+        } else {
+            Cors::permissive()
+        };
+
         App::new()
             .app_data(Data::new(pool.clone()))
-            .wrap(Cors::permissive())
+            .wrap(cors)
             .configure(Routes::configure_routes)
     })
     .bind(format!("{}:{}", conf.server.host, conf.server.port))?
@@ -1,19 +1,59 @@
 use crate::configuration::config::Config;
 
 use self::{
-    permission::permission_repository::PermissionRepository, role::role_repository::RoleRepository,
+    access_request::access_request_repository::AccessRequestRepository,
+    access_review::access_review_repository::AccessReviewRepository,
+    api_key::api_key_repository::ApiKeyRepository,
+    constraint::constraint_repository::ConstraintRepository,
+    event::event_repository::EventRepository,
+    group::group_repository::GroupRepository,
+    login::login_repository::LoginRepository,
+    organization::organization_repository::OrganizationRepository,
+    permission::permission_repository::PermissionRepository,
+    policy::policy_repository::PolicyRepository,
+    relationship::{
+        namespace_repository::NamespaceRepository, relationship_repository::RelationshipRepository,
+    },
+    role::role_repository::RoleRepository,
+    session::session_repository::SessionRepository,
     user::user_repository::UserRepository,
 };
 
+pub mod access_request;
+pub mod access_review;
+pub mod api_key;
+pub mod constraint;
+pub mod event;
+pub mod group;
+pub mod login;
+pub mod organization;
+pub mod page;
 pub mod permission;
+pub mod policy;
+pub mod relationship;
 pub mod role;
+pub mod session;
+pub mod transaction;
 pub mod user;
+pub mod version;
 
 #[derive(Clone)]
 pub struct Repositories {
     pub user_repository: UserRepository,
     pub role_repository: RoleRepository,
     pub permission_repository: PermissionRepository,
+    pub policy_repository: PolicyRepository,
+    pub relationship_repository: RelationshipRepository,
+    pub namespace_repository: NamespaceRepository,
+    pub organization_repository: OrganizationRepository,
+    pub group_repository: GroupRepository,
+    pub event_repository: EventRepository,
+    pub access_request_repository: AccessRequestRepository,
+    pub constraint_repository: ConstraintRepository,
+    pub access_review_repository: AccessReviewRepository,
+    pub api_key_repository: ApiKeyRepository,
+    pub session_repository: SessionRepository,
+    pub login_repository: LoginRepository,
 }
 
 impl Repositories {
@@ -22,6 +62,26 @@ impl Repositories {
             user_repository: UserRepository::new(&config.mongodb.user_collection),
             role_repository: RoleRepository::new(&config.mongodb.role_collection),
             permission_repository: PermissionRepository::new(&config.mongodb.permission_collection),
+            policy_repository: PolicyRepository::new(&config.mongodb.policy_collection),
+            relationship_repository: RelationshipRepository::new(
+                &config.mongodb.relationship_collection,
+            ),
+            namespace_repository: NamespaceRepository::new(&config.mongodb.namespace_collection),
+            organization_repository: OrganizationRepository::new(
+                &config.mongodb.organization_collection,
+            ),
+            group_repository: GroupRepository::new(&config.mongodb.group_collection),
+            event_repository: EventRepository::new(&config.mongodb.event_collection),
+            access_request_repository: AccessRequestRepository::new(
+                &config.mongodb.access_request_collection,
+            ),
+            constraint_repository: ConstraintRepository::new(&config.mongodb.constraint_collection),
+            access_review_repository: AccessReviewRepository::new(
+                &config.mongodb.access_review_collection,
+            ),
+            api_key_repository: ApiKeyRepository::new(&config.mongodb.api_key_collection),
+            session_repository: SessionRepository::new(&config.mongodb.session_collection),
+            login_repository: LoginRepository::new(&config.mongodb.login_collection),
         }
     }
 }
@@ -0,0 +1,2 @@
+pub mod access_request_repository;
+pub mod model;
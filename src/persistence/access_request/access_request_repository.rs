@@ -0,0 +1,178 @@
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, to_bson, Document},
+    error::Error,
+    options::FindOptions,
+    ClientSession, Database,
+};
+
+use crate::persistence::transaction;
+
+use super::model::access_request::{AccessRequest, AccessRequestState};
+
+#[derive(Clone)]
+pub struct AccessRequestRepository {
+    pub collection: String,
+}
+
+impl AccessRequestRepository {
+    pub fn new(collection: &str) -> Self {
+        Self {
+            collection: String::from(collection),
+        }
+    }
+
+    pub async fn create(
+        &self,
+        request: AccessRequest,
+        db: &Database,
+    ) -> Result<Option<AccessRequest>, Error> {
+        let organization = request.organization.clone();
+        let collection = db.collection::<AccessRequest>(&self.collection);
+        let res = match collection.insert_one(request, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        let new_uuid = match res.inserted_id.as_str() {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+
+        self.find_by_uuid(db, &organization, new_uuid).await
+    }
+
+    pub async fn find_by_uuid(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<Option<AccessRequest>, Error> {
+        let filter = doc! { "_id": uuid, "organization": organization };
+        let cursor = match db
+            .collection::<AccessRequest>(&self.collection)
+            .find_one(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor)
+    }
+
+    pub async fn find_by_requester(
+        &self,
+        db: &Database,
+        organization: &str,
+        requester_id: &str,
+    ) -> Result<Vec<AccessRequest>, Error> {
+        self.find(
+            db,
+            doc! { "organization": organization, "requesterId": requester_id },
+        )
+        .await
+    }
+
+    pub async fn find_by_state(
+        &self,
+        db: &Database,
+        organization: &str,
+        state: AccessRequestState,
+    ) -> Result<Vec<AccessRequest>, Error> {
+        self.find(
+            db,
+            doc! { "organization": organization, "state": to_bson(&state).unwrap() },
+        )
+        .await
+    }
+
+    /// Find the requests of every organization that are in the given state
+    pub async fn find_all_by_state(
+        &self,
+        db: &Database,
+        state: AccessRequestState,
+    ) -> Result<Vec<AccessRequest>, Error> {
+        self.find(db, doc! { "state": to_bson(&state).unwrap() })
+            .await
+    }
+
+    pub async fn find_pending(
+        &self,
+        db: &Database,
+        organization: &str,
+        requester_id: &str,
+        role_id: &str,
+    ) -> Result<Option<AccessRequest>, Error> {
+        let filter = doc! {
+            "organization": organization,
+            "requesterId": requester_id,
+            "roleId": role_id,
+            "state": to_bson(&AccessRequestState::Pending).unwrap(),
+        };
+        let cursor = match db
+            .collection::<AccessRequest>(&self.collection)
+            .find_one(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor)
+    }
+
+    /// Store the decision of a request, but only if the request is still in the state that the
+    /// decision was based on. Returns `None` when the request was changed in the meantime.
+    pub async fn transition(
+        &self,
+        db: &Database,
+        from: AccessRequestState,
+        request: AccessRequest,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Option<AccessRequest>, Error> {
+        let collection = db.collection::<AccessRequest>(&self.collection);
+        let filter = doc! {
+            "_id": &request.id,
+            "organization": &request.organization,
+            "state": to_bson(&from).unwrap(),
+        };
+        let update = doc! {
+            "$set": {
+                "state": to_bson(&request.state).unwrap(),
+                "decidedBy": &request.decided_by,
+                "decidedAt": &request.decided_at,
+                "comment": &request.comment,
+                "validUntil": &request.valid_until,
+            }
+        };
+
+        let res = match transaction::update_one(&collection, filter, update, session).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        // Only the decision changed, so the stored request is the one that was passed in
+        if res.matched_count > 0 {
+            Ok(Some(request))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn find(&self, db: &Database, filter: Document) -> Result<Vec<AccessRequest>, Error> {
+        let options = FindOptions::builder()
+            .sort(doc! { "createdAt": -1 })
+            .build();
+        let cursor = match db
+            .collection::<AccessRequest>(&self.collection)
+            .find(filter, options)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        cursor.try_collect().await
+    }
+}
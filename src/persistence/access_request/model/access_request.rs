@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AccessRequestState {
+    Pending,
+    Approved,
+    Denied,
+    Expired,
+    Revoked,
+}
+
+impl AccessRequestState {
+    /// Check whether a request in this state may move to the next state.
+    /// Denied, expired and revoked requests are final.
+    pub fn can_transition_to(self, next: AccessRequestState) -> bool {
+        use AccessRequestState::*;
+
+        matches!(
+            (self, next),
+            (Pending, Approved)
+                | (Pending, Denied)
+                | (Pending, Expired)
+                | (Pending, Revoked)
+                | (Approved, Expired)
+                | (Approved, Revoked)
+        )
+    }
+}
+
+/// A request of a user to temporarily receive a role
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AccessRequest {
+    #[serde(rename(serialize = "_id", deserialize = "_id"))]
+    pub id: String,
+    pub organization: String,
+    #[serde(rename(serialize = "requesterId", deserialize = "requesterId"))]
+    pub requester_id: String,
+    #[serde(rename(serialize = "roleId", deserialize = "roleId"))]
+    pub role_id: String,
+    /// The number of seconds that the role is granted for once the request is approved
+    pub duration: i64,
+    pub justification: String,
+    pub state: AccessRequestState,
+    #[serde(rename(serialize = "createdAt", deserialize = "createdAt"))]
+    pub created_at: String,
+    #[serde(rename(serialize = "decidedBy", deserialize = "decidedBy"))]
+    pub decided_by: Option<String>,
+    #[serde(rename(serialize = "decidedAt", deserialize = "decidedAt"))]
+    pub decided_at: Option<String>,
+    pub comment: Option<String>,
+    #[serde(rename(serialize = "validUntil", deserialize = "validUntil"))]
+    pub valid_until: Option<String>,
+}
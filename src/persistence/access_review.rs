@@ -0,0 +1,2 @@
+pub mod access_review_repository;
+pub mod model;
@@ -0,0 +1,155 @@
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, to_bson},
+    error::Error,
+    options::FindOptions,
+    Database,
+};
+
+use super::model::access_review::{AccessReview, ReviewDecision, ReviewItem, ReviewState};
+
+#[derive(Clone)]
+pub struct AccessReviewRepository {
+    pub collection: String,
+}
+
+impl AccessReviewRepository {
+    pub fn new(collection: &str) -> Self {
+        Self {
+            collection: String::from(collection),
+        }
+    }
+
+    pub async fn create(
+        &self,
+        review: AccessReview,
+        db: &Database,
+    ) -> Result<Option<AccessReview>, Error> {
+        let organization = review.organization.clone();
+        let collection = db.collection::<AccessReview>(&self.collection);
+        let res = match collection.insert_one(review, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        let new_uuid = match res.inserted_id.as_str() {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+
+        self.find_by_uuid(db, &organization, new_uuid).await
+    }
+
+    pub async fn find_all(
+        &self,
+        db: &Database,
+        organization: &str,
+    ) -> Result<Vec<AccessReview>, Error> {
+        let options = FindOptions::builder()
+            .sort(doc! { "createdAt": -1 })
+            .build();
+        let cursor = match db
+            .collection::<AccessReview>(&self.collection)
+            .find(doc! { "organization": organization }, options)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        cursor.try_collect().await
+    }
+
+    pub async fn find_by_uuid(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<Option<AccessReview>, Error> {
+        let filter = doc! { "_id": uuid, "organization": organization };
+        let cursor = match db
+            .collection::<AccessReview>(&self.collection)
+            .find_one(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor)
+    }
+
+    /// Store the decision on a pending item of an open campaign. Returns `None` when the
+    /// campaign is closed or the item was already decided upon.
+    pub async fn decide_item(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        item: ReviewItem,
+    ) -> Result<Option<AccessReview>, Error> {
+        let collection = db.collection::<AccessReview>(&self.collection);
+        let filter = doc! {
+            "_id": uuid,
+            "organization": organization,
+            "state": to_bson(&ReviewState::Open).unwrap(),
+            "items": {
+                "$elemMatch": {
+                    "id": &item.id,
+                    "decision": to_bson(&ReviewDecision::Pending).unwrap(),
+                }
+            },
+        };
+        let update = doc! {
+            "$set": {
+                "items.$.decision": to_bson(&item.decision).unwrap(),
+                "items.$.reviewerId": item.reviewer_id,
+                "items.$.decidedAt": item.decided_at,
+                "items.$.comment": item.comment,
+            }
+        };
+
+        let res = match collection.update_one(filter, update, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        if res.matched_count > 0 {
+            self.find_by_uuid(db, organization, uuid).await
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub async fn close(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        closed_at: &str,
+    ) -> Result<Option<AccessReview>, Error> {
+        let collection = db.collection::<AccessReview>(&self.collection);
+        let filter = doc! {
+            "_id": uuid,
+            "organization": organization,
+            "state": to_bson(&ReviewState::Open).unwrap(),
+        };
+        let update = doc! {
+            "$set": {
+                "state": to_bson(&ReviewState::Closed).unwrap(),
+                "closedAt": closed_at,
+            }
+        };
+
+        let res = match collection.update_one(filter, update, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        if res.matched_count > 0 {
+            self.find_by_uuid(db, organization, uuid).await
+        } else {
+            Ok(None)
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReviewState {
+    Open,
+    Closed,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReviewDecision {
+    Pending,
+    Approved,
+    Revoked,
+}
+
+/// The role assignments that a campaign covers. Empty lists do not restrict the campaign.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReviewScope {
+    pub roles: Vec<String>,
+    pub users: Vec<String>,
+}
+
+/// A single role of a single user that has to be recertified
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReviewItem {
+    pub id: String,
+    #[serde(rename(serialize = "userId", deserialize = "userId"))]
+    pub user_id: String,
+    pub username: String,
+    #[serde(rename(serialize = "roleId", deserialize = "roleId"))]
+    pub role_id: String,
+    #[serde(rename(serialize = "roleName", deserialize = "roleName"))]
+    pub role_name: String,
+    pub decision: ReviewDecision,
+    #[serde(rename(serialize = "reviewerId", deserialize = "reviewerId"))]
+    pub reviewer_id: Option<String>,
+    #[serde(rename(serialize = "decidedAt", deserialize = "decidedAt"))]
+    pub decided_at: Option<String>,
+    pub comment: Option<String>,
+}
+
+/// A recertification campaign
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AccessReview {
+    #[serde(rename(serialize = "_id", deserialize = "_id"))]
+    pub id: String,
+    pub organization: String,
+    pub name: String,
+    pub description: String,
+    pub scope: ReviewScope,
+    pub reviewers: Vec<String>,
+    pub state: ReviewState,
+    #[serde(rename(serialize = "createdBy", deserialize = "createdBy"))]
+    pub created_by: String,
+    #[serde(rename(serialize = "createdAt", deserialize = "createdAt"))]
+    pub created_at: String,
+    #[serde(rename(serialize = "closedAt", deserialize = "closedAt"))]
+    pub closed_at: Option<String>,
+    pub items: Vec<ReviewItem>,
+}
@@ -0,0 +1,159 @@
+use futures::TryStreamExt;
+use mongodb::{bson::doc, error::Error, ClientSession, Database};
+
+use crate::persistence::transaction;
+
+use super::model::api_key::ApiKey;
+
+#[derive(Clone)]
+pub struct ApiKeyRepository {
+    pub collection: String,
+}
+
+impl ApiKeyRepository {
+    pub fn new(collection: &str) -> Self {
+        Self {
+            collection: String::from(collection),
+        }
+    }
+
+    pub async fn create(&self, api_key: ApiKey, db: &Database) -> Result<Option<ApiKey>, Error> {
+        let organization = api_key.organization.clone();
+        let collection = db.collection::<ApiKey>(&self.collection);
+        let res = match collection.insert_one(api_key, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        let new_uuid = match res.inserted_id.as_str() {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+
+        self.find_by_uuid(db, &organization, new_uuid).await
+    }
+
+    pub async fn find_by_owner(
+        &self,
+        db: &Database,
+        organization: &str,
+        owner_id: &str,
+    ) -> Result<Vec<ApiKey>, Error> {
+        let cursor = match db
+            .collection::<ApiKey>(&self.collection)
+            .find(
+                doc! { "organization": organization, "ownerId": owner_id },
+                None,
+            )
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        cursor.try_collect().await
+    }
+
+    pub async fn find_by_uuid(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<Option<ApiKey>, Error> {
+        let filter = doc! { "_id": uuid, "organization": organization };
+        let cursor = match db
+            .collection::<ApiKey>(&self.collection)
+            .find_one(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor)
+    }
+
+    /// Find a key by its prefix. Keys identify their organization, so the lookup is global.
+    pub async fn find_by_prefix(
+        &self,
+        db: &Database,
+        prefix: &str,
+    ) -> Result<Option<ApiKey>, Error> {
+        let filter = doc! { "prefix": prefix };
+        let cursor = match db
+            .collection::<ApiKey>(&self.collection)
+            .find_one(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor)
+    }
+
+    pub async fn update_last_used(
+        &self,
+        db: &Database,
+        uuid: &str,
+        last_used: &str,
+    ) -> Result<u64, Error> {
+        let collection = db.collection::<ApiKey>(&self.collection);
+        let filter = doc! { "_id": uuid };
+        let update = doc! { "$set": { "lastUsed": last_used } };
+
+        let res = match collection.update_one(filter, update, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(res.modified_count)
+    }
+
+    /// Revoke a key that was not revoked before
+    pub async fn revoke(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        revoked_at: &str,
+    ) -> Result<Option<ApiKey>, Error> {
+        let collection = db.collection::<ApiKey>(&self.collection);
+        let filter = doc! { "_id": uuid, "organization": organization, "revokedAt": null };
+        let update = doc! { "$set": { "revokedAt": revoked_at } };
+
+        let res = match collection.update_one(filter, update, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        if res.matched_count > 0 {
+            self.find_by_uuid(db, organization, uuid).await
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Delete every key of an owner
+    pub async fn delete_by_owner(
+        &self,
+        db: &Database,
+        organization: &str,
+        owner_id: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        let filter = doc! { "organization": organization, "ownerId": owner_id };
+        let cursor = match transaction::delete_many(
+            &db.collection::<ApiKey>(&self.collection),
+            filter,
+            session,
+        )
+        .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor.deleted_count)
+    }
+}
@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::persistence::user::model::role_assignment::parse_timestamp;
+
+/// A long-lived credential of a user or service account. Only the hash of the key is stored, the
+/// prefix is used to look the key up.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ApiKey {
+    #[serde(rename(serialize = "_id", deserialize = "_id"))]
+    pub id: String,
+    pub organization: String,
+    #[serde(rename(serialize = "ownerId", deserialize = "ownerId"))]
+    pub owner_id: String,
+    pub name: String,
+    pub prefix: String,
+    pub hash: String,
+    /// The names of the permissions that the key is restricted to, if any
+    pub permissions: Option<Vec<String>>,
+    #[serde(rename(serialize = "createdBy", deserialize = "createdBy"))]
+    pub created_by: String,
+    #[serde(rename(serialize = "createdAt", deserialize = "createdAt"))]
+    pub created_at: String,
+    #[serde(rename(serialize = "expiresAt", deserialize = "expiresAt"))]
+    pub expires_at: Option<String>,
+    #[serde(rename(serialize = "lastUsed", deserialize = "lastUsed"))]
+    pub last_used: Option<String>,
+    #[serde(rename(serialize = "revokedAt", deserialize = "revokedAt"))]
+    pub revoked_at: Option<String>,
+}
+
+impl ApiKey {
+    /// Whether the key has expired at the given moment. Keys with an unreadable expiry are
+    /// treated as expired.
+    pub fn has_expired(&self, now: DateTime<Utc>) -> bool {
+        match &self.expires_at {
+            Some(d) => !matches!(parse_timestamp(d), Some(x) if x > now),
+            None => false,
+        }
+    }
+}
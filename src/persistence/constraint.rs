@@ -0,0 +1,2 @@
+pub mod constraint_repository;
+pub mod model;
@@ -0,0 +1,162 @@
+use futures::TryStreamExt;
+use mongodb::{bson::doc, error::Error, ClientSession, Database};
+
+use crate::persistence::transaction;
+
+use super::model::constraint::Constraint;
+
+#[derive(Clone)]
+pub struct ConstraintRepository {
+    pub collection: String,
+}
+
+impl ConstraintRepository {
+    pub fn new(collection: &str) -> Self {
+        Self {
+            collection: String::from(collection),
+        }
+    }
+
+    pub async fn create(
+        &self,
+        constraint: Constraint,
+        db: &Database,
+    ) -> Result<Option<Constraint>, Error> {
+        let organization = constraint.organization.clone();
+        let collection = db.collection::<Constraint>(&self.collection);
+        let res = match collection.insert_one(constraint, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        let new_uuid = match res.inserted_id.as_str() {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+
+        self.find_by_uuid(db, &organization, new_uuid).await
+    }
+
+    pub async fn find_all(
+        &self,
+        db: &Database,
+        organization: &str,
+    ) -> Result<Vec<Constraint>, Error> {
+        let cursor = match db
+            .collection::<Constraint>(&self.collection)
+            .find(doc! { "organization": organization }, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        cursor.try_collect().await
+    }
+
+    pub async fn find_by_uuid(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<Option<Constraint>, Error> {
+        let filter = doc! { "_id": uuid, "organization": organization };
+        let cursor = match db
+            .collection::<Constraint>(&self.collection)
+            .find_one(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor)
+    }
+
+    pub async fn find_by_name(
+        &self,
+        db: &Database,
+        organization: &str,
+        name: &str,
+    ) -> Result<Option<Constraint>, Error> {
+        let filter = doc! { "name": name, "organization": organization };
+        let cursor = match db
+            .collection::<Constraint>(&self.collection)
+            .find_one(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor)
+    }
+
+    pub async fn update(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        constraint: Constraint,
+    ) -> Result<Option<Constraint>, Error> {
+        let collection = db.collection::<Constraint>(&self.collection);
+        let filter = doc! { "_id": uuid, "organization": organization };
+        let update = doc! {
+            "$set": {
+                "name": constraint.name,
+                "description": constraint.description,
+                "roles": constraint.roles,
+            }
+        };
+
+        let res = match collection.update_one(filter, update, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        if res.matched_count > 0 {
+            self.find_by_uuid(db, organization, uuid).await
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Remove a role from every constraint in the organization
+    pub async fn remove_role(
+        &self,
+        db: &Database,
+        organization: &str,
+        role_id: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        let collection = db.collection::<Constraint>(&self.collection);
+        let filter = doc! { "organization": organization, "roles": role_id };
+        let update = doc! { "$pull": { "roles": role_id } };
+
+        let res = match transaction::update_many(&collection, filter, update, session).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(res.modified_count)
+    }
+
+    pub async fn delete(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<u64, Error> {
+        let filter = doc! { "_id": uuid, "organization": organization };
+        let cursor = match db
+            .collection::<Constraint>(&self.collection)
+            .delete_one(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor.deleted_count)
+    }
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// A set of mutually exclusive roles. No user may hold more than one role of the set.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Constraint {
+    #[serde(rename(serialize = "_id", deserialize = "_id"))]
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub roles: Vec<String>,
+    pub organization: String,
+}
@@ -0,0 +1,2 @@
+pub mod event_repository;
+pub mod model;
@@ -0,0 +1,45 @@
+use futures::TryStreamExt;
+use mongodb::{bson::doc, error::Error, options::FindOptions, Database};
+
+use super::model::event::Event;
+
+#[derive(Clone)]
+pub struct EventRepository {
+    pub collection: String,
+}
+
+impl EventRepository {
+    pub fn new(collection: &str) -> Self {
+        Self {
+            collection: String::from(collection),
+        }
+    }
+
+    pub async fn create(&self, event: Event, db: &Database) -> Result<(), Error> {
+        match db
+            .collection::<Event>(&self.collection)
+            .insert_one(event, None)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Find the events of an organization, newest first
+    pub async fn find_all(&self, db: &Database, organization: &str) -> Result<Vec<Event>, Error> {
+        let options = FindOptions::builder()
+            .sort(doc! { "timestamp": -1 })
+            .build();
+        let cursor = match db
+            .collection::<Event>(&self.collection)
+            .find(doc! { "organization": organization }, options)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        cursor.try_collect().await
+    }
+}
@@ -0,0 +1,27 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+pub const ROLE_ASSIGNMENT_EXPIRED: &str = "ROLE_ASSIGNMENT_EXPIRED";
+pub const IMPERSONATION_STARTED: &str = "IMPERSONATION_STARTED";
+pub const IMPERSONATED_REQUEST: &str = "IMPERSONATED_REQUEST";
+pub const NEW_DEVICE_LOGIN: &str = "NEW_DEVICE_LOGIN";
+pub const NEW_NETWORK_LOGIN: &str = "NEW_NETWORK_LOGIN";
+pub const IMPOSSIBLE_TRAVEL: &str = "IMPOSSIBLE_TRAVEL";
+pub const DATA_IMPORTED: &str = "DATA_IMPORTED";
+pub const SEED_APPLIED: &str = "SEED_APPLIED";
+
+/// Something noteworthy that happened inside an organization
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Event {
+    #[serde(rename(serialize = "_id", deserialize = "_id"))]
+    pub id: String,
+    #[serde(rename(serialize = "type", deserialize = "type"))]
+    pub event_type: String,
+    pub organization: String,
+    #[serde(rename(serialize = "userId", deserialize = "userId"))]
+    pub user_id: Option<String>,
+    pub message: String,
+    pub data: HashMap<String, String>,
+    pub timestamp: String,
+}
@@ -0,0 +1,253 @@
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, Document},
+    error::Error,
+    ClientSession, Database,
+};
+
+use crate::persistence::transaction;
+
+use super::model::group::Group;
+
+#[derive(Clone)]
+pub struct GroupRepository {
+    pub collection: String,
+}
+
+impl GroupRepository {
+    pub fn new(collection: &str) -> Self {
+        Self {
+            collection: String::from(collection),
+        }
+    }
+
+    pub async fn create(&self, group: Group, db: &Database) -> Result<Option<Group>, Error> {
+        let organization = group.organization.clone();
+        let collection = db.collection::<Group>(&self.collection);
+        let res = match collection.insert_one(group, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        let new_uuid = match res.inserted_id.as_str() {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+
+        self.find_by_uuid(db, &organization, new_uuid).await
+    }
+
+    pub async fn find_all(&self, db: &Database, organization: &str) -> Result<Vec<Group>, Error> {
+        self.find(db, doc! { "organization": organization }).await
+    }
+
+    pub async fn find_by_uuid(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<Option<Group>, Error> {
+        let filter = doc! { "_id": uuid, "organization": organization };
+        let cursor = match db
+            .collection::<Group>(&self.collection)
+            .find_one(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor)
+    }
+
+    pub async fn find_by_name(
+        &self,
+        db: &Database,
+        organization: &str,
+        name: &str,
+    ) -> Result<Option<Group>, Error> {
+        let filter = doc! { "name": name, "organization": organization };
+        let cursor = match db
+            .collection::<Group>(&self.collection)
+            .find_one(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor)
+    }
+
+    pub async fn find_by_member(
+        &self,
+        db: &Database,
+        organization: &str,
+        user_id: &str,
+    ) -> Result<Vec<Group>, Error> {
+        self.find(
+            db,
+            doc! { "members": user_id, "organization": organization },
+        )
+        .await
+    }
+
+    pub async fn find_by_uuids(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuids: &[String],
+    ) -> Result<Vec<Group>, Error> {
+        self.find(
+            db,
+            doc! { "_id": { "$in": uuids }, "organization": organization },
+        )
+        .await
+    }
+
+    /// Find the groups that contain any of the given groups
+    pub async fn find_by_child_groups(
+        &self,
+        db: &Database,
+        organization: &str,
+        group_ids: &[String],
+    ) -> Result<Vec<Group>, Error> {
+        self.find(
+            db,
+            doc! { "groups": { "$in": group_ids }, "organization": organization },
+        )
+        .await
+    }
+
+    pub async fn update(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        group: Group,
+    ) -> Result<Option<Group>, Error> {
+        let collection = db.collection::<Group>(&self.collection);
+        let filter = doc! { "_id": uuid, "organization": organization };
+        let update = doc! {
+            "$set": {
+                "name": group.name,
+                "description": group.description,
+                "roles": mongodb::bson::to_bson(&group.roles).unwrap()
+            }
+        };
+
+        let res = match collection.update_one(filter, update, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        if res.matched_count > 0 {
+            self.find_by_uuid(db, organization, uuid).await
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Add a value to one of the array fields of a group
+    pub async fn add_to_set(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        field: &str,
+        value: &str,
+    ) -> Result<Option<Group>, Error> {
+        let collection = db.collection::<Group>(&self.collection);
+        let filter = doc! { "_id": uuid, "organization": organization };
+        let update = doc! { "$addToSet": { field: value } };
+
+        let res = match collection.update_one(filter, update, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        if res.matched_count > 0 {
+            self.find_by_uuid(db, organization, uuid).await
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Remove a value from one of the array fields of a group
+    pub async fn pull(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        field: &str,
+        value: &str,
+    ) -> Result<Option<Group>, Error> {
+        let collection = db.collection::<Group>(&self.collection);
+        let filter = doc! { "_id": uuid, "organization": organization };
+        let update = doc! { "$pull": { field: value } };
+
+        let res = match collection.update_one(filter, update, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        if res.matched_count > 0 {
+            self.find_by_uuid(db, organization, uuid).await
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Remove a value from one of the array fields of every group in the organization
+    pub async fn pull_from_all(
+        &self,
+        db: &Database,
+        organization: &str,
+        field: &str,
+        value: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        let collection = db.collection::<Group>(&self.collection);
+        let filter = doc! { field: value, "organization": organization };
+        let update = doc! { "$pull": { field: value } };
+
+        let res = match transaction::update_many(&collection, filter, update, session).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(res.modified_count)
+    }
+
+    pub async fn delete(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        let qry = doc! { "_id": uuid, "organization": organization };
+        let cursor =
+            match transaction::delete_one(&db.collection::<Group>(&self.collection), qry, session)
+                .await
+            {
+                Ok(d) => d,
+                Err(e) => return Err(e),
+            };
+
+        Ok(cursor.deleted_count)
+    }
+
+    async fn find(&self, db: &Database, filter: Document) -> Result<Vec<Group>, Error> {
+        let cursor = match db
+            .collection::<Group>(&self.collection)
+            .find(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        cursor.try_collect().await
+    }
+}
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+use crate::persistence::organization::model::organization::default_organization;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Group {
+    #[serde(rename(serialize = "_id", deserialize = "_id"))]
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub members: Vec<String>,
+    pub groups: Vec<String>,
+    pub roles: Vec<String>,
+    #[serde(default = "default_organization")]
+    pub organization: String,
+}
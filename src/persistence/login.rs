@@ -0,0 +1,2 @@
+pub mod login_repository;
+pub mod model;
@@ -0,0 +1,89 @@
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, to_bson},
+    error::Error,
+    options::FindOptions,
+    Database,
+};
+
+use super::model::login_attempt::{LoginAttempt, LoginOutcome};
+
+#[derive(Clone)]
+pub struct LoginRepository {
+    pub collection: String,
+}
+
+impl LoginRepository {
+    pub fn new(collection: &str) -> Self {
+        Self {
+            collection: String::from(collection),
+        }
+    }
+
+    pub async fn create(&self, attempt: LoginAttempt, db: &Database) -> Result<(), Error> {
+        match db
+            .collection::<LoginAttempt>(&self.collection)
+            .insert_one(attempt, None)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Find the most recent login attempts of a user, newest first
+    pub async fn find_by_user(
+        &self,
+        db: &Database,
+        organization: &str,
+        user_id: &str,
+        limit: i64,
+    ) -> Result<Vec<LoginAttempt>, Error> {
+        let options = FindOptions::builder()
+            .sort(doc! { "timestamp": -1 })
+            .limit(limit)
+            .build();
+        let cursor = match db
+            .collection::<LoginAttempt>(&self.collection)
+            .find(
+                doc! { "organization": organization, "userId": user_id },
+                options,
+            )
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        cursor.try_collect().await
+    }
+
+    /// Find the most recent successful logins of a user, newest first
+    pub async fn find_successful_by_user(
+        &self,
+        db: &Database,
+        organization: &str,
+        user_id: &str,
+        limit: i64,
+    ) -> Result<Vec<LoginAttempt>, Error> {
+        let options = FindOptions::builder()
+            .sort(doc! { "timestamp": -1 })
+            .limit(limit)
+            .build();
+        let filter = doc! {
+            "organization": organization,
+            "userId": user_id,
+            "outcome": to_bson(&LoginOutcome::Success).unwrap(),
+        };
+        let cursor = match db
+            .collection::<LoginAttempt>(&self.collection)
+            .find(filter, options)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        cursor.try_collect().await
+    }
+}
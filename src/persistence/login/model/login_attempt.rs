@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LoginOutcome {
+    Success,
+    UnknownUser,
+    DisabledUser,
+    InvalidPassword,
+}
+
+/// Something unusual about a successful login
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LoginAlert {
+    /// The login came from a device fingerprint that the user never signed in from
+    NewDevice,
+    /// The login came from an IP range that the user never signed in from
+    NewNetwork,
+    /// The login came from another country than a recent login of the user
+    ImpossibleTravel,
+}
+
+/// A single attempt to authenticate
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LoginAttempt {
+    #[serde(rename(serialize = "_id", deserialize = "_id"))]
+    pub id: String,
+    pub organization: String,
+    #[serde(rename(serialize = "userId", deserialize = "userId"))]
+    pub user_id: Option<String>,
+    pub username: String,
+    pub outcome: LoginOutcome,
+    pub ip: Option<String>,
+    #[serde(rename(serialize = "userAgent", deserialize = "userAgent"))]
+    pub user_agent: Option<String>,
+    pub fingerprint: Option<String>,
+    pub network: Option<String>,
+    pub country: Option<String>,
+    pub alerts: Vec<LoginAlert>,
+    pub timestamp: String,
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// The organization that owns every document that was created before organizations existed
+pub const DEFAULT_ORGANIZATION: &str = "default";
+
+pub fn default_organization() -> String {
+    String::from(DEFAULT_ORGANIZATION)
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Organization {
+    #[serde(rename(serialize = "_id", deserialize = "_id"))]
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub enabled: bool,
+    #[serde(rename(serialize = "createdAt", deserialize = "createdAt"))]
+    pub created_at: String,
+}
@@ -0,0 +1,114 @@
+use futures::TryStreamExt;
+use mongodb::{bson::doc, error::Error, ClientSession, Database};
+
+use crate::persistence::transaction;
+
+use super::model::organization::Organization;
+
+#[derive(Clone)]
+pub struct OrganizationRepository {
+    pub collection: String,
+}
+
+impl OrganizationRepository {
+    pub fn new(collection: &str) -> Self {
+        Self {
+            collection: String::from(collection),
+        }
+    }
+
+    pub async fn create(
+        &self,
+        organization: Organization,
+        db: &Database,
+    ) -> Result<Option<Organization>, Error> {
+        let collection = db.collection::<Organization>(&self.collection);
+        let res = match collection.insert_one(organization, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        let new_id = match res.inserted_id.as_str() {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+
+        self.find_by_id(db, new_id).await
+    }
+
+    pub async fn find_all(&self, db: &Database) -> Result<Vec<Organization>, Error> {
+        let cursor = match db
+            .collection::<Organization>(&self.collection)
+            .find(None, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        cursor.try_collect().await
+    }
+
+    pub async fn find_by_id(&self, db: &Database, id: &str) -> Result<Option<Organization>, Error> {
+        let filter = doc! { "_id": id };
+        let cursor = match db
+            .collection::<Organization>(&self.collection)
+            .find_one(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor)
+    }
+
+    pub async fn update(
+        &self,
+        db: &Database,
+        id: &str,
+        organization: Organization,
+    ) -> Result<Option<Organization>, Error> {
+        let collection = db.collection::<Organization>(&self.collection);
+        let filter = doc! { "_id": id };
+        let update = doc! {
+            "$set": {
+                "name": organization.name,
+                "description": organization.description,
+                "enabled": organization.enabled,
+            }
+        };
+
+        let res = match collection.update_one(filter, update, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        if res.matched_count > 0 {
+            self.find_by_id(db, id).await
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub async fn delete(
+        &self,
+        db: &Database,
+        id: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        let qry = doc! { "_id": id };
+        let cursor = match transaction::delete_one(
+            &db.collection::<Organization>(&self.collection),
+            qry,
+            session,
+        )
+        .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor.deleted_count)
+    }
+}
@@ -0,0 +1,50 @@
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, Document},
+    error::Error,
+    options::FindOptions,
+    Collection,
+};
+use serde::de::DeserializeOwned;
+
+/// A window of a sorted query
+pub struct PageRequest {
+    pub sort: Document,
+    pub offset: u64,
+    pub limit: i64,
+}
+
+/// A page of results together with the number of results of the whole query
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: u64,
+}
+
+/// Run a query and return the requested page of its results
+pub async fn find_page<T>(
+    collection: Collection<T>,
+    filter: Document,
+    page: &PageRequest,
+) -> Result<Page<T>, Error>
+where
+    T: DeserializeOwned + Unpin + Send + Sync,
+{
+    let total = collection.count_documents(filter.clone(), None).await?;
+
+    let options = FindOptions::builder()
+        .sort(page.sort.clone())
+        .skip(page.offset)
+        .limit(page.limit)
+        .build();
+    let cursor = collection.find(filter, options).await?;
+
+    Ok(Page {
+        items: cursor.try_collect().await?,
+        total,
+    })
+}
+
+/// Match the values of a field that start with the given text, ignoring case
+pub fn prefix_filter(prefix: &str) -> Document {
+    doc! { "$regex": format!("^{}", regex::escape(prefix)), "$options": "i" }
+}
@@ -1,9 +1,19 @@
 use serde::{Deserialize, Serialize};
 
+use crate::persistence::organization::model::organization::default_organization;
+
 #[derive(Serialize, Deserialize)]
 pub struct Permission {
     #[serde(rename(serialize = "_id", deserialize = "_id"))]
     pub id: String,
     pub name: String,
     pub description: String,
+    #[serde(default = "default_organization")]
+    pub organization: String,
+    /// Incremented on every update, used for optimistic concurrency
+    #[serde(default)]
+    pub version: i64,
+    /// Owned by the seed manifest, which means that it cannot be changed through the API
+    #[serde(default)]
+    pub managed: bool,
 }
@@ -1,5 +1,13 @@
 use futures::TryStreamExt;
-use mongodb::{bson::doc, error::Error, Database};
+use mongodb::{
+    bson::doc, error::Error, options::IndexOptions, ClientSession, Database, IndexModel,
+};
+
+use crate::persistence::{
+    page::{find_page, prefix_filter, Page, PageRequest},
+    transaction,
+    version::version_filter,
+};
 
 use super::model::permission::Permission;
 
@@ -15,44 +23,85 @@ impl PermissionRepository {
         }
     }
 
+    /// Create the index that keeps the names of permissions unique within an organization
+    pub async fn create_unique_index(&self, db: &Database) -> Result<(), Error> {
+        let index = IndexModel::builder()
+            .keys(doc! { "organization": 1, "name": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+
+        db.collection::<Permission>(&self.collection)
+            .create_index(index, None)
+            .await?;
+
+        Ok(())
+    }
+
     pub async fn create(
         &self,
         permission: Permission,
         db: &Database,
+        mut session: Option<&mut ClientSession>,
     ) -> Result<Option<Permission>, Error> {
+        let organization = permission.organization.clone();
         let collection = db.collection::<Permission>(&self.collection);
-        let res = match collection.insert_one(permission, None).await {
-            Ok(d) => d,
-            Err(e) => return Err(e),
-        };
+        let res =
+            match transaction::insert_one(&collection, permission, session.as_deref_mut()).await {
+                Ok(d) => d,
+                Err(e) => return Err(e),
+            };
 
         let new_uuid = match res.inserted_id.as_str() {
             Some(d) => d,
             None => return Ok(None),
         };
 
-        self.find_by_uuid(db, new_uuid).await
+        let filter = doc! { "_id": new_uuid, "organization": organization };
+        transaction::find_one(&collection, filter, session).await
     }
 
-    pub async fn find_all(&self, db: &Database) -> Result<Vec<Permission>, Error> {
+    pub async fn find_all(
+        &self,
+        db: &Database,
+        organization: &str,
+    ) -> Result<Vec<Permission>, Error> {
+        let filter = doc! { "organization": organization };
         let cursor = match db
             .collection::<Permission>(&self.collection)
-            .find(None, None)
+            .find(filter, None)
             .await
         {
             Ok(d) => d,
             Err(e) => return Err(e),
         };
 
-        Ok(cursor.try_collect().await.unwrap_or_else(|_| vec![]))
+        cursor.try_collect().await
+    }
+
+    /// Find a page of the permissions of an organization, optionally only those whose name starts
+    /// with the given text
+    pub async fn find_page(
+        &self,
+        db: &Database,
+        organization: &str,
+        name: Option<&str>,
+        page: &PageRequest,
+    ) -> Result<Page<Permission>, Error> {
+        let mut filter = doc! { "organization": organization };
+        if let Some(d) = name {
+            filter.insert("name", prefix_filter(d));
+        }
+
+        find_page(db.collection::<Permission>(&self.collection), filter, page).await
     }
 
     pub async fn find_by_uuid(
         &self,
         db: &Database,
+        organization: &str,
         uuid: &str,
     ) -> Result<Option<Permission>, Error> {
-        let filter = doc! { "_id": uuid};
+        let filter = doc! { "_id": uuid, "organization": organization };
         let cursor = match db
             .collection::<Permission>(&self.collection)
             .find_one(filter, None)
@@ -68,9 +117,10 @@ impl PermissionRepository {
     pub async fn find_by_name(
         &self,
         db: &Database,
+        organization: &str,
         name: &str,
     ) -> Result<Option<Permission>, Error> {
-        let filter = doc! { "name": name};
+        let filter = doc! { "name": name, "organization": organization };
         let cursor = match db
             .collection::<Permission>(&self.collection)
             .find_one(filter, None)
@@ -83,22 +133,34 @@ impl PermissionRepository {
         Ok(cursor)
     }
 
+    /// Only updates the permission if it is still at the version that it was read at. Returns `None`
+    /// when the permission does not exist or was changed in the meantime.
     pub async fn update(
         &self,
         db: &Database,
+        organization: &str,
         uuid: &str,
         permission: Permission,
+        mut session: Option<&mut ClientSession>,
     ) -> Result<Option<Permission>, Error> {
         let collection = db.collection::<Permission>(&self.collection);
-        let filter = doc! { "_id": uuid };
+        let filter = doc! {
+            "_id": uuid,
+            "organization": organization,
+            "version": version_filter(permission.version)
+        };
         let update = doc! {
+            "$inc": { "version": 1_i64 },
             "$set": {
                 "name": permission.name,
                 "description": permission.description,
+                "managed": permission.managed,
             }
         };
 
-        let res = match collection.update_one(filter, update, None).await {
+        let res = match transaction::update_one(&collection, filter, update, session.as_deref_mut())
+            .await
+        {
             Ok(d) => d,
             Err(e) => return Err(e),
         };
@@ -106,23 +168,81 @@ impl PermissionRepository {
         let matched_count = res.matched_count;
 
         if matched_count > 0 {
-            self.find_by_uuid(db, uuid).await
+            let filter = doc! { "_id": uuid, "organization": organization };
+            transaction::find_one(&collection, filter, session).await
         } else {
             Ok(None)
         }
     }
 
-    pub async fn delete(&self, db: &Database, uuid: &str) -> Result<u64, Error> {
-        let qry = doc! { "_id": uuid };
-        let cursor = match db
+    /// Deletes the permission, only if it is still at `version` when one is given
+    pub async fn delete(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        version: Option<i64>,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        let mut qry = doc! { "_id": uuid, "organization": organization };
+        if let Some(d) = version {
+            qry.insert("version", version_filter(d));
+        }
+        let cursor = match transaction::delete_one(
+            &db.collection::<Permission>(&self.collection),
+            qry,
+            session,
+        )
+        .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor.deleted_count)
+    }
+
+    /// Deletes every permission of an organization with one of the given names
+    pub async fn delete_by_names(
+        &self,
+        db: &Database,
+        organization: &str,
+        names: &[&str],
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        let qry = doc! { "organization": organization, "name": { "$in": names } };
+        let cursor = match transaction::delete_many(
+            &db.collection::<Permission>(&self.collection),
+            qry,
+            session,
+        )
+        .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor.deleted_count)
+    }
+
+    /// Assign the default organization to permissions that were created before organizations existed
+    pub async fn assign_default_organization(
+        &self,
+        db: &Database,
+        organization: &str,
+    ) -> Result<u64, Error> {
+        let filter = doc! { "organization": { "$exists": false } };
+        let update = doc! { "$set": { "organization": organization } };
+
+        let res = match db
             .collection::<Permission>(&self.collection)
-            .delete_one(qry, None)
+            .update_many(filter, update, None)
             .await
         {
             Ok(d) => d,
             Err(e) => return Err(e),
         };
 
-        Ok(cursor.deleted_count)
+        Ok(res.modified_count)
     }
 }
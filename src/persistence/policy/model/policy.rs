@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::persistence::organization::model::organization::default_organization;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Effect {
+    Allow,
+    Deny,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Operator {
+    Equals,
+    NotEquals,
+    In,
+    NotIn,
+    Contains,
+    GreaterThan,
+    GreaterThanOrEqual,
+    LessThan,
+    LessThanOrEqual,
+    Between,
+    StartsWith,
+    InCidr,
+    Exists,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Condition {
+    pub attribute: String,
+    pub operator: Operator,
+    #[serde(default)]
+    pub value: Option<Value>,
+    #[serde(
+        rename(serialize = "valueAttribute", deserialize = "valueAttribute"),
+        default
+    )]
+    pub value_attribute: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Policy {
+    #[serde(rename(serialize = "_id", deserialize = "_id"))]
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub effect: Effect,
+    pub actions: Vec<String>,
+    pub conditions: Vec<Condition>,
+    pub enabled: bool,
+    #[serde(default = "default_organization")]
+    pub organization: String,
+}
@@ -0,0 +1,155 @@
+use futures::TryStreamExt;
+use mongodb::{bson::doc, error::Error, Database};
+
+use super::model::policy::Policy;
+
+#[derive(Clone)]
+pub struct PolicyRepository {
+    pub collection: String,
+}
+
+impl PolicyRepository {
+    pub fn new(collection: &str) -> Self {
+        Self {
+            collection: String::from(collection),
+        }
+    }
+
+    pub async fn create(&self, policy: Policy, db: &Database) -> Result<Option<Policy>, Error> {
+        let organization = policy.organization.clone();
+        let collection = db.collection::<Policy>(&self.collection);
+        let res = match collection.insert_one(policy, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        let new_uuid = match res.inserted_id.as_str() {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+
+        self.find_by_uuid(db, &organization, new_uuid).await
+    }
+
+    pub async fn find_all(&self, db: &Database, organization: &str) -> Result<Vec<Policy>, Error> {
+        let filter = doc! { "organization": organization };
+        let cursor = match db
+            .collection::<Policy>(&self.collection)
+            .find(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        cursor.try_collect().await
+    }
+
+    pub async fn find_by_uuid(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<Option<Policy>, Error> {
+        let filter = doc! { "_id": uuid, "organization": organization };
+        let cursor = match db
+            .collection::<Policy>(&self.collection)
+            .find_one(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor)
+    }
+
+    pub async fn find_by_name(
+        &self,
+        db: &Database,
+        organization: &str,
+        name: &str,
+    ) -> Result<Option<Policy>, Error> {
+        let filter = doc! { "name": name, "organization": organization };
+        let cursor = match db
+            .collection::<Policy>(&self.collection)
+            .find_one(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor)
+    }
+
+    pub async fn find_enabled_by_action(
+        &self,
+        db: &Database,
+        organization: &str,
+        action: &str,
+    ) -> Result<Vec<Policy>, Error> {
+        let filter = doc! { "organization": organization, "actions": action, "enabled": true };
+        let cursor = match db
+            .collection::<Policy>(&self.collection)
+            .find(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        cursor.try_collect().await
+    }
+
+    pub async fn update(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        policy: Policy,
+    ) -> Result<Option<Policy>, Error> {
+        let collection = db.collection::<Policy>(&self.collection);
+        let filter = doc! { "_id": uuid, "organization": organization };
+        let update = doc! {
+            "$set": {
+                "name": policy.name,
+                "description": policy.description,
+                "effect": mongodb::bson::to_bson(&policy.effect).unwrap(),
+                "actions": mongodb::bson::to_bson(&policy.actions).unwrap(),
+                "conditions": mongodb::bson::to_bson(&policy.conditions).unwrap(),
+                "enabled": policy.enabled,
+            }
+        };
+
+        let res = match collection.update_one(filter, update, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        if res.matched_count > 0 {
+            self.find_by_uuid(db, organization, uuid).await
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub async fn delete(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<u64, Error> {
+        let qry = doc! { "_id": uuid, "organization": organization };
+        let cursor = match db
+            .collection::<Policy>(&self.collection)
+            .delete_one(qry, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor.deleted_count)
+    }
+}
@@ -0,0 +1,3 @@
+pub mod model;
+pub mod namespace_repository;
+pub mod relationship_repository;
@@ -0,0 +1,2 @@
+pub mod namespace;
+pub mod relation_tuple;
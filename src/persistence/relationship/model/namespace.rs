@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+use crate::persistence::organization::model::organization::default_organization;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Rewrite {
+    /// The subjects that are stored directly for the relation
+    This,
+    /// The subjects of another relation on the same object
+    ComputedUserset { relation: String },
+    /// The subjects of `computed_relation` on every object that is related through `tupleset`
+    TupleToUserset {
+        tupleset: String,
+        #[serde(rename(serialize = "computedRelation", deserialize = "computedRelation"))]
+        computed_relation: String,
+    },
+    /// The subjects of any of the children
+    Union { children: Vec<Rewrite> },
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Relation {
+    pub name: String,
+    #[serde(default)]
+    pub rewrite: Option<Rewrite>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Namespace {
+    #[serde(rename(serialize = "_id", deserialize = "_id"))]
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_organization")]
+    pub organization: String,
+    pub relations: Vec<Relation>,
+}
+
+impl Namespace {
+    pub fn new(organization: &str, name: &str, relations: Vec<Relation>) -> Self {
+        Self {
+            id: format!("{}/{}", organization, name),
+            name: String::from(name),
+            organization: String::from(organization),
+            relations,
+        }
+    }
+
+    pub fn find_relation(&self, name: &str) -> Option<&Relation> {
+        self.relations.iter().find(|r| r.name == name)
+    }
+}
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+use crate::persistence::organization::model::organization::default_organization;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RelationTuple {
+    #[serde(rename(serialize = "_id", deserialize = "_id"))]
+    pub id: String,
+    pub namespace: String,
+    #[serde(rename(serialize = "objectId", deserialize = "objectId"))]
+    pub object_id: String,
+    pub relation: String,
+    #[serde(rename(serialize = "subjectNamespace", deserialize = "subjectNamespace"))]
+    pub subject_namespace: String,
+    #[serde(rename(serialize = "subjectId", deserialize = "subjectId"))]
+    pub subject_id: String,
+    #[serde(rename(serialize = "subjectRelation", deserialize = "subjectRelation"))]
+    pub subject_relation: Option<String>,
+    #[serde(default = "default_organization")]
+    pub organization: String,
+}
+
+impl RelationTuple {
+    pub fn new(
+        organization: &str,
+        namespace: &str,
+        object_id: &str,
+        relation: &str,
+        subject_namespace: &str,
+        subject_id: &str,
+        subject_relation: Option<&str>,
+    ) -> Self {
+        let mut tuple = Self {
+            id: String::new(),
+            namespace: String::from(namespace),
+            object_id: String::from(object_id),
+            relation: String::from(relation),
+            subject_namespace: String::from(subject_namespace),
+            subject_id: String::from(subject_id),
+            subject_relation: subject_relation.map(String::from),
+            organization: String::from(organization),
+        };
+        tuple.id = format!("{}/{}", organization, tuple);
+        tuple
+    }
+
+    /// The object of the tuple in `namespace:id` notation
+    pub fn object(&self) -> String {
+        format!("{}:{}", self.namespace, self.object_id)
+    }
+
+    /// The subject of the tuple in `namespace:id` or `namespace:id#relation` notation
+    pub fn subject(&self) -> String {
+        match &self.subject_relation {
+            Some(r) => format!("{}:{}#{}", self.subject_namespace, self.subject_id, r),
+            None => format!("{}:{}", self.subject_namespace, self.subject_id),
+        }
+    }
+}
+
+impl std::fmt::Display for RelationTuple {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}#{}@{}", self.object(), self.relation, self.subject())
+    }
+}
@@ -0,0 +1,88 @@
+use futures::TryStreamExt;
+use mongodb::{bson::doc, error::Error, options::ReplaceOptions, Database};
+
+use super::model::namespace::Namespace;
+
+#[derive(Clone)]
+pub struct NamespaceRepository {
+    pub collection: String,
+}
+
+impl NamespaceRepository {
+    pub fn new(collection: &str) -> Self {
+        Self {
+            collection: String::from(collection),
+        }
+    }
+
+    pub async fn find_all(
+        &self,
+        db: &Database,
+        organization: &str,
+    ) -> Result<Vec<Namespace>, Error> {
+        let filter = doc! { "organization": organization };
+        let cursor = match db
+            .collection::<Namespace>(&self.collection)
+            .find(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        cursor.try_collect().await
+    }
+
+    pub async fn find_by_name(
+        &self,
+        db: &Database,
+        organization: &str,
+        name: &str,
+    ) -> Result<Option<Namespace>, Error> {
+        let filter = doc! { "organization": organization, "name": name };
+        let cursor = match db
+            .collection::<Namespace>(&self.collection)
+            .find_one(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor)
+    }
+
+    pub async fn save(
+        &self,
+        db: &Database,
+        namespace: Namespace,
+    ) -> Result<Option<Namespace>, Error> {
+        let collection = db.collection::<Namespace>(&self.collection);
+        let filter = doc! { "_id": &namespace.id };
+        let options = ReplaceOptions::builder().upsert(true).build();
+
+        collection.replace_one(filter, &namespace, options).await?;
+
+        self.find_by_name(db, &namespace.organization, &namespace.name)
+            .await
+    }
+
+    pub async fn delete(
+        &self,
+        db: &Database,
+        organization: &str,
+        name: &str,
+    ) -> Result<u64, Error> {
+        let qry = doc! { "organization": organization, "name": name };
+        let cursor = match db
+            .collection::<Namespace>(&self.collection)
+            .delete_one(qry, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor.deleted_count)
+    }
+}
@@ -0,0 +1,86 @@
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, Document},
+    error::Error,
+    ClientSession, Database,
+};
+
+use crate::persistence::transaction;
+
+use super::model::relation_tuple::RelationTuple;
+
+#[derive(Clone)]
+pub struct RelationshipRepository {
+    pub collection: String,
+}
+
+impl RelationshipRepository {
+    pub fn new(collection: &str) -> Self {
+        Self {
+            collection: String::from(collection),
+        }
+    }
+
+    /// Store a tuple. Writing a tuple that already exists has no effect.
+    pub async fn write(
+        &self,
+        db: &Database,
+        tuple: RelationTuple,
+        session: Option<&mut ClientSession>,
+    ) -> Result<(), Error> {
+        let collection = db.collection::<RelationTuple>(&self.collection);
+        let filter = doc! { "_id": &tuple.id };
+
+        match transaction::upsert_one(&collection, filter, tuple, session).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn delete(
+        &self,
+        db: &Database,
+        organization: &str,
+        id: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        let collection = db.collection::<RelationTuple>(&self.collection);
+        let qry = doc! { "_id": id, "organization": organization };
+        let res = match transaction::delete_one(&collection, qry, session).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(res.deleted_count)
+    }
+
+    pub async fn find_by_object_and_relation(
+        &self,
+        db: &Database,
+        organization: &str,
+        namespace: &str,
+        object_id: &str,
+        relation: &str,
+    ) -> Result<Vec<RelationTuple>, Error> {
+        let filter = doc! {
+            "organization": organization,
+            "namespace": namespace,
+            "objectId": object_id,
+            "relation": relation
+        };
+        self.find(db, filter).await
+    }
+
+    pub async fn find(&self, db: &Database, filter: Document) -> Result<Vec<RelationTuple>, Error> {
+        let cursor = match db
+            .collection::<RelationTuple>(&self.collection)
+            .find(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        cursor.try_collect().await
+    }
+}
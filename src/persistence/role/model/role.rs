@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::persistence::organization::model::organization::default_organization;
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Role {
     #[serde(rename(serialize = "_id", deserialize = "_id"))]
@@ -7,4 +9,15 @@ pub struct Role {
     pub name: String,
     pub description: String,
     pub permissions: Vec<String>,
+    /// The permissions whose holders decide on access requests for this role
+    #[serde(rename = "approverPermissions", default)]
+    pub approver_permissions: Vec<String>,
+    #[serde(default = "default_organization")]
+    pub organization: String,
+    /// Incremented on every update, used for optimistic concurrency
+    #[serde(default)]
+    pub version: i64,
+    /// Owned by the seed manifest, which means that it cannot be changed through the API
+    #[serde(default)]
+    pub managed: bool,
 }
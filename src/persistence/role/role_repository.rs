@@ -1,5 +1,16 @@
 use futures::TryStreamExt;
-use mongodb::{bson::doc, error::Error, Database};
+use mongodb::{
+    bson::{doc, Document},
+    error::Error,
+    options::IndexOptions,
+    ClientSession, Database, IndexModel,
+};
+
+use crate::persistence::{
+    page::{find_page, prefix_filter, Page, PageRequest},
+    transaction,
+    version::version_filter,
+};
 
 use super::model::role::Role;
 
@@ -15,9 +26,29 @@ impl RoleRepository {
         }
     }
 
-    pub async fn create(&self, role: Role, db: &Database) -> Result<Option<Role>, Error> {
+    /// Create the index that keeps the names of roles unique within an organization
+    pub async fn create_unique_index(&self, db: &Database) -> Result<(), Error> {
+        let index = IndexModel::builder()
+            .keys(doc! { "organization": 1, "name": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+
+        db.collection::<Role>(&self.collection)
+            .create_index(index, None)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn create(
+        &self,
+        role: Role,
+        db: &Database,
+        mut session: Option<&mut ClientSession>,
+    ) -> Result<Option<Role>, Error> {
+        let organization = role.organization.clone();
         let collection = db.collection::<Role>(&self.collection);
-        let res = match collection.insert_one(role, None).await {
+        let res = match transaction::insert_one(&collection, role, session.as_deref_mut()).await {
             Ok(d) => d,
             Err(e) => return Err(e),
         };
@@ -27,24 +58,48 @@ impl RoleRepository {
             None => return Ok(None),
         };
 
-        self.find_by_uuid(db, new_uuid).await
+        let filter = doc! { "_id": new_uuid, "organization": organization };
+        transaction::find_one(&collection, filter, session).await
     }
 
-    pub async fn find_all(&self, db: &Database) -> Result<Vec<Role>, Error> {
+    pub async fn find_all(&self, db: &Database, organization: &str) -> Result<Vec<Role>, Error> {
+        let filter = doc! { "organization": organization };
         let cursor = match db
             .collection::<Role>(&self.collection)
-            .find(None, None)
+            .find(filter, None)
             .await
         {
             Ok(d) => d,
             Err(e) => return Err(e),
         };
 
-        Ok(cursor.try_collect().await.unwrap_or_else(|_| vec![]))
+        cursor.try_collect().await
+    }
+
+    /// Find a page of the roles of an organization, optionally only those whose name starts
+    /// with the given text
+    pub async fn find_page(
+        &self,
+        db: &Database,
+        organization: &str,
+        name: Option<&str>,
+        page: &PageRequest,
+    ) -> Result<Page<Role>, Error> {
+        let mut filter = doc! { "organization": organization };
+        if let Some(d) = name {
+            filter.insert("name", prefix_filter(d));
+        }
+
+        find_page(db.collection::<Role>(&self.collection), filter, page).await
     }
 
-    pub async fn find_by_uuid(&self, db: &Database, uuid: &str) -> Result<Option<Role>, Error> {
-        let filter = doc! { "_id": uuid};
+    pub async fn find_by_uuid(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<Option<Role>, Error> {
+        let filter = doc! { "_id": uuid, "organization": organization };
         let cursor = match db
             .collection::<Role>(&self.collection)
             .find_one(filter, None)
@@ -57,8 +112,13 @@ impl RoleRepository {
         Ok(cursor)
     }
 
-    pub async fn find_by_name(&self, db: &Database, name: &str) -> Result<Option<Role>, Error> {
-        let filter = doc! { "name": name};
+    pub async fn find_by_name(
+        &self,
+        db: &Database,
+        organization: &str,
+        name: &str,
+    ) -> Result<Option<Role>, Error> {
+        let filter = doc! { "name": name, "organization": organization };
         let cursor = match db
             .collection::<Role>(&self.collection)
             .find_one(filter, None)
@@ -71,41 +131,135 @@ impl RoleRepository {
         Ok(cursor)
     }
 
-    pub async fn find_by_permission_id(
+    /// Count the roles of an organization that have one of the given ids
+    pub async fn count_by_uuids(
         &self,
         db: &Database,
-        permission_id: &str,
-    ) -> Result<Vec<Role>, Error> {
-        let filter = doc! { "permissions": permission_id};
-        let cursor = match db
-            .collection::<Role>(&self.collection)
-            .find(filter, None)
+        organization: &str,
+        uuids: &[String],
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        let filter = doc! { "_id": { "$in": uuids }, "organization": organization };
+        transaction::count_documents(&db.collection::<Role>(&self.collection), filter, session)
             .await
-        {
+    }
+
+    /// Add a permission to a role. Adding a permission that the role already contains has no
+    /// effect.
+    pub async fn add_permission(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        permission_id: &str,
+    ) -> Result<Option<Role>, Error> {
+        self.modify_permissions(
+            db,
+            organization,
+            uuid,
+            doc! { "$addToSet": { "permissions": permission_id } },
+        )
+        .await
+    }
+
+    /// Remove a permission from a role
+    pub async fn remove_permission(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        permission_id: &str,
+    ) -> Result<Option<Role>, Error> {
+        self.modify_permissions(
+            db,
+            organization,
+            uuid,
+            doc! { "$pull": { "permissions": permission_id } },
+        )
+        .await
+    }
+
+    async fn modify_permissions(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        mut update: Document,
+    ) -> Result<Option<Role>, Error> {
+        let collection = db.collection::<Role>(&self.collection);
+        let filter = doc! { "_id": uuid, "organization": organization };
+        update.insert("$inc", doc! { "version": 1_i64 });
+
+        let res = match collection.update_one(filter, update, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        if res.matched_count > 0 {
+            self.find_by_uuid(db, organization, uuid).await
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Remove a permission from every role of an organization that contains it, either as one of
+    /// its permissions or as one of its approver permissions
+    pub async fn pull_permission(
+        &self,
+        db: &Database,
+        organization: &str,
+        permission_id: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        let collection = db.collection::<Role>(&self.collection);
+        let filter = doc! {
+            "organization": organization,
+            "$or": [
+                { "permissions": permission_id },
+                { "approverPermissions": permission_id },
+            ],
+        };
+        let update = doc! {
+            "$pull": { "permissions": permission_id, "approverPermissions": permission_id },
+            "$inc": { "version": 1_i64 }
+        };
+
+        let res = match transaction::update_many(&collection, filter, update, session).await {
             Ok(d) => d,
             Err(e) => return Err(e),
         };
 
-        Ok(cursor.try_collect().await.unwrap_or_else(|_| vec![]))
+        Ok(res.modified_count)
     }
 
     pub async fn update(
         &self,
         db: &Database,
+        organization: &str,
         uuid: &str,
         role: Role,
+        mut session: Option<&mut ClientSession>,
     ) -> Result<Option<Role>, Error> {
         let collection = db.collection::<Role>(&self.collection);
-        let filter = doc! { "_id": uuid };
+        let filter = doc! {
+            "_id": uuid,
+            "organization": organization,
+            "version": version_filter(role.version)
+        };
         let update = doc! {
+            "$inc": { "version": 1_i64 },
             "$set": {
                 "name": role.name,
                 "description": role.description,
-                "permissions": mongodb::bson::to_bson(&role.permissions).unwrap()
+                "permissions": mongodb::bson::to_bson(&role.permissions).unwrap(),
+                "approverPermissions": mongodb::bson::to_bson(&role.approver_permissions).unwrap(),
+                "managed": role.managed
             }
         };
 
-        let res = match collection.update_one(filter, update, None).await {
+        let res = match transaction::update_one(&collection, filter, update, session.as_deref_mut())
+            .await
+        {
             Ok(d) => d,
             Err(e) => return Err(e),
         };
@@ -113,23 +267,55 @@ impl RoleRepository {
         let matched_count = res.matched_count;
 
         if matched_count > 0 {
-            self.find_by_uuid(db, uuid).await
+            let filter = doc! { "_id": uuid, "organization": organization };
+            transaction::find_one(&collection, filter, session).await
         } else {
             Ok(None)
         }
     }
 
-    pub async fn delete(&self, db: &Database, uuid: &str) -> Result<u64, Error> {
-        let qry = doc! { "_id": uuid };
-        let cursor = match db
+    /// Deletes the role, only if it is still at `version` when one is given
+    pub async fn delete(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        version: Option<i64>,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        let mut qry = doc! { "_id": uuid, "organization": organization };
+        if let Some(d) = version {
+            qry.insert("version", version_filter(d));
+        }
+        let cursor =
+            match transaction::delete_one(&db.collection::<Role>(&self.collection), qry, session)
+                .await
+            {
+                Ok(d) => d,
+                Err(e) => return Err(e),
+            };
+
+        Ok(cursor.deleted_count)
+    }
+
+    /// Assign the default organization to roles that were created before organizations existed
+    pub async fn assign_default_organization(
+        &self,
+        db: &Database,
+        organization: &str,
+    ) -> Result<u64, Error> {
+        let filter = doc! { "organization": { "$exists": false } };
+        let update = doc! { "$set": { "organization": organization } };
+
+        let res = match db
             .collection::<Role>(&self.collection)
-            .delete_one(qry, None)
+            .update_many(filter, update, None)
             .await
         {
             Ok(d) => d,
             Err(e) => return Err(e),
         };
 
-        Ok(cursor.deleted_count)
+        Ok(res.modified_count)
     }
 }
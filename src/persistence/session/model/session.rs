@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// A signed-in device of a user. Tokens that are issued by `authenticate` and `impersonate`
+/// reference their session and stop working once it is revoked.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Session {
+    #[serde(rename(serialize = "_id", deserialize = "_id"))]
+    pub id: String,
+    pub organization: String,
+    #[serde(rename(serialize = "userId", deserialize = "userId"))]
+    pub user_id: String,
+    #[serde(rename(serialize = "createdAt", deserialize = "createdAt"))]
+    pub created_at: String,
+    #[serde(rename(serialize = "lastSeen", deserialize = "lastSeen"))]
+    pub last_seen: String,
+    #[serde(rename(serialize = "expiresAt", deserialize = "expiresAt"))]
+    pub expires_at: String,
+    pub ip: Option<String>,
+    #[serde(rename(serialize = "userAgent", deserialize = "userAgent"))]
+    pub user_agent: Option<String>,
+    pub device: String,
+    /// The user that is impersonating the owner of the session
+    #[serde(rename(serialize = "actorId", deserialize = "actorId"), default)]
+    pub actor_id: Option<String>,
+}
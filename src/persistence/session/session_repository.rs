@@ -0,0 +1,154 @@
+use futures::TryStreamExt;
+use mongodb::{bson::doc, error::Error, options::FindOptions, ClientSession, Database};
+
+use crate::persistence::transaction;
+
+use super::model::session::Session;
+
+#[derive(Clone)]
+pub struct SessionRepository {
+    pub collection: String,
+}
+
+impl SessionRepository {
+    pub fn new(collection: &str) -> Self {
+        Self {
+            collection: String::from(collection),
+        }
+    }
+
+    pub async fn create(&self, session: Session, db: &Database) -> Result<Option<Session>, Error> {
+        let organization = session.organization.clone();
+        let collection = db.collection::<Session>(&self.collection);
+        let res = match collection.insert_one(session, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        let new_uuid = match res.inserted_id.as_str() {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+
+        self.find_by_uuid(db, &organization, new_uuid).await
+    }
+
+    pub async fn find_by_uuid(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<Option<Session>, Error> {
+        let filter = doc! { "_id": uuid, "organization": organization };
+        let cursor = match db
+            .collection::<Session>(&self.collection)
+            .find_one(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor)
+    }
+
+    /// Find the sessions of a user, most recently seen first
+    pub async fn find_by_user(
+        &self,
+        db: &Database,
+        organization: &str,
+        user_id: &str,
+    ) -> Result<Vec<Session>, Error> {
+        let options = FindOptions::builder().sort(doc! { "lastSeen": -1 }).build();
+        let cursor = match db
+            .collection::<Session>(&self.collection)
+            .find(
+                doc! { "organization": organization, "userId": user_id },
+                options,
+            )
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        cursor.try_collect().await
+    }
+
+    pub async fn update_last_seen(
+        &self,
+        db: &Database,
+        uuid: &str,
+        last_seen: &str,
+    ) -> Result<u64, Error> {
+        let collection = db.collection::<Session>(&self.collection);
+        let filter = doc! { "_id": uuid };
+        let update = doc! { "$set": { "lastSeen": last_seen } };
+
+        let res = match collection.update_one(filter, update, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(res.modified_count)
+    }
+
+    /// Delete a session of a user
+    pub async fn delete(
+        &self,
+        db: &Database,
+        organization: &str,
+        user_id: &str,
+        uuid: &str,
+    ) -> Result<u64, Error> {
+        let filter = doc! { "_id": uuid, "organization": organization, "userId": user_id };
+        let cursor = match db
+            .collection::<Session>(&self.collection)
+            .delete_one(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor.deleted_count)
+    }
+
+    pub async fn delete_by_user(
+        &self,
+        db: &Database,
+        organization: &str,
+        user_id: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        let filter = doc! { "organization": organization, "userId": user_id };
+        let cursor = match transaction::delete_many(
+            &db.collection::<Session>(&self.collection),
+            filter,
+            session,
+        )
+        .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor.deleted_count)
+    }
+
+    /// Delete every session whose tokens have expired. Timestamps are stored as RFC 3339 in UTC,
+    /// so they can be compared as strings.
+    pub async fn delete_expired(&self, db: &Database, now: &str) -> Result<u64, Error> {
+        let filter = doc! { "expiresAt": { "$lt": now } };
+        let cursor = match db
+            .collection::<Session>(&self.collection)
+            .delete_many(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(cursor.deleted_count)
+    }
+}
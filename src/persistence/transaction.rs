@@ -0,0 +1,183 @@
+use mongodb::{
+    bson::{doc, Document},
+    error::Error,
+    options::{ReplaceOptions, UpdateModifications},
+    results::{DeleteResult, InsertOneResult, UpdateResult},
+    Client, ClientSession, Collection, Database,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Starts transactions for multi-document writes, if the deployment supports them
+#[derive(Clone)]
+pub struct Transactions {
+    client: Client,
+    enabled: bool,
+}
+
+impl Transactions {
+    /// Transactions require a replica set or a sharded cluster. Standalone deployments fall back
+    /// to applying writes one by one.
+    pub async fn detect(client: Client, db: &Database) -> Result<Transactions, Error> {
+        let hello = db.run_command(doc! { "isMaster": 1 }, None).await?;
+        let enabled =
+            hello.contains_key("setName") || hello.get_str("msg").ok() == Some("isdbgrid");
+
+        Ok(Transactions { client, enabled })
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub async fn begin(&self) -> Result<Transaction, Error> {
+        if !self.enabled {
+            return Ok(Transaction { session: None });
+        }
+
+        let mut session = self.client.start_session(None).await?;
+        session.start_transaction(None).await?;
+
+        Ok(Transaction {
+            session: Some(session),
+        })
+    }
+}
+
+/// A session with a running transaction. On standalone deployments there is no session and the
+/// writes are applied one by one instead. Dropping a transaction without committing it aborts it.
+pub struct Transaction {
+    session: Option<ClientSession>,
+}
+
+impl Transaction {
+    pub fn session(&mut self) -> Option<&mut ClientSession> {
+        self.session.as_mut()
+    }
+
+    pub async fn commit(self) -> Result<(), Error> {
+        if let Some(mut d) = self.session {
+            d.commit_transaction().await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn abort(self) -> Result<(), Error> {
+        if let Some(mut d) = self.session {
+            d.abort_transaction().await?;
+        }
+
+        Ok(())
+    }
+}
+
+pub async fn insert_one<T: Serialize>(
+    collection: &Collection<T>,
+    document: T,
+    session: Option<&mut ClientSession>,
+) -> Result<InsertOneResult, Error> {
+    match session {
+        Some(s) => collection.insert_one_with_session(document, None, s).await,
+        None => collection.insert_one(document, None).await,
+    }
+}
+
+pub async fn find_one<T: DeserializeOwned + Unpin + Send + Sync>(
+    collection: &Collection<T>,
+    filter: Document,
+    session: Option<&mut ClientSession>,
+) -> Result<Option<T>, Error> {
+    match session {
+        Some(s) => collection.find_one_with_session(filter, None, s).await,
+        None => collection.find_one(filter, None).await,
+    }
+}
+
+pub async fn count_documents<T>(
+    collection: &Collection<T>,
+    filter: Document,
+    session: Option<&mut ClientSession>,
+) -> Result<u64, Error> {
+    match session {
+        Some(s) => {
+            collection
+                .count_documents_with_session(filter, None, s)
+                .await
+        }
+        None => collection.count_documents(filter, None).await,
+    }
+}
+
+/// Update the first document that matches the filter with an update document or an aggregation
+/// pipeline
+pub async fn update_one<T>(
+    collection: &Collection<T>,
+    filter: Document,
+    update: impl Into<UpdateModifications>,
+    session: Option<&mut ClientSession>,
+) -> Result<UpdateResult, Error> {
+    match session {
+        Some(s) => {
+            collection
+                .update_one_with_session(filter, update, None, s)
+                .await
+        }
+        None => collection.update_one(filter, update, None).await,
+    }
+}
+
+/// Replace the document that matches the filter, or insert it when there is none
+pub async fn upsert_one<T: Serialize>(
+    collection: &Collection<T>,
+    filter: Document,
+    replacement: T,
+    session: Option<&mut ClientSession>,
+) -> Result<UpdateResult, Error> {
+    let options = ReplaceOptions::builder().upsert(true).build();
+    match session {
+        Some(s) => {
+            collection
+                .replace_one_with_session(filter, replacement, options, s)
+                .await
+        }
+        None => collection.replace_one(filter, replacement, options).await,
+    }
+}
+
+pub async fn update_many<T>(
+    collection: &Collection<T>,
+    filter: Document,
+    update: Document,
+    session: Option<&mut ClientSession>,
+) -> Result<UpdateResult, Error> {
+    match session {
+        Some(s) => {
+            collection
+                .update_many_with_session(filter, update, None, s)
+                .await
+        }
+        None => collection.update_many(filter, update, None).await,
+    }
+}
+
+pub async fn delete_one<T>(
+    collection: &Collection<T>,
+    filter: Document,
+    session: Option<&mut ClientSession>,
+) -> Result<DeleteResult, Error> {
+    match session {
+        Some(s) => collection.delete_one_with_session(filter, None, s).await,
+        None => collection.delete_one(filter, None).await,
+    }
+}
+
+pub async fn delete_many<T>(
+    collection: &Collection<T>,
+    filter: Document,
+    session: Option<&mut ClientSession>,
+) -> Result<DeleteResult, Error> {
+    match session {
+        Some(s) => collection.delete_many_with_session(filter, None, s).await,
+        None => collection.delete_many(filter, None).await,
+    }
+}
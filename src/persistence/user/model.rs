@@ -1 +1,3 @@
+pub mod role_assignment;
 pub mod user;
+pub mod user_filter;
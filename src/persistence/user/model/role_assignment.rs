@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A role that is only granted to a user within a specific window of time.
+/// Timestamps are stored as RFC 3339 strings.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RoleAssignment {
+    #[serde(rename(serialize = "roleId", deserialize = "roleId"))]
+    pub role_id: String,
+    #[serde(rename(serialize = "validFrom", deserialize = "validFrom"))]
+    pub valid_from: Option<String>,
+    #[serde(rename(serialize = "validUntil", deserialize = "validUntil"))]
+    pub valid_until: String,
+    pub reason: String,
+    #[serde(rename(serialize = "assignedBy", deserialize = "assignedBy"))]
+    pub assigned_by: String,
+}
+
+impl RoleAssignment {
+    /// Check whether the assignment grants its role at the given moment.
+    /// Assignments with timestamps that cannot be parsed are never active.
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        let started = match &self.valid_from {
+            Some(d) => matches!(parse_timestamp(d), Some(x) if x <= now),
+            None => true,
+        };
+
+        started && !self.has_lapsed(now)
+    }
+
+    /// Check whether the validity window of the assignment has ended
+    pub fn has_lapsed(&self, now: DateTime<Utc>) -> bool {
+        match parse_timestamp(&self.valid_until) {
+            Some(d) => d <= now,
+            None => true,
+        }
+    }
+}
+
+/// Parse an RFC 3339 timestamp into a UTC date and time
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|d| d.with_timezone(&Utc))
+}
@@ -1,4 +1,11 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::persistence::organization::model::organization::default_organization;
+
+use super::role_assignment::RoleAssignment;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct User {
@@ -13,9 +20,32 @@ pub struct User {
     #[serde(rename(serialize = "lastName", deserialize = "lastName"))]
     pub last_name: String,
     pub enabled: bool,
+    #[serde(
+        rename(serialize = "platformAdmin", deserialize = "platformAdmin"),
+        default
+    )]
+    pub platform_admin: bool,
+    /// Service accounts cannot sign in with a password and authenticate with API keys instead
+    #[serde(
+        rename(serialize = "serviceAccount", deserialize = "serviceAccount"),
+        default
+    )]
+    pub service_account: bool,
     pub roles: Vec<String>,
+    #[serde(
+        rename(serialize = "roleAssignments", deserialize = "roleAssignments"),
+        default
+    )]
+    pub role_assignments: Vec<RoleAssignment>,
     #[serde(rename(serialize = "createdAt", deserialize = "createdAt"))]
     pub created_at: String,
     #[serde(rename(serialize = "lastActive", deserialize = "lastActive"))]
     pub last_active: String,
+    #[serde(default)]
+    pub attributes: HashMap<String, Value>,
+    #[serde(default = "default_organization")]
+    pub organization: String,
+    /// Incremented on every update, used for optimistic concurrency
+    #[serde(default)]
+    pub version: i64,
 }
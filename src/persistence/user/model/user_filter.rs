@@ -0,0 +1,47 @@
+use mongodb::bson::{doc, Document};
+
+use crate::persistence::page::prefix_filter;
+
+/// The criteria that users can be listed by. Timestamps use the format in which `createdAt` is
+/// stored.
+#[derive(Default)]
+pub struct UserFilter {
+    pub username: Option<String>,
+    pub email_address: Option<String>,
+    pub enabled: Option<bool>,
+    pub role: Option<String>,
+    pub created_from: Option<String>,
+    pub created_to: Option<String>,
+}
+
+impl UserFilter {
+    pub fn to_document(&self, organization: &str) -> Document {
+        let mut filter = doc! { "organization": organization };
+
+        if let Some(d) = &self.username {
+            filter.insert("username", prefix_filter(d));
+        }
+        if let Some(d) = &self.email_address {
+            filter.insert("emailAddress", prefix_filter(d));
+        }
+        if let Some(d) = self.enabled {
+            filter.insert("enabled", d);
+        }
+        if let Some(d) = &self.role {
+            filter.insert("roles", d);
+        }
+
+        let mut created = Document::new();
+        if let Some(d) = &self.created_from {
+            created.insert("$gte", d);
+        }
+        if let Some(d) = &self.created_to {
+            created.insert("$lte", d);
+        }
+        if !created.is_empty() {
+            filter.insert("createdAt", created);
+        }
+
+        filter
+    }
+}
@@ -1,7 +1,18 @@
 use futures::TryStreamExt;
-use mongodb::{bson::doc, error::Error, Database};
+use mongodb::{
+    bson::{doc, from_document, Document},
+    error::Error,
+    options::{FindOptions, IndexOptions},
+    ClientSession, Database, IndexModel,
+};
 
-use super::model::user::User;
+use crate::persistence::{
+    page::{find_page, Page, PageRequest},
+    transaction,
+    version::version_filter,
+};
+
+use super::model::{role_assignment::RoleAssignment, user::User, user_filter::UserFilter};
 
 #[derive(Clone)]
 pub struct UserRepository {
@@ -15,9 +26,15 @@ impl UserRepository {
         }
     }
 
-    pub async fn create(&self, user: User, db: &Database) -> Result<Option<User>, Error> {
+    pub async fn create(
+        &self,
+        user: User,
+        db: &Database,
+        mut session: Option<&mut ClientSession>,
+    ) -> Result<Option<User>, Error> {
+        let organization = user.organization.clone();
         let collection = db.collection::<User>(&self.collection);
-        let res = match collection.insert_one(user, None).await {
+        let res = match transaction::insert_one(&collection, user, session.as_deref_mut()).await {
             Ok(d) => d,
             Err(e) => return Err(e),
         };
@@ -27,24 +44,151 @@ impl UserRepository {
             None => return Ok(None),
         };
 
-        self.find_by_uuid(db, new_uuid).await
+        let filter = doc! { "_id": new_uuid, "organization": organization };
+        transaction::find_one(&collection, filter, session).await
+    }
+
+    pub async fn find_all(&self, db: &Database, organization: &str) -> Result<Vec<User>, Error> {
+        let filter = doc! { "organization": organization };
+        let cursor = match db
+            .collection::<User>(&self.collection)
+            .find(filter, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        cursor.try_collect().await
+    }
+
+    pub async fn find_page(
+        &self,
+        db: &Database,
+        organization: &str,
+        filter: &UserFilter,
+        page: &PageRequest,
+    ) -> Result<Page<User>, Error> {
+        find_page(
+            db.collection::<User>(&self.collection),
+            filter.to_document(organization),
+            page,
+        )
+        .await
+    }
+
+    /// Create the text index that backs user searches, if it does not exist yet
+    pub async fn create_search_index(&self, db: &Database) -> Result<(), Error> {
+        let options = IndexOptions::builder()
+            .name(String::from("user_search"))
+            .weights(doc! { "username": 10, "emailAddress": 5, "firstName": 3, "lastName": 3 })
+            .default_language(String::from("none"))
+            .build();
+        let index = IndexModel::builder()
+            .keys(doc! {
+                "organization": 1,
+                "username": "text",
+                "emailAddress": "text",
+                "firstName": "text",
+                "lastName": "text",
+            })
+            .options(options)
+            .build();
+
+        db.collection::<User>(&self.collection)
+            .create_index(index, None)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Create the indexes that keep usernames and email addresses unique within an organization
+    pub async fn create_unique_indexes(&self, db: &Database) -> Result<(), Error> {
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! { "organization": 1, "username": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "organization": 1, "emailAddress": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+        ];
+
+        db.collection::<User>(&self.collection)
+            .create_indexes(indexes, None)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Find the users that contain one of the words of the query, together with their text score
+    pub async fn search_text(
+        &self,
+        db: &Database,
+        organization: &str,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<(User, f64)>, Error> {
+        let filter = doc! { "organization": organization, "$text": { "$search": query } };
+        let score = doc! { "score": { "$meta": "textScore" } };
+        let options = FindOptions::builder()
+            .projection(score.clone())
+            .sort(score)
+            .limit(limit)
+            .build();
+
+        let cursor = match db
+            .collection::<Document>(&self.collection)
+            .find(filter, options)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+        let documents: Vec<Document> = cursor.try_collect().await?;
+
+        let mut res = vec![];
+        for mut document in documents {
+            let score = match document.remove("score") {
+                Some(d) => d.as_f64().unwrap_or_default(),
+                None => 0.0,
+            };
+            res.push((from_document::<User>(document)?, score));
+        }
+
+        Ok(res)
     }
 
-    pub async fn find_all(&self, db: &Database) -> Result<Vec<User>, Error> {
+    /// Find the users that have a word starting with one of the given prefixes, ignoring case
+    pub async fn find_by_word_prefixes(
+        &self,
+        db: &Database,
+        organization: &str,
+        prefixes: &[String],
+        limit: i64,
+    ) -> Result<Vec<User>, Error> {
+        let filter = word_prefix_filter(organization, prefixes);
+        let options = FindOptions::builder().limit(limit).build();
         let cursor = match db
             .collection::<User>(&self.collection)
-            .find(None, None)
+            .find(filter, options)
             .await
         {
             Ok(d) => d,
             Err(e) => return Err(e),
         };
 
-        Ok(cursor.try_collect().await.unwrap_or_else(|_| vec![]))
+        cursor.try_collect().await
     }
 
-    pub async fn find_by_uuid(&self, db: &Database, uuid: &str) -> Result<Option<User>, Error> {
-        let filter = doc! { "_id": uuid};
+    pub async fn find_by_uuid(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<Option<User>, Error> {
+        let filter = doc! { "_id": uuid, "organization": organization };
         let cursor = match db
             .collection::<User>(&self.collection)
             .find_one(filter, None)
@@ -60,10 +204,11 @@ impl UserRepository {
     pub async fn find_by_username(
         &self,
         db: &Database,
+        organization: &str,
         username: &str,
     ) -> Result<Option<User>, Error> {
         let collection = db.collection::<User>(&self.collection);
-        let qry = doc! { "username": username};
+        let qry = doc! { "username": username, "organization": organization };
 
         let res = match collection.find_one(qry, None).await {
             Ok(d) => d,
@@ -76,10 +221,11 @@ impl UserRepository {
     pub async fn find_by_email_address(
         &self,
         db: &Database,
+        organization: &str,
         email_address: &str,
     ) -> Result<Option<User>, Error> {
         let collection = db.collection::<User>(&self.collection);
-        let filter = doc! { "emailAddress": email_address};
+        let filter = doc! { "emailAddress": email_address, "organization": organization };
 
         let res = match collection.find_one(filter, None).await {
             Ok(d) => d,
@@ -89,41 +235,69 @@ impl UserRepository {
         Ok(res)
     }
 
-    pub async fn find_by_role_id(&self, db: &Database, role_id: &str) -> Result<Vec<User>, Error> {
-        let filter = doc! { "roles": role_id };
-
-        let cursor = match db
-            .collection::<User>(&self.collection)
-            .find(filter, None)
+    /// Only updates the user if it is still at the version that it was read at. Returns `None`
+    /// when the user does not exist or was changed in the meantime.
+    pub async fn update(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        user: User,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Option<User>, Error> {
+        self.update_fields(db, organization, uuid, user, false, session)
             .await
-        {
-            Ok(d) => d,
-            Err(e) => return Err(e),
-        };
+    }
 
-        Ok(cursor.try_collect().await.unwrap_or_else(|_| vec![]))
+    /// Update a user together with its password hash, so that both change at once
+    pub async fn update_with_password(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        user: User,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Option<User>, Error> {
+        self.update_fields(db, organization, uuid, user, true, session)
+            .await
     }
 
-    pub async fn update(
+    async fn update_fields(
         &self,
         db: &Database,
+        organization: &str,
         uuid: &str,
         user: User,
+        with_password: bool,
+        mut session: Option<&mut ClientSession>,
     ) -> Result<Option<User>, Error> {
         let collection = db.collection::<User>(&self.collection);
-        let filter = doc! { "_id": uuid };
+        let filter = doc! {
+            "_id": uuid,
+            "organization": organization,
+            "version": version_filter(user.version)
+        };
+        let mut fields = doc! {
+            "username": user.username,
+            "emailAddress": user.email_address,
+            "firstName": user.first_name,
+            "lastName": user.last_name,
+            "enabled": user.enabled,
+            "platformAdmin": user.platform_admin,
+            "roles": mongodb::bson::to_bson(&user.roles).unwrap(),
+            "attributes": mongodb::bson::to_bson(&user.attributes).unwrap()
+        };
+        if with_password {
+            fields.insert("password", user.password);
+        }
         let update = doc! {
-            "$set": {
-                "username": user.username,
-                "emailAddress": user.email_address,
-                "firstName": user.first_name,
-                "lastName": user.last_name,
-                "enabled": user.enabled,
-                "roles": mongodb::bson::to_bson(&user.roles).unwrap()
-            }
+            "$inc": { "version": 1_i64 },
+            "$set": fields
         };
 
-        let res = match collection.update_one(filter, update, None).await {
+        let res = match transaction::update_one(&collection, filter, update, session.as_deref_mut())
+            .await
+        {
             Ok(d) => d,
             Err(e) => return Err(e),
         };
@@ -131,7 +305,8 @@ impl UserRepository {
         let matched_count = res.matched_count;
 
         if matched_count > 0 {
-            self.find_by_uuid(db, uuid).await
+            let filter = doc! { "_id": uuid, "organization": organization };
+            transaction::find_one(&collection, filter, session).await
         } else {
             Ok(None)
         }
@@ -140,15 +315,17 @@ impl UserRepository {
     pub async fn update_password(
         &self,
         db: &Database,
+        organization: &str,
         uuid: &str,
         password: &str,
     ) -> Result<Option<User>, Error> {
         let collection = db.collection::<User>(&self.collection);
-        let filter = doc! {"_id": uuid};
+        let filter = doc! {"_id": uuid, "organization": organization};
 
-        let update = doc! {"$set": {
-            "password": password,
-        }};
+        let update = doc! {
+            "$set": { "password": password },
+            "$inc": { "version": 1_i64 },
+        };
 
         let res = match collection.update_one(filter, update, None).await {
             Ok(d) => d,
@@ -158,20 +335,46 @@ impl UserRepository {
         let count = res.matched_count;
 
         if count > 0 {
-            self.find_by_uuid(db, uuid).await
+            self.find_by_uuid(db, organization, uuid).await
         } else {
             Ok(None)
         }
     }
 
+    /// Make a user a platform administrator, unless it already is one. Returns `None` when the
+    /// user does not exist.
+    pub async fn grant_platform_admin(
+        &self,
+        db: &Database,
+        organization: &str,
+        username: &str,
+    ) -> Result<Option<User>, Error> {
+        let collection = db.collection::<User>(&self.collection);
+        let filter = doc! {
+            "username": username,
+            "organization": organization,
+            "platformAdmin": { "$ne": true }
+        };
+
+        let update = doc! {
+            "$set": { "platformAdmin": true },
+            "$inc": { "version": 1_i64 },
+        };
+
+        collection.update_one(filter, update, None).await?;
+
+        self.find_by_username(db, organization, username).await
+    }
+
     pub async fn update_last_active(
         &self,
         db: &Database,
+        organization: &str,
         uuid: &str,
         last_active: &str,
     ) -> Result<Option<User>, Error> {
         let collection = db.collection::<User>(&self.collection);
-        let filter = doc! {"_id": uuid};
+        let filter = doc! {"_id": uuid, "organization": organization};
 
         let update = doc! {"$set": {
             "lastActive": last_active,
@@ -185,23 +388,362 @@ impl UserRepository {
         let count = res.matched_count;
 
         if count > 0 {
-            self.find_by_uuid(db, uuid).await
+            self.find_by_uuid(db, organization, uuid).await
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Add a time-bound role assignment, replacing any earlier assignment of the same role
+    pub async fn add_role_assignment(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        assignment: RoleAssignment,
+        mut session: Option<&mut ClientSession>,
+    ) -> Result<Option<User>, Error> {
+        let collection = db.collection::<User>(&self.collection);
+        let filter = doc! {"_id": uuid, "organization": organization};
+        let role_id = assignment.role_id.clone();
+
+        let assignment = match mongodb::bson::to_bson(&assignment) {
+            Ok(d) => d,
+            Err(e) => return Err(Error::from(e)),
+        };
+
+        // A single pipeline update, so that the assignment is never missing in between
+        let update = vec![doc! {"$set": {
+            "roleAssignments": { "$concatArrays": [
+                { "$filter": {
+                    "input": { "$ifNull": ["$roleAssignments", []] },
+                    "cond": { "$ne": ["$$this.roleId", &role_id] },
+                } },
+                [{ "$literal": assignment }],
+            ] },
+            "version": { "$add": [{ "$ifNull": ["$version", 0_i64] }, 1_i64] },
+        }}];
+
+        let res = match transaction::update_one(&collection, filter, update, session.as_deref_mut())
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        if res.matched_count > 0 {
+            let filter = doc! { "_id": uuid, "organization": organization };
+            transaction::find_one(&collection, filter, session).await
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub async fn remove_role_assignment(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        role_id: &str,
+        mut session: Option<&mut ClientSession>,
+    ) -> Result<Option<User>, Error> {
+        let collection = db.collection::<User>(&self.collection);
+        let filter = doc! {"_id": uuid, "organization": organization};
+        let update = doc! {
+            "$pull": { "roleAssignments": { "roleId": role_id } },
+            "$inc": { "version": 1_i64 },
+        };
+
+        let res = match transaction::update_one(&collection, filter, update, session.as_deref_mut())
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        if res.matched_count > 0 {
+            let filter = doc! { "_id": uuid, "organization": organization };
+            transaction::find_one(&collection, filter, session).await
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Give a user a role directly. Adding a role that the user already holds has no effect.
+    pub async fn add_role(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        role_id: &str,
+    ) -> Result<Option<User>, Error> {
+        self.modify_roles(
+            db,
+            organization,
+            uuid,
+            doc! { "$addToSet": { "roles": role_id } },
+        )
+        .await
+    }
+
+    /// Take a role that a user holds directly away from the user
+    pub async fn remove_role(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        role_id: &str,
+    ) -> Result<Option<User>, Error> {
+        self.modify_roles(
+            db,
+            organization,
+            uuid,
+            doc! { "$pull": { "roles": role_id } },
+        )
+        .await
+    }
+
+    async fn modify_roles(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        mut update: Document,
+    ) -> Result<Option<User>, Error> {
+        let collection = db.collection::<User>(&self.collection);
+        let filter = doc! { "_id": uuid, "organization": organization };
+        update.insert("$inc", doc! { "version": 1_i64 });
+
+        let res = match collection.update_one(filter, update, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        if res.matched_count > 0 {
+            self.find_by_uuid(db, organization, uuid).await
         } else {
             Ok(None)
         }
     }
 
-    pub async fn delete(&self, db: &Database, uuid: &str) -> Result<u64, Error> {
-        let qry = doc! { "_id": uuid };
+    /// Remove a role from every user of an organization that holds it directly
+    pub async fn pull_role(
+        &self,
+        db: &Database,
+        organization: &str,
+        role_id: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        let collection = db.collection::<User>(&self.collection);
+        let filter = doc! { "organization": organization, "roles": role_id };
+        let update = doc! { "$pull": { "roles": role_id }, "$inc": { "version": 1_i64 } };
+
+        let res = match transaction::update_many(&collection, filter, update, session).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(res.modified_count)
+    }
+
+    /// Remove a time-bound role assignment that has lapsed, unless it was replaced in the meantime
+    /// by an assignment of the same role with another validity window. Returns the number of
+    /// users that changed.
+    pub async fn remove_lapsed_role_assignment(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        role_id: &str,
+        valid_until: &str,
+    ) -> Result<u64, Error> {
+        let collection = db.collection::<User>(&self.collection);
+        let assignment = doc! {"roleId": role_id, "validUntil": valid_until};
+        let filter = doc! {
+            "_id": uuid,
+            "organization": organization,
+            "roleAssignments": { "$elemMatch": assignment.clone() },
+        };
+        let update = doc! {
+            "$pull": { "roleAssignments": assignment },
+            "$inc": { "version": 1_i64 },
+        };
+
+        let res = match collection.update_one(filter, update, None).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(res.modified_count)
+    }
+
+    /// Remove the time-bound assignments of a role from every user in the organization
+    pub async fn remove_role_assignments_by_role(
+        &self,
+        db: &Database,
+        organization: &str,
+        role_id: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        let collection = db.collection::<User>(&self.collection);
+        let filter = doc! {"organization": organization, "roleAssignments.roleId": role_id};
+        let update = doc! {
+            "$pull": { "roleAssignments": { "roleId": role_id } },
+            "$inc": { "version": 1_i64 },
+        };
+
+        let res = match transaction::update_many(&collection, filter, update, session).await {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(res.modified_count)
+    }
+
+    /// Find the users of every organization that have at least one time-bound role assignment
+    pub async fn find_with_role_assignments(&self, db: &Database) -> Result<Vec<User>, Error> {
+        let filter = doc! {"roleAssignments.0": { "$exists": true }};
         let cursor = match db
             .collection::<User>(&self.collection)
-            .delete_one(qry, None)
+            .find(filter, None)
             .await
         {
             Ok(d) => d,
             Err(e) => return Err(e),
         };
 
+        cursor.try_collect().await
+    }
+
+    /// Deletes the user, only if it is still at `version` when one is given
+    pub async fn delete(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        version: Option<i64>,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        let mut qry = doc! { "_id": uuid, "organization": organization };
+        if let Some(d) = version {
+            qry.insert("version", version_filter(d));
+        }
+        let cursor =
+            match transaction::delete_one(&db.collection::<User>(&self.collection), qry, session)
+                .await
+            {
+                Ok(d) => d,
+                Err(e) => return Err(e),
+            };
+
         Ok(cursor.deleted_count)
     }
+
+    /// Assign the default organization to users that were created before organizations existed
+    pub async fn assign_default_organization(
+        &self,
+        db: &Database,
+        organization: &str,
+    ) -> Result<u64, Error> {
+        let filter = doc! { "organization": { "$exists": false } };
+        let update = doc! { "$set": { "organization": organization } };
+
+        let res = match db
+            .collection::<User>(&self.collection)
+            .update_many(filter, update, None)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return Err(e),
+        };
+
+        Ok(res.modified_count)
+    }
+}
+
+/// Match the users of an organization with a word in one of the searched fields that starts with
+/// one of the prefixes, ignoring case. Words are separated by anything that is not alphanumeric.
+fn word_prefix_filter(organization: &str, prefixes: &[String]) -> Document {
+    let mut conditions = vec![];
+    for prefix in prefixes {
+        let regex = doc! {
+            "$regex": format!("(^|[^[:alnum:]]){}", regex::escape(prefix)),
+            "$options": "i",
+        };
+        for field in ["username", "emailAddress", "firstName", "lastName"] {
+            conditions.push(doc! { field: regex.clone() });
+        }
+    }
+
+    doc! { "organization": organization, "$or": conditions }
+}
+
+#[cfg(test)]
+mod tests {
+    use mongodb::bson::Bson;
+
+    use super::*;
+
+    fn conditions(filter: &Document) -> Vec<(String, String)> {
+        filter
+            .get_array("$or")
+            .unwrap()
+            .iter()
+            .map(|c| {
+                let c = c.as_document().unwrap();
+                let (field, regex) = c.iter().next().unwrap();
+                let regex = regex.as_document().unwrap();
+                assert_eq!(regex.get_str("$options").unwrap(), "i");
+                (
+                    field.clone(),
+                    String::from(regex.get_str("$regex").unwrap()),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn word_prefix_filter_is_scoped_to_the_organization() {
+        let filter = word_prefix_filter("acme", &[String::from("jo")]);
+
+        assert_eq!(filter.get("organization"), Some(&Bson::from("acme")));
+    }
+
+    #[test]
+    fn word_prefix_filter_searches_every_field_for_every_prefix() {
+        let filter = word_prefix_filter("acme", &[String::from("jo"), String::from("sm")]);
+
+        assert_eq!(
+            conditions(&filter),
+            vec![
+                (String::from("username"), String::from("(^|[^[:alnum:]])jo")),
+                (
+                    String::from("emailAddress"),
+                    String::from("(^|[^[:alnum:]])jo")
+                ),
+                (
+                    String::from("firstName"),
+                    String::from("(^|[^[:alnum:]])jo")
+                ),
+                (String::from("lastName"), String::from("(^|[^[:alnum:]])jo")),
+                (String::from("username"), String::from("(^|[^[:alnum:]])sm")),
+                (
+                    String::from("emailAddress"),
+                    String::from("(^|[^[:alnum:]])sm")
+                ),
+                (
+                    String::from("firstName"),
+                    String::from("(^|[^[:alnum:]])sm")
+                ),
+                (String::from("lastName"), String::from("(^|[^[:alnum:]])sm")),
+            ]
+        );
+    }
+
+    #[test]
+    fn word_prefix_filter_escapes_regex_characters() {
+        let filter = word_prefix_filter("acme", &[String::from("a.")]);
+
+        assert_eq!(conditions(&filter)[0].1, "(^|[^[:alnum:]])a\\.");
+    }
 }
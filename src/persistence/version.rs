@@ -0,0 +1,11 @@
+use mongodb::bson::{doc, Bson};
+
+/// Match documents that are still at a version. Documents that were written before versions
+/// existed have no version field and count as version 0.
+pub fn version_filter(version: i64) -> Bson {
+    if version == 0 {
+        Bson::Document(doc! { "$in": [0_i64, Bson::Null] })
+    } else {
+        Bson::Int64(version)
+    }
+}
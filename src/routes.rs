@@ -1,47 +1,163 @@
-use actix_web::web;
+use std::collections::{HashMap, HashSet};
+
+use actix_web::{web, HttpMessage};
+use chrono::Utc;
+use jsonwebtoken::errors::ErrorKind;
 use jsonwebtoken::{DecodingKey, Validation};
 use mongodb::error::Error;
 use mongodb::Database;
+use serde::Deserialize;
+use serde_json::Value;
+use uuid::Uuid;
 
 use crate::configuration::app_data_pool::AppDataPool;
+use crate::errors::app_error::AppError;
+use crate::persistence::access_request::model::access_request::AccessRequest;
+use crate::persistence::access_review::model::access_review::AccessReview;
+use crate::persistence::api_key::model::api_key::ApiKey;
+use crate::persistence::constraint::model::constraint::Constraint;
+use crate::persistence::event::model::event::{Event, IMPERSONATED_REQUEST};
+use crate::persistence::group::model::group::Group;
+use crate::persistence::login::model::login_attempt::LoginAttempt;
+use crate::persistence::organization::model::organization::{Organization, DEFAULT_ORGANIZATION};
 use crate::persistence::permission::model::permission::Permission;
+use crate::persistence::policy::model::policy::{Effect, Policy};
+use crate::persistence::relationship::model::namespace::Namespace;
+use crate::persistence::relationship::model::relation_tuple::RelationTuple;
 use crate::persistence::role::model::role::Role;
+use crate::persistence::session::model::session::Session;
 use crate::persistence::user::model::user::User;
+use crate::routes::user::dto::role_assignment::RoleAssignment as RoleAssignmentDto;
+use crate::routes::user::dto::session::Session as SessionDto;
 use crate::routes::user::dto::user::User as UserDto;
+use crate::services::api_key::api_key_service::{hash_key, parse_prefix};
+use crate::services::constraint::constraint_service::conflicting_roles;
+use crate::services::group::group_service::GroupService;
 use crate::services::permission::permission_service::PermissionService;
+use crate::services::permission::system_permission::SystemPermission;
+use crate::services::policy::policy_service::build_context;
 use crate::services::role::role_service::RoleService;
 
+use self::access_request::access_request_route;
+use self::access_request::dto::access_request::AccessRequest as AccessRequestDto;
+use self::access_review::access_review_route;
+use self::access_review::dto::access_review::AccessReview as AccessReviewDto;
 use self::actuator::actuator_route;
+use self::admin::admin_route;
+use self::api_key::api_key_route;
+use self::api_key::dto::api_key::ApiKey as ApiKeyDto;
 use self::authentication::authentication_route;
 use self::authentication::dto::authentication_response::Claims;
+use self::authentication::dto::login_attempt::LoginAttempt as LoginAttemptDto;
+use self::authorization::authorization_route;
+use self::authorization::dto::authorization_trace::{
+    AuthorizationTrace, PermissionTrace, RoleTrace,
+};
+use self::authorization::dto::denial_reason::DenialReason;
+use self::authorization::dto::offending_grant::{GrantType, OffendingGrant};
+use self::constraint::constraint_route;
+use self::constraint::dto::constraint::Constraint as ConstraintDto;
+use self::event::dto::event::Event as EventDto;
+use self::event::event_route;
+use self::group::dto::group::Group as GroupDto;
+use self::group::group_route;
+use self::organization::dto::organization::Organization as OrganizationDto;
+use self::organization::organization_route;
 use self::permission::dto::permission::Permission as PermissionDto;
 use self::permission::permission_route;
+use self::policy::dto::policy::Policy as PolicyDto;
+use self::policy::policy_route;
+use self::relationship::dto::namespace::Namespace as NamespaceDto;
+use self::relationship::dto::tuple::Tuple as TupleDto;
+use self::relationship::relationship_route;
 use self::role::dto::role::Role as RoleDto;
 use self::role::role_route;
+use self::seed::seed_route;
+use self::service_account::service_account_route;
 use self::user::user_route;
 
+pub mod access_request;
+pub mod access_review;
 pub mod actuator;
+pub mod admin;
+pub mod api_key;
 pub mod authentication;
+pub mod authorization;
+pub mod constraint;
+pub mod etag;
+pub mod event;
+pub mod group;
+pub mod merge_patch;
+pub mod organization;
+pub mod pagination;
 pub mod permission;
+pub mod policy;
+pub mod relationship;
 pub mod role;
+pub mod seed;
+pub mod service_account;
 pub mod user;
+pub mod validation;
 
 pub const EMAIL_REGEX_PATTERN: &str =
     r"^([a-z0-9_+]([a-z0-9_+.]*[a-z0-9_+])?)@([a-z0-9]+([\-.]{1}[a-z0-9]+)*\.[a-z]{2,6})";
 
+/// The cookie that holds the token in cookie mode
+pub const TOKEN_COOKIE: &str = "token";
+/// The cookie that holds the CSRF token in cookie mode, which is readable by scripts
+pub const CSRF_COOKIE: &str = "csrf_token";
+/// The header that has to repeat the CSRF cookie on requests that change state
+pub const CSRF_HEADER: &str = "X-CSRF-Token";
+
+pub const ORGANIZATION_ID_REGEX_PATTERN: &str = r"^[a-z0-9][a-z0-9\-]{0,62}$";
+
+/// Path of a single user, role or permission. The routes of an organization can be mounted under
+/// `/orgs/{org}`, so the uuid has to be extracted by name.
+#[derive(Deserialize)]
+pub struct EntityPath {
+    pub uuid: String,
+}
+
 pub struct Routes {}
 
 impl Routes {
     pub fn configure_routes(cfg: &mut web::ServiceConfig) {
         cfg.service(web::scope("/actuators").service(actuator_route::get_status));
 
+        Routes::configure_organization_routes(cfg);
+        cfg.service(web::scope("/orgs/{org}").configure(Routes::configure_organization_routes));
+
+        cfg.service(
+            web::scope("/organizations")
+                .service(organization_route::create_organization)
+                .service(organization_route::get_all_organizations)
+                .service(organization_route::find_by_id)
+                .service(organization_route::update_organization)
+                .service(organization_route::delete_organization),
+        );
+
+        cfg.service(web::scope("/seed").service(seed_route::get_drift));
+    }
+
+    /// Routes that operate on the data of a single organization. They are available both at the
+    /// root, where they use the organization of the caller, and under `/orgs/{org}`.
+    fn configure_organization_routes(cfg: &mut web::ServiceConfig) {
         cfg.service(
             web::scope("/users")
                 .service(user_route::create_user)
                 .service(user_route::find_all_users)
+                .service(user_route::search_users)
                 .service(user_route::find_by_uuid)
                 .service(user_route::update_by_uuid)
+                .service(user_route::patch_by_uuid)
                 .service(user_route::update_password)
+                .service(user_route::add_role)
+                .service(user_route::remove_role)
+                .service(user_route::assign_role)
+                .service(user_route::revoke_role_assignment)
+                .service(user_route::impersonate)
+                .service(user_route::get_sessions)
+                .service(user_route::revoke_session)
                 .service(user_route::delete_by_uuid),
         );
 
@@ -51,15 +167,35 @@ impl Routes {
                 .service(role_route::get_all_roles)
                 .service(role_route::get_role_by_id)
                 .service(role_route::update_role)
+                .service(role_route::patch_role)
+                .service(role_route::add_permission)
+                .service(role_route::remove_permission)
                 .service(role_route::delete_role),
         );
 
+        cfg.service(
+            web::scope("/groups")
+                .service(group_route::create_group)
+                .service(group_route::get_all_groups)
+                .service(group_route::get_group_by_id)
+                .service(group_route::update_group)
+                .service(group_route::delete_group)
+                .service(group_route::add_member)
+                .service(group_route::remove_member)
+                .service(group_route::add_nested_group)
+                .service(group_route::remove_nested_group)
+                .service(group_route::add_role)
+                .service(group_route::remove_role),
+        );
+
         cfg.service(
             web::scope("/permissions")
                 .service(permission_route::create_permission)
                 .service(permission_route::get_all_permissions)
+                .service(permission_route::get_system_permissions)
                 .service(permission_route::find_by_uuid)
                 .service(permission_route::update_permission)
+                .service(permission_route::patch_permission)
                 .service(permission_route::delete_permission),
         );
 
@@ -67,9 +203,90 @@ impl Routes {
             web::scope("/authentication")
                 .service(authentication_route::authenticate)
                 .service(authentication_route::register)
+                .service(authentication_route::logout)
                 .service(authentication_route::get_current_user)
                 .service(authentication_route::update_current_user)
-                .service(authentication_route::update_current_user_password),
+                .service(authentication_route::update_current_user_password)
+                .service(authentication_route::get_current_sessions)
+                .service(authentication_route::revoke_current_session)
+                .service(authentication_route::get_current_logins),
+        );
+
+        cfg.service(
+            web::scope("/access-requests")
+                .service(access_request_route::create_access_request)
+                .service(access_request_route::get_my_access_requests)
+                .service(access_request_route::get_pending_access_requests)
+                .service(access_request_route::get_access_request_by_id)
+                .service(access_request_route::approve_access_request)
+                .service(access_request_route::deny_access_request)
+                .service(access_request_route::revoke_access_request),
+        );
+
+        cfg.service(
+            web::scope("/service-accounts")
+                .service(service_account_route::create_service_account)
+                .service(service_account_route::get_all_service_accounts),
+        );
+
+        cfg.service(
+            web::scope("/api-keys")
+                .service(api_key_route::create_api_key)
+                .service(api_key_route::get_api_keys)
+                .service(api_key_route::revoke_api_key),
+        );
+
+        cfg.service(
+            web::scope("/access-reviews")
+                .service(access_review_route::create_access_review)
+                .service(access_review_route::get_all_access_reviews)
+                .service(access_review_route::get_access_review_by_id)
+                .service(access_review_route::approve_item)
+                .service(access_review_route::revoke_item)
+                .service(access_review_route::close_access_review)
+                .service(access_review_route::get_report),
+        );
+
+        cfg.service(
+            web::scope("/constraints")
+                .service(constraint_route::create_constraint)
+                .service(constraint_route::get_all_constraints)
+                .service(constraint_route::get_violations)
+                .service(constraint_route::get_constraint_by_id)
+                .service(constraint_route::update_constraint)
+                .service(constraint_route::delete_constraint),
+        );
+
+        cfg.service(web::scope("/events").service(event_route::get_all_events));
+
+        cfg.service(
+            web::scope("/admin")
+                .app_data(web::PayloadConfig::new(admin_route::MAX_IMPORT_SIZE))
+                .service(admin_route::import_data)
+                .service(admin_route::export_data),
+        );
+
+        cfg.service(web::scope("/authorize").service(authorization_route::explain));
+
+        cfg.service(
+            web::scope("/policies")
+                .service(policy_route::create_policy)
+                .service(policy_route::get_all_policies)
+                .service(policy_route::find_by_uuid)
+                .service(policy_route::update_policy)
+                .service(policy_route::delete_policy),
+        );
+
+        cfg.service(
+            web::scope("/relationships")
+                .service(relationship_route::check)
+                .service(relationship_route::expand)
+                .service(relationship_route::read_tuples)
+                .service(relationship_route::write_tuples)
+                .service(relationship_route::get_all_namespaces)
+                .service(relationship_route::find_namespace_by_name)
+                .service(relationship_route::save_namespace)
+                .service(relationship_route::delete_namespace),
         );
     }
 }
@@ -77,118 +294,936 @@ impl Routes {
 pub async fn check_user_permissions(
     req: &actix_web::HttpRequest,
     pool: &web::Data<AppDataPool>,
-    permission_name: &str,
-) -> bool {
-    let uuid = match get_user_uuid_from_token(req, pool) {
-        None => return false,
+    permission: SystemPermission,
+) -> Result<User, AppError> {
+    let user = find_caller(req, pool).await?;
+    let trace = evaluate_caller_permission(req, pool, &user, permission.name()).await;
+
+    match trace.reason {
+        None => Ok(user),
+        Some(reason) => Err(AppError::from(reason)),
+    }
+}
+
+/// The names of the permissions that the API key of a request is restricted to
+#[derive(Clone)]
+pub struct ApiKeyRestriction(pub Vec<String>);
+
+/// Find the user that the token or API key of the request belongs to
+pub async fn find_caller(
+    req: &actix_web::HttpRequest,
+    pool: &web::Data<AppDataPool>,
+) -> Result<User, AppError> {
+    if let Some(key) = api_key_from_request(req) {
+        return authenticate_api_key(req, pool, &key).await;
+    }
+
+    let claims = match decode_token(req, pool) {
+        Ok(d) => d,
+        Err(reason) => return Err(AppError::from(reason)),
+    };
+
+    if let Err(reason) = check_session(pool, &claims).await {
+        return Err(AppError::from(reason));
+    }
+
+    // Impersonation tokens are only valid for as long as the actor is
+    if let Some(actor) = &claims.act {
+        let actor = find_principal(pool, &actor.org, &actor.sub).await?;
+        if !actor.enabled {
+            return Err(AppError::from(DenialReason::InvalidToken));
+        }
+
+        let mut data = HashMap::new();
+        data.insert(String::from("actorId"), actor.id.clone());
+        data.insert(String::from("method"), req.method().to_string());
+        data.insert(String::from("path"), String::from(req.path()));
+
+        let event = Event {
+            id: Uuid::new_v4().to_string(),
+            event_type: String::from(IMPERSONATED_REQUEST),
+            organization: claims.org.clone(),
+            user_id: Some(claims.sub.clone()),
+            message: format!(
+                "{} {} by {} impersonating {}",
+                req.method(),
+                req.path(),
+                actor.username,
+                claims.sub
+            ),
+            data,
+            timestamp: Utc::now().to_string(),
+        };
+
+        pool.services
+            .event_service
+            .create(event, &pool.database)
+            .await?;
+    }
+
+    find_principal(pool, &claims.org, &claims.sub).await
+}
+
+/// Make sure that the session of a token was not revoked and record that it was seen. Tokens
+/// without a session are not tied to one.
+pub async fn check_session(
+    pool: &web::Data<AppDataPool>,
+    claims: &Claims,
+) -> Result<(), DenialReason> {
+    let sid = match &claims.sid {
         Some(d) => d,
+        None => return Ok(()),
+    };
+
+    match pool
+        .services
+        .session_service
+        .find_by_uuid(&pool.database, &claims.org, sid)
+        .await
+    {
+        Ok(Some(d)) if d.user_id == claims.sub => {}
+        Ok(_) => return Err(DenialReason::RevokedSession),
+        Err(_) => return Err(DenialReason::StorageFailure),
     };
 
+    match pool
+        .services
+        .session_service
+        .update_last_seen(&pool.database, sid, &Utc::now().to_rfc3339())
+        .await
+    {
+        Ok(_) => Ok(()),
+        Err(_) => Err(DenialReason::StorageFailure),
+    }
+}
+
+/// Extract the API key of a request from the `X-API-Key` header or from an
+/// `Authorization: ApiKey ...` header
+fn api_key_from_request(req: &actix_web::HttpRequest) -> Option<String> {
+    if let Some(d) = req.headers().get("X-API-Key") {
+        return Some(String::from(d.to_str().unwrap_or_default().trim()));
+    }
+
+    let auth = req.headers().get("Authorization")?.to_str().ok()?;
+    if auth.len() > 7 && auth[0..7].eq_ignore_ascii_case("apikey ") {
+        return Some(String::from(auth[7..].trim()));
+    }
+
+    None
+}
+
+/// Find the owner of an API key, record that the key was used and remember the restriction of
+/// the key for the rest of the request
+async fn authenticate_api_key(
+    req: &actix_web::HttpRequest,
+    pool: &web::Data<AppDataPool>,
+    key: &str,
+) -> Result<User, AppError> {
+    let prefix = match parse_prefix(key) {
+        Some(d) => d,
+        None => return Err(AppError::from(DenialReason::InvalidToken)),
+    };
+
+    let api_key = match pool
+        .services
+        .api_key_service
+        .find_by_prefix(&pool.database, prefix)
+        .await
+    {
+        Ok(Some(d)) if d.hash == hash_key(key) && d.revoked_at.is_none() => d,
+        Ok(_) => return Err(AppError::from(DenialReason::InvalidToken)),
+        Err(_) => return Err(AppError::from(DenialReason::StorageFailure)),
+    };
+
+    let now = Utc::now();
+    if api_key.has_expired(now) {
+        return Err(AppError::from(DenialReason::ExpiredToken));
+    }
+
+    if pool
+        .services
+        .api_key_service
+        .update_last_used(&pool.database, &api_key.id, &now.to_rfc3339())
+        .await
+        .is_err()
+    {
+        return Err(AppError::from(DenialReason::StorageFailure));
+    }
+
+    if let Some(permissions) = api_key.permissions {
+        req.extensions_mut().insert(ApiKeyRestriction(permissions));
+    }
+
+    find_principal(pool, &api_key.organization, &api_key.owner_id).await
+}
+
+/// The permissions that the API key of a request is restricted to, if the request was
+/// authenticated with a restricted key
+pub fn api_key_restriction(req: &actix_web::HttpRequest) -> Option<Vec<String>> {
+    req.extensions()
+        .get::<ApiKeyRestriction>()
+        .map(|r| r.0.clone())
+}
+
+async fn find_principal(
+    pool: &web::Data<AppDataPool>,
+    organization: &str,
+    uuid: &str,
+) -> Result<User, AppError> {
     match pool
         .services
         .user_service
-        .find_by_uuid(&pool.database, &uuid)
+        .find_by_uuid(&pool.database, organization, uuid)
         .await
     {
-        Ok(res) => match res {
-            None => false,
-            Some(d) => {
-                if !d.enabled {
-                    return false;
+        Ok(d) => match d {
+            Some(d) => Ok(d),
+            None => Err(AppError::from(DenialReason::UnknownUser)),
+        },
+        Err(_) => Err(AppError::from(DenialReason::StorageFailure)),
+    }
+}
+
+/// Evaluate a permission of a caller that was already found, including the policies that apply
+pub async fn evaluate_caller_permission(
+    req: &actix_web::HttpRequest,
+    pool: &web::Data<AppDataPool>,
+    user: &User,
+    permission_name: &str,
+) -> AuthorizationTrace {
+    let trace = evaluate_user_permission(
+        &pool.database,
+        user,
+        &pool.services.role_service,
+        &pool.services.permission_service,
+        &pool.services.group_service,
+        permission_name,
+    )
+    .await;
+
+    let trace = match api_key_restriction(req) {
+        Some(allowed) if trace.granted && !allowed.iter().any(|p| p == permission_name) => {
+            trace.deny(DenialReason::RestrictedApiKey, "apiKey")
+        }
+        _ => trace,
+    };
+
+    apply_policies(pool, trace, user, None, client_ip(req)).await
+}
+
+/// Evaluate the policies that depend on the attributes of a specific resource
+///
+/// # Arguments
+///
+/// * `req` - The `HttpRequest` that is being handled
+/// * `pool` - The `AppDataPool` that contains the services
+/// * `user` - The `User` that was returned by `check_user_permissions`
+/// * `permission` - The permission that is being checked
+/// * `resource` - The attributes of the resource that is being accessed
+pub async fn check_resource_policies(
+    req: &actix_web::HttpRequest,
+    pool: &web::Data<AppDataPool>,
+    user: &User,
+    permission: SystemPermission,
+    resource: Value,
+) -> Result<(), AppError> {
+    let context = build_context(
+        user,
+        permission.name(),
+        Some(resource),
+        client_ip(req),
+        Utc::now(),
+    );
+
+    match pool
+        .services
+        .policy_service
+        .evaluate(&pool.database, &user.organization, &context)
+        .await
+    {
+        Ok(d) => {
+            if d.allowed {
+                Ok(())
+            } else {
+                Err(AppError::from(DenialReason::PolicyDenied))
+            }
+        }
+        Err(_) => Err(AppError::from(DenialReason::StorageFailure)),
+    }
+}
+
+/// Apply the attribute-based policies of the organization of the user to an evaluation that was
+/// granted by the roles of the user
+pub async fn apply_policies(
+    pool: &web::Data<AppDataPool>,
+    mut trace: AuthorizationTrace,
+    user: &User,
+    resource: Option<Value>,
+    client_ip: Option<String>,
+) -> AuthorizationTrace {
+    if !trace.granted {
+        return trace;
+    }
+
+    let context = build_context(user, &trace.permission, resource, client_ip, Utc::now());
+
+    match pool
+        .services
+        .policy_service
+        .evaluate(&pool.database, &user.organization, &context)
+        .await
+    {
+        Ok(d) => {
+            trace.policies = d.policies;
+            if d.allowed {
+                return trace;
+            }
+
+            let step = match trace
+                .policies
+                .iter()
+                .find(|p| p.matched && p.effect == Effect::Deny)
+            {
+                Some(p) => format!("policy:{}", p.policy_id),
+                None => String::from("policy"),
+            };
+            trace.deny(DenialReason::PolicyDenied, &step)
+        }
+        Err(_) => trace.deny(DenialReason::StorageFailure, "policy"),
+    }
+}
+
+pub fn client_ip(req: &actix_web::HttpRequest) -> Option<String> {
+    req.peer_addr().map(|a| a.ip().to_string())
+}
+
+pub fn user_agent(req: &actix_web::HttpRequest) -> Option<String> {
+    req.headers()
+        .get("User-Agent")
+        .and_then(|d| d.to_str().ok())
+        .map(String::from)
+}
+
+/// Check the permission of the caller and resolve the organization that the request operates on
+pub async fn check_organization_permissions(
+    req: &actix_web::HttpRequest,
+    pool: &web::Data<AppDataPool>,
+    permission: SystemPermission,
+) -> Result<(User, String), AppError> {
+    let user = check_user_permissions(req, pool, permission).await?;
+    let organization = resolve_organization(req, pool, &user).await?;
+
+    Ok((user, organization))
+}
+
+/// The names of the permissions that the caller can hand out, or `None` when they are allowed to
+/// grant anything
+async fn grantable_permissions(
+    req: &actix_web::HttpRequest,
+    pool: &web::Data<AppDataPool>,
+    caller: &User,
+) -> Result<Option<HashSet<String>>, AppError> {
+    let trace =
+        evaluate_caller_permission(req, pool, caller, SystemPermission::GrantAny.name()).await;
+    match trace.reason {
+        None => return Ok(None),
+        Some(DenialReason::StorageFailure) => {
+            return Err(AppError::from(DenialReason::StorageFailure))
+        }
+        Some(_) => {}
+    }
+
+    let mut held = effective_permissions(
+        &pool.database,
+        caller,
+        &pool.services.role_service,
+        &pool.services.permission_service,
+        &pool.services.group_service,
+    )
+    .await?;
+
+    // A restricted API key can only hand out what it is allowed to use itself
+    if let Some(allowed) = api_key_restriction(req) {
+        held.retain(|p| allowed.contains(p));
+    }
+
+    Ok(Some(held))
+}
+
+/// Make sure that the caller only grants roles and permissions that they hold themselves, unless
+/// they are allowed to grant anything. Only the grants that are being added should be passed.
+pub async fn check_grants(
+    req: &actix_web::HttpRequest,
+    pool: &web::Data<AppDataPool>,
+    caller: &User,
+    organization: &str,
+    roles: &[String],
+    permissions: &[String],
+) -> Result<(), AppError> {
+    if roles.is_empty() && permissions.is_empty() {
+        return Ok(());
+    }
+
+    let held = match grantable_permissions(req, pool, caller).await? {
+        Some(d) => d,
+        None => return Ok(()),
+    };
+
+    let mut offending = vec![];
+    for role in roles {
+        let role = match pool
+            .services
+            .role_service
+            .find_by_uuid(&pool.database, organization, role)
+            .await?
+        {
+            Some(d) => d,
+            None => continue,
+        };
+
+        let mut missing_permissions = vec![];
+        for permission in &role.permissions {
+            if let Some(d) = pool
+                .services
+                .permission_service
+                .find_by_uuid(&pool.database, organization, permission)
+                .await?
+            {
+                if !held.contains(&d.name) {
+                    missing_permissions.push(d.name);
                 }
-                return does_user_have_permission(
-                    &pool.database,
-                    &d,
-                    &pool.services.role_service,
-                    &pool.services.permission_service,
-                    &permission_name,
-                )
-                .await;
             }
-        },
-        Err(_) => false,
+        }
+
+        if !missing_permissions.is_empty() {
+            offending.push(OffendingGrant {
+                grant_type: GrantType::Role,
+                id: role.id,
+                name: role.name,
+                missing_permissions,
+            });
+        }
+    }
+
+    for permission in permissions {
+        if let Some(d) = pool
+            .services
+            .permission_service
+            .find_by_uuid(&pool.database, organization, permission)
+            .await?
+        {
+            if !held.contains(&d.name) {
+                offending.push(OffendingGrant {
+                    grant_type: GrantType::Permission,
+                    id: d.id,
+                    name: d.name.clone(),
+                    missing_permissions: vec![d.name],
+                });
+            }
+        }
+    }
+
+    if offending.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::with_grants(offending))
     }
 }
 
-pub fn get_user_uuid_from_token(
+/// Make sure that the caller holds every permission that is handed out, given by id and name,
+/// unless they are allowed to grant anything. Unlike `check_grants`, the permissions do not need
+/// to exist yet.
+pub async fn check_granted_permissions(
     req: &actix_web::HttpRequest,
     pool: &web::Data<AppDataPool>,
-) -> Option<String> {
-    let auth_option = req.headers().get("Authorization");
-    let auth = match auth_option {
-        None => return None,
+    caller: &User,
+    granted: &[(String, String)],
+) -> Result<(), AppError> {
+    if granted.is_empty() {
+        return Ok(());
+    }
+
+    let held = match grantable_permissions(req, pool, caller).await? {
+        Some(d) => d,
+        None => return Ok(()),
+    };
+
+    let offending: Vec<OffendingGrant> = granted
+        .iter()
+        .filter(|(_, name)| !held.contains(name))
+        .map(|(id, name)| OffendingGrant {
+            grant_type: GrantType::Permission,
+            id: id.clone(),
+            name: name.clone(),
+            missing_permissions: vec![name.clone()],
+        })
+        .collect();
+
+    if offending.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::with_grants(offending))
+    }
+}
+
+/// Create a user in a transaction that makes sure that the roles of the user still exist
+pub async fn create_user_with_roles(pool: &AppDataPool, user: User) -> Result<User, AppError> {
+    let mut transaction = pool.transactions.begin().await?;
+
+    let roles: HashSet<&String> = user.roles.iter().collect();
+    let existing = pool
+        .services
+        .role_service
+        .count_by_uuids(
+            &pool.database,
+            &user.organization,
+            &user.roles,
+            transaction.session(),
+        )
+        .await?;
+    if existing != roles.len() as u64 {
+        return Err(AppError::conflict(
+            "A role of the user was deleted, please try again!",
+        ));
+    }
+
+    let user = match pool
+        .services
+        .user_service
+        .create(user, &pool.database, transaction.session())
+        .await?
+    {
         Some(d) => d,
+        None => return Err(AppError::storage("Unable to create user!")),
+    };
+
+    transaction.commit().await?;
+
+    Ok(user)
+}
+
+/// Check that the caller is an enabled user and resolve the organization that the request
+/// operates on, without requiring any specific permission
+pub async fn check_organization_user(
+    req: &actix_web::HttpRequest,
+    pool: &web::Data<AppDataPool>,
+) -> Result<(User, String), AppError> {
+    let user = find_caller(req, pool).await?;
+    if !user.enabled {
+        return Err(AppError::from(DenialReason::DisabledUser));
+    }
+
+    let organization = resolve_organization(req, pool, &user).await?;
+
+    Ok((user, organization))
+}
+
+/// Check that the caller is an enabled platform administrator
+pub async fn check_platform_admin(
+    req: &actix_web::HttpRequest,
+    pool: &web::Data<AppDataPool>,
+) -> Result<User, AppError> {
+    let user = find_caller(req, pool).await?;
+
+    if !user.enabled {
+        return Err(AppError::from(DenialReason::DisabledUser));
+    }
+
+    if !user.platform_admin {
+        return Err(AppError::from(DenialReason::PlatformAdminRequired));
+    }
+
+    Ok(user)
+}
+
+/// Resolve the organization that a request operates on. Requests under `/orgs/{org}` use the
+/// organization in the path, which only platform administrators may set to an organization other
+/// than their own. All other requests use the organization of the caller.
+pub async fn resolve_organization(
+    req: &actix_web::HttpRequest,
+    pool: &web::Data<AppDataPool>,
+    caller: &User,
+) -> Result<String, AppError> {
+    let organization = match req.match_info().get("org") {
+        Some(d) => String::from(d),
+        None => caller.organization.clone(),
     };
 
-    let token_result = auth.to_str();
-    let token = match token_result {
+    if organization != caller.organization && !caller.platform_admin {
+        return Err(AppError::from(DenialReason::ForeignOrganization));
+    }
+
+    match pool
+        .services
+        .organization_service
+        .find_by_id(&pool.database, &organization)
+        .await?
+    {
+        Some(x) if x.enabled => Ok(organization),
+        _ => Err(AppError::not_found("Organization not found!")),
+    }
+}
+
+/// The organization of a request that is not authenticated yet: the organization in the path,
+/// the given fallback, or the default organization
+pub fn request_organization(req: &actix_web::HttpRequest, fallback: Option<&str>) -> String {
+    match req.match_info().get("org") {
+        Some(d) => String::from(d),
+        None => match fallback {
+            Some(d) if !d.is_empty() => String::from(d),
+            _ => String::from(DEFAULT_ORGANIZATION),
+        },
+    }
+}
+
+/// Decode the token of a request. The token is taken from the `Authorization` header or, in
+/// cookie mode, from the token cookie.
+pub fn decode_token(
+    req: &actix_web::HttpRequest,
+    pool: &web::Data<AppDataPool>,
+) -> Result<Claims, DenialReason> {
+    let token = match req.headers().get("Authorization") {
+        Some(d) => bearer_token(d)?,
+        None => cookie_token(req, pool)?,
+    };
+
+    let token_result = jsonwebtoken::decode::<Claims>(
+        &token,
+        &DecodingKey::from_secret(pool.jwt.secret.as_ref()),
+        &Validation::default(),
+    );
+
+    match token_result {
+        Ok(mut d) => {
+            // Tokens that were issued before organizations existed belong to the default one
+            if d.claims.org.is_empty() {
+                d.claims.org = String::from(DEFAULT_ORGANIZATION);
+            }
+            Ok(d.claims)
+        }
+        Err(e) => match e.kind() {
+            ErrorKind::ExpiredSignature => Err(DenialReason::ExpiredToken),
+            _ => Err(DenialReason::InvalidToken),
+        },
+    }
+}
+
+fn bearer_token(auth: &actix_web::http::header::HeaderValue) -> Result<String, DenialReason> {
+    let token = match auth.to_str() {
         Ok(d) => String::from(d),
-        Err(_e) => return None,
+        Err(_e) => return Err(DenialReason::InvalidToken),
     };
 
     if token.len() < 8 {
-        return None;
+        return Err(DenialReason::MissingToken);
     }
 
     let slice = &token[0..7];
     if slice.to_lowercase() != "bearer " {
-        return None;
+        return Err(DenialReason::MissingToken);
     }
 
-    let bearer_token = &token[7..token.len()];
+    Ok(String::from(&token[7..token.len()]))
+}
 
-    let token_result = jsonwebtoken::decode::<Claims>(
-        &bearer_token,
-        &DecodingKey::from_secret(&pool.jwt.secret.as_ref()),
-        &Validation::default(),
-    );
-    let res = match token_result {
-        Ok(d) => d,
-        Err(_) => return None,
+/// Read the token from the cookie that `authenticate` sets in cookie mode. Browsers send cookies
+/// on their own, so requests that change state have to repeat the CSRF cookie in the CSRF header.
+fn cookie_token(
+    req: &actix_web::HttpRequest,
+    pool: &web::Data<AppDataPool>,
+) -> Result<String, DenialReason> {
+    if !pool.cookies.enabled {
+        return Err(DenialReason::MissingToken);
+    }
+
+    let token = match req.cookie(TOKEN_COOKIE) {
+        Some(d) => String::from(d.value()),
+        None => return Err(DenialReason::MissingToken),
     };
 
-    return Some(res.claims.sub);
+    if !req.method().is_safe() {
+        let header = req.headers().get(CSRF_HEADER).and_then(|d| d.to_str().ok());
+
+        match (req.cookie(CSRF_COOKIE), header) {
+            (Some(c), Some(h)) if !c.value().is_empty() && c.value() == h => {}
+            _ => return Err(DenialReason::InvalidCsrfToken),
+        }
+    }
+
+    Ok(token)
 }
 
-pub async fn does_user_have_permission(
+/// A role that a user holds, together with the way in which it was obtained
+pub struct EffectiveRole {
+    pub id: String,
+    /// The group that the role was inherited from
+    pub via_group: Option<String>,
+    /// The end of the time-bound assignment that granted the role
+    pub valid_until: Option<String>,
+}
+
+/// Collect the roles of a user. Direct roles come first, followed by the time-bound assignments
+/// that are currently active and the roles that are inherited through (nested) groups.
+pub async fn effective_roles(
+    db: &Database,
+    user: &User,
+    group_service: &GroupService,
+) -> Result<Vec<EffectiveRole>, Error> {
+    let mut roles: Vec<EffectiveRole> = user
+        .roles
+        .iter()
+        .map(|r| EffectiveRole {
+            id: r.clone(),
+            via_group: None,
+            valid_until: None,
+        })
+        .collect();
+
+    let now = Utc::now();
+    for assignment in user.role_assignments.iter().filter(|a| a.is_active(now)) {
+        if !roles.iter().any(|r| r.id == assignment.role_id) {
+            roles.push(EffectiveRole {
+                id: assignment.role_id.clone(),
+                via_group: None,
+                valid_until: Some(assignment.valid_until.clone()),
+            });
+        }
+    }
+
+    let groups = group_service
+        .find_effective_groups(db, &user.organization, &user.id)
+        .await?;
+
+    for group in groups {
+        for role in group.roles {
+            if !roles.iter().any(|r| r.id == role) {
+                roles.push(EffectiveRole {
+                    id: role,
+                    via_group: Some(group.id.clone()),
+                    valid_until: None,
+                });
+            }
+        }
+    }
+
+    Ok(roles)
+}
+
+/// Collect the ids of every role that a user holds or is going to hold: direct roles, time-bound
+/// assignments that have not lapsed yet and the roles that are inherited through (nested) groups
+pub async fn held_roles(
+    db: &Database,
+    user: &User,
+    group_service: &GroupService,
+) -> Result<HashSet<String>, Error> {
+    let now = Utc::now();
+    let mut roles: HashSet<String> = user.roles.iter().cloned().collect();
+
+    for assignment in &user.role_assignments {
+        if !assignment.has_lapsed(now) {
+            roles.insert(assignment.role_id.clone());
+        }
+    }
+
+    for group in group_service
+        .find_effective_groups(db, &user.organization, &user.id)
+        .await?
+    {
+        roles.extend(group.roles);
+    }
+
+    Ok(roles)
+}
+
+/// Make sure that a set of roles held by a user does not violate any separation-of-duties
+/// constraint of the organization
+pub async fn check_constraints(
+    pool: &web::Data<AppDataPool>,
+    organization: &str,
+    username: &str,
+    roles: &HashSet<String>,
+) -> Result<(), AppError> {
+    let constraints = pool
+        .services
+        .constraint_service
+        .find_all(&pool.database, organization)
+        .await?;
+
+    for constraint in &constraints {
+        let conflicting = conflicting_roles(constraint, roles);
+        if !conflicting.is_empty() {
+            return Err(AppError::validation(&format!(
+                "User {} cannot hold roles {} at the same time because of constraint {}!",
+                username,
+                conflicting.join(", "),
+                constraint.name
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the constraints for a user whose roles are about to change. The roles of the user are
+/// combined with the roles that are being added.
+pub async fn check_user_constraints(
+    pool: &web::Data<AppDataPool>,
+    user: &User,
+    added_roles: &[String],
+) -> Result<(), AppError> {
+    let mut roles = held_roles(&pool.database, user, &pool.services.group_service).await?;
+    roles.extend(added_roles.iter().cloned());
+
+    check_constraints(pool, &user.organization, &user.username, &roles).await
+}
+
+/// Collect the names of every permission that a user holds through their effective roles.
+/// Roles and permissions that no longer exist are ignored.
+pub async fn effective_permissions(
     db: &Database,
     user: &User,
     role_service: &RoleService,
     permission_service: &PermissionService,
+    group_service: &GroupService,
+) -> Result<HashSet<String>, Error> {
+    let mut permissions = HashSet::new();
+
+    for role in effective_roles(db, user, group_service).await? {
+        let role = match role_service
+            .find_by_uuid(db, &user.organization, &role.id)
+            .await?
+        {
+            Some(d) => d,
+            None => continue,
+        };
+
+        for permission in &role.permissions {
+            if let Some(d) = permission_service
+                .find_by_uuid(db, &user.organization, permission)
+                .await?
+            {
+                permissions.insert(d.name);
+            }
+        }
+    }
+
+    Ok(permissions)
+}
+
+/// Evaluate whether a user holds a permission, recording every role and permission that was
+/// inspected along the way so that a denial can be explained afterwards
+pub async fn evaluate_user_permission(
+    db: &Database,
+    user: &User,
+    role_service: &RoleService,
+    permission_service: &PermissionService,
+    group_service: &GroupService,
     permission_name: &str,
-) -> bool {
+) -> AuthorizationTrace {
+    let mut trace = AuthorizationTrace::new(&user.id, permission_name);
+
+    if !user.enabled {
+        return trace.deny(DenialReason::DisabledUser, &format!("user:{}", user.id));
+    }
+
     if permission_name.is_empty() {
-        return false;
+        return trace.deny(DenialReason::MissingPermission, "permission");
     }
 
-    for role in &user.roles {
-        let optional_role = match role_service.find_by_uuid(db, role).await {
+    let roles = match effective_roles(db, user, group_service).await {
+        Ok(d) => d,
+        Err(_) => return trace.deny(DenialReason::StorageFailure, &format!("user:{}", user.id)),
+    };
+
+    for EffectiveRole {
+        id: role,
+        via_group,
+        valid_until,
+    } in &roles
+    {
+        let optional_role = match role_service
+            .find_by_uuid(db, &user.organization, role)
+            .await
+        {
             Ok(d) => d,
-            Err(_) => return false,
+            Err(_) => return trace.deny(DenialReason::StorageFailure, &format!("role:{}", role)),
         };
 
         let actual_role = match optional_role {
             Some(d) => d,
-            None => return false,
+            None => {
+                trace.roles.push(RoleTrace {
+                    id: role.clone(),
+                    name: None,
+                    found: false,
+                    via_group: via_group.clone(),
+                    valid_until: valid_until.clone(),
+                    permissions: vec![],
+                });
+                return trace.deny(DenialReason::DanglingRole, &format!("role:{}", role));
+            }
+        };
+
+        let mut role_trace = RoleTrace {
+            id: actual_role.id.clone(),
+            name: Some(actual_role.name.clone()),
+            found: true,
+            via_group: via_group.clone(),
+            valid_until: valid_until.clone(),
+            permissions: vec![],
         };
 
         for permission in &actual_role.permissions {
-            let optional_permission = match permission_service.find_by_uuid(db, permission).await {
+            let optional_permission = match permission_service
+                .find_by_uuid(db, &user.organization, permission)
+                .await
+            {
                 Ok(d) => d,
-                Err(_) => return false,
+                Err(_) => {
+                    trace.roles.push(role_trace);
+                    return trace.deny(
+                        DenialReason::StorageFailure,
+                        &format!("permission:{}", permission),
+                    );
+                }
             };
 
             let actual_permission = match optional_permission {
                 Some(d) => d,
-                None => return false,
+                None => {
+                    role_trace.permissions.push(PermissionTrace {
+                        id: permission.clone(),
+                        name: None,
+                        found: false,
+                        matched: false,
+                    });
+                    trace.roles.push(role_trace);
+                    return trace.deny(
+                        DenialReason::DanglingPermission,
+                        &format!("permission:{}", permission),
+                    );
+                }
             };
 
-            if actual_permission.name == permission_name {
-                return true;
+            let matched = actual_permission.name == permission_name;
+            role_trace.permissions.push(PermissionTrace {
+                id: actual_permission.id,
+                name: Some(actual_permission.name),
+                found: true,
+                matched,
+            });
+
+            if matched {
+                trace.roles.push(role_trace);
+                trace.granted = true;
+                return trace;
             }
         }
+
+        trace.roles.push(role_trace);
     }
-    false
+
+    trace.deny(DenialReason::MissingPermission, "permission")
 }
 
 pub async fn convert_user_to_dto(
@@ -199,17 +1234,32 @@ pub async fn convert_user_to_dto(
 ) -> Result<UserDto, Error> {
     let mut roles = vec![];
     for role in &user.roles {
-        match role_service.find_by_uuid(db, role).await {
-            Ok(d) => {
-                if let Some(x) = d {
-                    match convert_role_to_dto(x, &db, &permission_service).await {
-                        Ok(d) => roles.push(d),
-                        Err(e) => return Err(e),
-                    };
-                }
-            }
-            Err(e) => return Err(e),
-        };
+        if let Some(x) = role_service
+            .find_by_uuid(db, &user.organization, role)
+            .await?
+        {
+            let dto = convert_role_to_dto(x, db, permission_service).await?;
+            roles.push(dto);
+        }
+    }
+
+    let now = Utc::now();
+    let mut role_assignments = vec![];
+    for assignment in &user.role_assignments {
+        if let Some(x) = role_service
+            .find_by_uuid(db, &user.organization, &assignment.role_id)
+            .await?
+        {
+            let dto = convert_role_to_dto(x, db, permission_service).await?;
+            role_assignments.push(RoleAssignmentDto {
+                role: dto,
+                valid_from: assignment.valid_from.clone(),
+                valid_until: assignment.valid_until.clone(),
+                reason: assignment.reason.clone(),
+                assigned_by: assignment.assigned_by.clone(),
+                active: assignment.is_active(now),
+            });
+        }
     }
 
     Ok(UserDto {
@@ -219,9 +1269,43 @@ pub async fn convert_user_to_dto(
         first_name: user.first_name,
         last_name: user.last_name,
         enabled: user.enabled,
+        platform_admin: user.platform_admin,
+        service_account: user.service_account,
         roles,
+        role_assignments,
         created_at: user.created_at,
         last_active: user.last_active,
+        attributes: user.attributes,
+        organization: user.organization,
+        version: user.version,
+    })
+}
+
+pub async fn convert_group_to_dto(
+    group: Group,
+    db: &Database,
+    role_service: &RoleService,
+    permission_service: &PermissionService,
+) -> Result<GroupDto, Error> {
+    let mut roles = vec![];
+    for role in &group.roles {
+        if let Some(x) = role_service
+            .find_by_uuid(db, &group.organization, role)
+            .await?
+        {
+            let dto = convert_role_to_dto(x, db, permission_service).await?;
+            roles.push(dto);
+        }
+    }
+
+    Ok(GroupDto {
+        id: group.id,
+        name: group.name,
+        description: group.description,
+        members: group.members,
+        groups: group.groups,
+        roles,
+        organization: group.organization,
     })
 }
 
@@ -232,14 +1316,12 @@ pub async fn convert_role_to_dto(
 ) -> Result<RoleDto, Error> {
     let mut permissions = vec![];
     for permission in role.permissions {
-        match permission_service.find_by_uuid(db, &permission).await {
-            Ok(d) => {
-                if let Some(x) = d {
-                    permissions.push(convert_permission_to_dto(x));
-                }
-            }
-            Err(e) => return Err(e),
-        };
+        if let Some(x) = permission_service
+            .find_by_uuid(db, &role.organization, &permission)
+            .await?
+        {
+            permissions.push(convert_permission_to_dto(x));
+        }
     }
 
     Ok(RoleDto {
@@ -247,13 +1329,160 @@ pub async fn convert_role_to_dto(
         name: role.name,
         description: role.description,
         permissions,
+        approver_permissions: role.approver_permissions,
+        organization: role.organization,
+        version: role.version,
+        managed: role.managed,
     })
 }
 
 pub fn convert_permission_to_dto(permission: Permission) -> PermissionDto {
+    let system = SystemPermission::from_name(&permission.name).is_some();
+
     PermissionDto {
         id: permission.id,
         name: permission.name,
         description: permission.description,
+        organization: permission.organization,
+        version: permission.version,
+        managed: permission.managed,
+        system,
+    }
+}
+
+pub fn convert_policy_to_dto(policy: Policy) -> PolicyDto {
+    PolicyDto {
+        id: policy.id,
+        name: policy.name,
+        description: policy.description,
+        effect: policy.effect,
+        actions: policy.actions,
+        conditions: policy.conditions,
+        enabled: policy.enabled,
+        organization: policy.organization,
+    }
+}
+
+pub fn convert_tuple_to_dto(tuple: RelationTuple) -> TupleDto {
+    TupleDto {
+        object: tuple.object(),
+        relation: tuple.relation.clone(),
+        subject: tuple.subject(),
+    }
+}
+
+pub fn convert_namespace_to_dto(namespace: Namespace) -> NamespaceDto {
+    NamespaceDto {
+        name: namespace.name,
+        relations: namespace.relations,
+    }
+}
+
+pub fn convert_organization_to_dto(organization: Organization) -> OrganizationDto {
+    OrganizationDto {
+        id: organization.id,
+        name: organization.name,
+        description: organization.description,
+        enabled: organization.enabled,
+        created_at: organization.created_at,
+    }
+}
+
+pub fn convert_event_to_dto(event: Event) -> EventDto {
+    EventDto {
+        id: event.id,
+        event_type: event.event_type,
+        organization: event.organization,
+        user_id: event.user_id,
+        message: event.message,
+        data: event.data,
+        timestamp: event.timestamp,
+    }
+}
+
+pub fn convert_access_request_to_dto(request: AccessRequest) -> AccessRequestDto {
+    AccessRequestDto {
+        id: request.id,
+        organization: request.organization,
+        requester_id: request.requester_id,
+        role_id: request.role_id,
+        duration: request.duration,
+        justification: request.justification,
+        state: request.state,
+        created_at: request.created_at,
+        decided_by: request.decided_by,
+        decided_at: request.decided_at,
+        comment: request.comment,
+        valid_until: request.valid_until,
+    }
+}
+
+pub fn convert_constraint_to_dto(constraint: Constraint) -> ConstraintDto {
+    ConstraintDto {
+        id: constraint.id,
+        name: constraint.name,
+        description: constraint.description,
+        roles: constraint.roles,
+        organization: constraint.organization,
+    }
+}
+
+pub fn convert_access_review_to_dto(review: AccessReview) -> AccessReviewDto {
+    AccessReviewDto {
+        id: review.id,
+        organization: review.organization,
+        name: review.name,
+        description: review.description,
+        scope: review.scope,
+        reviewers: review.reviewers,
+        state: review.state,
+        created_by: review.created_by,
+        created_at: review.created_at,
+        closed_at: review.closed_at,
+        items: review.items,
+    }
+}
+
+pub fn convert_api_key_to_dto(api_key: ApiKey) -> ApiKeyDto {
+    ApiKeyDto {
+        id: api_key.id,
+        organization: api_key.organization,
+        owner_id: api_key.owner_id,
+        name: api_key.name,
+        prefix: api_key.prefix,
+        permissions: api_key.permissions,
+        created_by: api_key.created_by,
+        created_at: api_key.created_at,
+        expires_at: api_key.expires_at,
+        last_used: api_key.last_used,
+        revoked_at: api_key.revoked_at,
+    }
+}
+
+pub fn convert_session_to_dto(session: Session, current: Option<&str>) -> SessionDto {
+    SessionDto {
+        current: current == Some(session.id.as_str()),
+        id: session.id,
+        user_id: session.user_id,
+        created_at: session.created_at,
+        last_seen: session.last_seen,
+        expires_at: session.expires_at,
+        ip: session.ip,
+        user_agent: session.user_agent,
+        device: session.device,
+        actor_id: session.actor_id,
+    }
+}
+
+pub fn convert_login_attempt_to_dto(attempt: LoginAttempt) -> LoginAttemptDto {
+    LoginAttemptDto {
+        id: attempt.id,
+        outcome: attempt.outcome,
+        ip: attempt.ip,
+        user_agent: attempt.user_agent,
+        network: attempt.network,
+        country: attempt.country,
+        alerts: attempt.alerts,
+        timestamp: attempt.timestamp,
     }
 }
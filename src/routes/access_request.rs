@@ -0,0 +1,2 @@
+pub mod access_request_route;
+pub mod dto;
@@ -0,0 +1,427 @@
+use std::collections::HashMap;
+
+use actix_web::{get, post, web, HttpRequest, HttpResponse};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::{
+    configuration::app_data_pool::AppDataPool,
+    errors::app_error::AppError,
+    persistence::{
+        access_request::model::access_request::{AccessRequest, AccessRequestState},
+        user::model::{role_assignment::RoleAssignment, user::User},
+    },
+    routes::{
+        access_request::dto::{
+            access_request_decision::AccessRequestDecision,
+            create_access_request::CreateAccessRequest,
+        },
+        authorization::dto::denial_reason::DenialReason,
+        check_user_constraints, convert_access_request_to_dto, evaluate_caller_permission,
+        validation::Valid,
+        EntityPath,
+    },
+    services::{
+        access_request::access_request_service::MAX_ACCESS_DURATION,
+        permission::system_permission::SystemPermission,
+    },
+};
+
+#[post("/")]
+pub async fn create_access_request(
+    create: Valid<CreateAccessRequest>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::RequestAccess)
+            .await?;
+
+    if create.duration <= 0 || create.duration > MAX_ACCESS_DURATION {
+        return Err(AppError::validation(&format!(
+            "Duration must be between 1 and {} seconds!",
+            MAX_ACCESS_DURATION
+        )));
+    }
+
+    if pool
+        .services
+        .role_service
+        .find_by_uuid(&pool.database, &organization, &create.role_id)
+        .await?
+        .is_none()
+    {
+        return Err(AppError::validation(&format!(
+            "Invalid role {}",
+            create.role_id
+        )));
+    }
+
+    if pool
+        .services
+        .access_request_service
+        .find_pending(&pool.database, &organization, &caller.id, &create.role_id)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::conflict(&format!(
+            "There already is a pending request for role {}!",
+            create.role_id
+        )));
+    }
+
+    let request = AccessRequest {
+        id: Uuid::new_v4().to_string(),
+        organization,
+        requester_id: caller.id,
+        role_id: create.role_id.clone(),
+        duration: create.duration,
+        justification: create.justification.clone(),
+        state: AccessRequestState::Pending,
+        created_at: Utc::now().to_rfc3339(),
+        decided_by: None,
+        decided_at: None,
+        comment: None,
+        valid_until: None,
+    };
+
+    match pool
+        .services
+        .access_request_service
+        .create(request, &pool.database)
+        .await?
+    {
+        Some(d) => Ok(HttpResponse::Ok().json(convert_access_request_to_dto(d))),
+        None => Err(AppError::storage("Unable to create access request!")),
+    }
+}
+
+/// List the access requests of the caller
+#[get("/mine")]
+pub async fn get_my_access_requests(
+    req: HttpRequest,
+    pool: web::Data<AppDataPool>,
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::RequestAccess)
+            .await?;
+
+    let requests = pool
+        .services
+        .access_request_service
+        .find_by_requester(&pool.database, &organization, &caller.id)
+        .await?;
+    Ok(HttpResponse::Ok().json(
+        requests
+            .into_iter()
+            .map(convert_access_request_to_dto)
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// List the pending access requests that the caller is allowed to decide on
+#[get("/pending")]
+pub async fn get_pending_access_requests(
+    req: HttpRequest,
+    pool: web::Data<AppDataPool>,
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization) = crate::routes::check_organization_user(&req, &pool).await?;
+
+    let pending = pool
+        .services
+        .access_request_service
+        .find_by_state(&pool.database, &organization, AccessRequestState::Pending)
+        .await?;
+
+    let mut approvable: HashMap<String, bool> = HashMap::new();
+    let mut requests = vec![];
+    for request in pending {
+        if request.requester_id == caller.id {
+            continue;
+        }
+
+        let allowed = match approvable.get(&request.role_id) {
+            Some(d) => *d,
+            None => {
+                let approver =
+                    is_approver(&req, &pool, &caller, &organization, &request.role_id).await?;
+                approvable.insert(request.role_id.clone(), approver);
+                approver
+            }
+        };
+
+        if allowed {
+            requests.push(convert_access_request_to_dto(request));
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(requests))
+}
+
+#[get("/{uuid}")]
+pub async fn get_access_request_by_id(
+    path: web::Path<EntityPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (caller, organization) = crate::routes::check_organization_user(&req, &pool).await?;
+
+    let request = find_access_request(&pool, &organization, &path).await?;
+
+    if request.requester_id != caller.id {
+        match is_approver(&req, &pool, &caller, &organization, &request.role_id).await? {
+            true => {}
+            false => return Err(AppError::from(DenialReason::MissingPermission)),
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(convert_access_request_to_dto(request)))
+}
+
+#[post("/{uuid}/approve")]
+pub async fn approve_access_request(
+    path: web::Path<EntityPath>,
+    decision: Valid<AccessRequestDecision>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    decide(
+        &req,
+        &pool,
+        &path.into_inner().uuid,
+        decision.into_inner(),
+        AccessRequestState::Approved,
+    )
+    .await
+}
+
+#[post("/{uuid}/deny")]
+pub async fn deny_access_request(
+    path: web::Path<EntityPath>,
+    decision: Valid<AccessRequestDecision>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    decide(
+        &req,
+        &pool,
+        &path.into_inner().uuid,
+        decision.into_inner(),
+        AccessRequestState::Denied,
+    )
+    .await
+}
+
+/// Revoke an access request. Requesters can withdraw their own pending requests, approvers can
+/// also revoke requests that were already approved, which removes the granted role.
+#[post("/{uuid}/revoke")]
+pub async fn revoke_access_request(
+    path: web::Path<EntityPath>,
+    decision: Valid<AccessRequestDecision>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    decide(
+        &req,
+        &pool,
+        &path.into_inner().uuid,
+        decision.into_inner(),
+        AccessRequestState::Revoked,
+    )
+    .await
+}
+
+/// Move an access request to the next state on behalf of the caller
+async fn decide(
+    req: &HttpRequest,
+    pool: &web::Data<AppDataPool>,
+    uuid: &str,
+    decision: AccessRequestDecision,
+    next: AccessRequestState,
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization) = crate::routes::check_organization_user(req, pool).await?;
+
+    let request = find_access_request(pool, &organization, uuid).await?;
+
+    // Requesters may only withdraw their own pending requests and never decide on them
+    let withdrawal = request.requester_id == caller.id
+        && next == AccessRequestState::Revoked
+        && request.state == AccessRequestState::Pending;
+
+    if !withdrawal {
+        if request.requester_id == caller.id {
+            return Err(AppError::from(DenialReason::MissingPermission));
+        }
+
+        match is_approver(req, pool, &caller, &organization, &request.role_id).await? {
+            true => {}
+            false => return Err(AppError::from(DenialReason::MissingPermission)),
+        }
+    }
+
+    if !request.state.can_transition_to(next) {
+        return Err(AppError::validation(&format!(
+            "Access request cannot move from {:?} to {:?}!",
+            request.state, next
+        )));
+    }
+
+    let now = Utc::now();
+    let from = request.state;
+    let mut updated = request.clone();
+    updated.state = next;
+    updated.decided_by = Some(caller.id.clone());
+    updated.decided_at = Some(now.to_rfc3339());
+    updated.comment = decision.comment;
+
+    if next == AccessRequestState::Approved {
+        match pool
+            .services
+            .role_service
+            .find_by_uuid(&pool.database, &organization, &request.role_id)
+            .await?
+        {
+            Some(_) => {}
+            None => {
+                return Err(AppError::validation(&format!(
+                    "Role {} no longer exists!",
+                    request.role_id
+                )));
+            }
+        };
+
+        let requester = match pool
+            .services
+            .user_service
+            .find_by_uuid(&pool.database, &organization, &request.requester_id)
+            .await?
+        {
+            Some(d) => d,
+            None => {
+                return Err(AppError::validation("The requester no longer exists!"));
+            }
+        };
+
+        check_user_constraints(pool, &requester, std::slice::from_ref(&request.role_id)).await?;
+
+        updated.valid_until = Some((now + Duration::seconds(request.duration)).to_rfc3339());
+    }
+
+    // The decision and the role assignment that it grants or takes away change together
+    let mut transaction = pool.transactions.begin().await?;
+
+    let updated = match pool
+        .services
+        .access_request_service
+        .transition(&pool.database, from, updated, transaction.session())
+        .await?
+    {
+        Some(d) => d,
+        None => {
+            transaction.abort().await?;
+            return Err(AppError::validation(
+                "Access request was changed in the meantime!",
+            ));
+        }
+    };
+
+    match (from, next) {
+        (_, AccessRequestState::Approved) => {
+            let assignment = RoleAssignment {
+                role_id: updated.role_id.clone(),
+                valid_from: None,
+                valid_until: updated.valid_until.clone().unwrap_or_default(),
+                reason: format!("Access request {}: {}", updated.id, updated.justification),
+                assigned_by: caller.id,
+            };
+
+            pool.services
+                .user_service
+                .add_role_assignment(
+                    &pool.database,
+                    &organization,
+                    &updated.requester_id,
+                    assignment,
+                    transaction.session(),
+                )
+                .await?;
+        }
+        (AccessRequestState::Approved, AccessRequestState::Revoked) => {
+            pool.services
+                .user_service
+                .remove_role_assignment(
+                    &pool.database,
+                    &organization,
+                    &updated.requester_id,
+                    &updated.role_id,
+                    transaction.session(),
+                )
+                .await?;
+        }
+        _ => {}
+    }
+
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().json(convert_access_request_to_dto(updated)))
+}
+
+async fn find_access_request(
+    pool: &web::Data<AppDataPool>,
+    organization: &str,
+    uuid: &str,
+) -> Result<AccessRequest, AppError> {
+    match pool
+        .services
+        .access_request_service
+        .find_by_uuid(&pool.database, organization, uuid)
+        .await?
+    {
+        Some(d) => Ok(d),
+        None => Err(AppError::not_found("Access request not found!")),
+    }
+}
+
+/// Check whether the caller holds one of the approver permissions of a role of the organization in
+/// the path, taking the policies and the restrictions of API keys into account
+async fn is_approver(
+    req: &HttpRequest,
+    pool: &web::Data<AppDataPool>,
+    caller: &User,
+    organization: &str,
+    role_id: &str,
+) -> Result<bool, AppError> {
+    let role = match pool
+        .services
+        .role_service
+        .find_by_uuid(&pool.database, organization, role_id)
+        .await?
+    {
+        Some(d) => d,
+        None => return Ok(false),
+    };
+
+    for permission in &role.approver_permissions {
+        let permission = match pool
+            .services
+            .permission_service
+            .find_by_uuid(&pool.database, organization, permission)
+            .await?
+        {
+            Some(d) => d,
+            None => continue,
+        };
+
+        let trace = evaluate_caller_permission(req, pool, caller, &permission.name).await;
+        match trace.reason {
+            None => return Ok(true),
+            Some(DenialReason::StorageFailure) => {
+                return Err(AppError::from(DenialReason::StorageFailure))
+            }
+            Some(_) => {}
+        }
+    }
+
+    Ok(false)
+}
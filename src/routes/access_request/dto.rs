@@ -0,0 +1,3 @@
+pub mod access_request;
+pub mod access_request_decision;
+pub mod create_access_request;
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+use crate::persistence::access_request::model::access_request::AccessRequestState;
+
+#[derive(Serialize, Deserialize)]
+pub struct AccessRequest {
+    pub id: String,
+    pub organization: String,
+    #[serde(rename(serialize = "requesterId", deserialize = "requesterId"))]
+    pub requester_id: String,
+    #[serde(rename(serialize = "roleId", deserialize = "roleId"))]
+    pub role_id: String,
+    pub duration: i64,
+    pub justification: String,
+    pub state: AccessRequestState,
+    #[serde(rename(serialize = "createdAt", deserialize = "createdAt"))]
+    pub created_at: String,
+    #[serde(rename(serialize = "decidedBy", deserialize = "decidedBy"))]
+    pub decided_by: Option<String>,
+    #[serde(rename(serialize = "decidedAt", deserialize = "decidedAt"))]
+    pub decided_at: Option<String>,
+    pub comment: Option<String>,
+    #[serde(rename(serialize = "validUntil", deserialize = "validUntil"))]
+    pub valid_until: Option<String>,
+}
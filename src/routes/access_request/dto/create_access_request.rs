@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+use crate::routes::validation::{Validate, Validator};
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateAccessRequest {
+    #[serde(rename(serialize = "roleId", deserialize = "roleId"))]
+    pub role_id: String,
+    /// The number of seconds that the role is requested for
+    pub duration: i64,
+    pub justification: String,
+}
+
+impl Validate for CreateAccessRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.uuid("roleId", &self.role_id)
+            .required("justification", &self.justification)
+            .description("justification", &self.justification);
+    }
+}
@@ -0,0 +1,433 @@
+use std::collections::HashMap;
+
+use actix_web::{get, post, web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    configuration::app_data_pool::AppDataPool,
+    errors::app_error::AppError,
+    persistence::{
+        access_review::model::access_review::{
+            AccessReview, ReviewDecision as Decision, ReviewItem, ReviewScope, ReviewState,
+        },
+        user::model::user::User,
+    },
+    routes::{
+        access_review::dto::{
+            create_access_review::CreateAccessReview, report_query::ReportQuery,
+            review_decision::ReviewDecision, review_item_path::ReviewItemPath,
+        },
+        authorization::dto::denial_reason::DenialReason,
+        convert_access_review_to_dto, evaluate_caller_permission,
+        validation::Valid,
+        EntityPath,
+    },
+    services::{
+        access_review::access_review_service::{summarize, to_csv},
+        permission::system_permission::SystemPermission,
+    },
+};
+
+#[post("/")]
+pub async fn create_access_review(
+    create: Valid<CreateAccessReview>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ManageAccessReview,
+    )
+    .await?;
+
+    for reviewer in &create.reviewers {
+        if pool
+            .services
+            .user_service
+            .find_by_uuid(&pool.database, &organization, reviewer)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::validation(&format!(
+                "Invalid reviewer {}",
+                reviewer
+            )));
+        }
+    }
+
+    let role_names: HashMap<String, String> = pool
+        .services
+        .role_service
+        .find_all(&pool.database, &organization)
+        .await?
+        .into_iter()
+        .map(|r| (r.id, r.name))
+        .collect();
+
+    for role in &create.roles {
+        if !role_names.contains_key(role) {
+            return Err(AppError::validation(&format!("Invalid role {}", role)));
+        }
+    }
+
+    let users = find_users_in_scope(&pool, &organization, &create.users).await?;
+
+    // Snapshot the roles that are directly assigned to the users in scope
+    let mut items = vec![];
+    for user in users {
+        for role in &user.roles {
+            if !create.roles.is_empty() && !create.roles.contains(role) {
+                continue;
+            }
+
+            items.push(ReviewItem {
+                id: Uuid::new_v4().to_string(),
+                user_id: user.id.clone(),
+                username: user.username.clone(),
+                role_id: role.clone(),
+                role_name: role_names.get(role).cloned().unwrap_or_default(),
+                decision: Decision::Pending,
+                reviewer_id: None,
+                decided_at: None,
+                comment: None,
+            });
+        }
+    }
+
+    let review = AccessReview {
+        id: Uuid::new_v4().to_string(),
+        organization,
+        name: create.name.clone(),
+        description: create.description.clone(),
+        scope: ReviewScope {
+            roles: create.roles.clone(),
+            users: create.users.clone(),
+        },
+        reviewers: create.reviewers.clone(),
+        state: ReviewState::Open,
+        created_by: caller.id,
+        created_at: Utc::now().to_rfc3339(),
+        closed_at: None,
+        items,
+    };
+
+    match pool
+        .services
+        .access_review_service
+        .create(review, &pool.database)
+        .await?
+    {
+        Some(d) => Ok(HttpResponse::Ok().json(convert_access_review_to_dto(d))),
+        None => Err(AppError::storage("Unable to create access review!")),
+    }
+}
+
+#[get("/")]
+pub async fn get_all_access_reviews(
+    req: HttpRequest,
+    pool: web::Data<AppDataPool>,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ReadAccessReview,
+    )
+    .await?;
+
+    let reviews = pool
+        .services
+        .access_review_service
+        .find_all(&pool.database, &organization)
+        .await?;
+    Ok(HttpResponse::Ok().json(
+        reviews
+            .into_iter()
+            .map(convert_access_review_to_dto)
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// Retrieve a campaign. Reviewers of the campaign can always see it.
+#[get("/{uuid}")]
+pub async fn get_access_review_by_id(
+    path: web::Path<EntityPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (caller, organization) = crate::routes::check_organization_user(&req, &pool).await?;
+
+    let review = find_access_review(&pool, &organization, &path).await?;
+
+    if !review.reviewers.contains(&caller.id) {
+        let trace = evaluate_caller_permission(
+            &req,
+            &pool,
+            &caller,
+            SystemPermission::ReadAccessReview.name(),
+        )
+        .await;
+        if let Some(reason) = trace.reason {
+            return Err(AppError::from(reason));
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(convert_access_review_to_dto(review)))
+}
+
+#[post("/{uuid}/items/{item}/approve")]
+pub async fn approve_item(
+    path: web::Path<ReviewItemPath>,
+    decision: Valid<ReviewDecision>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    decide(
+        &req,
+        &pool,
+        path.into_inner(),
+        decision.into_inner(),
+        Decision::Approved,
+    )
+    .await
+}
+
+/// Revoke a reviewed role, which removes it from the user
+#[post("/{uuid}/items/{item}/revoke")]
+pub async fn revoke_item(
+    path: web::Path<ReviewItemPath>,
+    decision: Valid<ReviewDecision>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    decide(
+        &req,
+        &pool,
+        path.into_inner(),
+        decision.into_inner(),
+        Decision::Revoked,
+    )
+    .await
+}
+
+/// Close a campaign and return its summary. Items that were not decided on stay pending.
+#[post("/{uuid}/close")]
+pub async fn close_access_review(
+    path: web::Path<EntityPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ManageAccessReview,
+    )
+    .await?;
+
+    let review = find_access_review(&pool, &organization, &path).await?;
+
+    if review.state == ReviewState::Closed {
+        return Err(AppError::conflict("Access review is already closed!"));
+    }
+
+    match pool
+        .services
+        .access_review_service
+        .close(
+            &pool.database,
+            &organization,
+            &path,
+            &Utc::now().to_rfc3339(),
+        )
+        .await?
+    {
+        Some(d) => Ok(HttpResponse::Ok().json(summarize(d))),
+        None => Err(AppError::conflict("Access review is already closed!")),
+    }
+}
+
+/// Export the summary of a campaign as JSON or, with `?format=csv`, as CSV
+#[get("/{uuid}/report")]
+pub async fn get_report(
+    path: web::Path<EntityPath>,
+    query: web::Query<ReportQuery>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ReadAccessReview,
+    )
+    .await?;
+
+    let review = find_access_review(&pool, &organization, &path).await?;
+
+    let summary = summarize(review);
+    match query.format.as_deref().unwrap_or("json") {
+        "json" => Ok(HttpResponse::Ok().json(summary)),
+        "csv" => Ok(HttpResponse::Ok()
+            .content_type("text/csv")
+            .insert_header((
+                "Content-Disposition",
+                format!("attachment; filename=\"access-review-{}.csv\"", path),
+            ))
+            .body(to_csv(&summary))),
+        format => Err(AppError::validation(&format!(
+            "Unsupported format {}",
+            format
+        ))),
+    }
+}
+
+/// Record the decision of a reviewer on a single item of an open campaign
+async fn decide(
+    req: &HttpRequest,
+    pool: &web::Data<AppDataPool>,
+    path: ReviewItemPath,
+    decision: ReviewDecision,
+    next: Decision,
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization) = crate::routes::check_organization_user(req, pool).await?;
+
+    let review = find_access_review(pool, &organization, &path.uuid).await?;
+
+    if !review.reviewers.contains(&caller.id) {
+        return Err(AppError::from(DenialReason::MissingPermission));
+    }
+
+    let mut item = match review.items.into_iter().find(|i| i.id == path.item) {
+        Some(d) => d,
+        None => return Err(AppError::not_found("Review item not found!")),
+    };
+
+    // Reviewers cannot recertify their own access
+    if item.user_id == caller.id {
+        return Err(AppError::from(DenialReason::MissingPermission));
+    }
+
+    if review.state != ReviewState::Open {
+        return Err(AppError::validation("Access review is closed!"));
+    }
+
+    if item.decision != Decision::Pending {
+        return Err(AppError::conflict("Item was already decided on!"));
+    }
+
+    item.decision = next;
+    item.reviewer_id = Some(caller.id);
+    item.decided_at = Some(Utc::now().to_rfc3339());
+    item.comment = decision.comment;
+
+    let user_id = item.user_id.clone();
+    let role_id = item.role_id.clone();
+
+    let review = match pool
+        .services
+        .access_review_service
+        .decide_item(&pool.database, &organization, &path.uuid, item)
+        .await?
+    {
+        Some(d) => d,
+        None => {
+            return Err(AppError::validation(
+                "Access review was changed in the meantime!",
+            ));
+        }
+    };
+
+    if next == Decision::Revoked {
+        revoke_role(pool, &organization, &user_id, &role_id).await?;
+    }
+
+    Ok(HttpResponse::Ok().json(convert_access_review_to_dto(review)))
+}
+
+/// Remove a role from a user, if the user still exists and still holds it
+async fn revoke_role(
+    pool: &web::Data<AppDataPool>,
+    organization: &str,
+    user_id: &str,
+    role_id: &str,
+) -> Result<(), AppError> {
+    let mut user = match pool
+        .services
+        .user_service
+        .find_by_uuid(&pool.database, organization, user_id)
+        .await?
+    {
+        Some(d) => d,
+        None => return Ok(()),
+    };
+
+    if !user.roles.iter().any(|r| r == role_id) {
+        return Ok(());
+    }
+
+    user.roles.retain(|r| r != role_id);
+
+    match pool
+        .services
+        .user_service
+        .update(&pool.database, organization, user_id, user, None)
+        .await?
+    {
+        Some(_) => Ok(()),
+        None => Err(AppError::precondition_failed(
+            "User was changed by another request!",
+        )),
+    }
+}
+
+async fn find_access_review(
+    pool: &web::Data<AppDataPool>,
+    organization: &str,
+    uuid: &str,
+) -> Result<AccessReview, AppError> {
+    match pool
+        .services
+        .access_review_service
+        .find_by_uuid(&pool.database, organization, uuid)
+        .await?
+    {
+        Some(d) => Ok(d),
+        None => Err(AppError::not_found("Access review not found!")),
+    }
+}
+
+/// Find the users that a campaign covers. Without explicit users, all users of the organization
+/// are in scope.
+async fn find_users_in_scope(
+    pool: &web::Data<AppDataPool>,
+    organization: &str,
+    users: &[String],
+) -> Result<Vec<User>, AppError> {
+    if users.is_empty() {
+        return Ok(pool
+            .services
+            .user_service
+            .find_all(&pool.database, organization)
+            .await?);
+    }
+
+    let mut found = vec![];
+    for uuid in users {
+        match pool
+            .services
+            .user_service
+            .find_by_uuid(&pool.database, organization, uuid)
+            .await?
+        {
+            Some(d) => found.push(d),
+            None => {
+                return Err(AppError::validation(&format!("Invalid user {}", uuid)));
+            }
+        };
+    }
+
+    Ok(found)
+}
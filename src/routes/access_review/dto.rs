@@ -0,0 +1,5 @@
+pub mod access_review;
+pub mod create_access_review;
+pub mod report_query;
+pub mod review_decision;
+pub mod review_item_path;
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use crate::persistence::access_review::model::access_review::{
+    ReviewItem, ReviewScope, ReviewState,
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct AccessReview {
+    pub id: String,
+    pub organization: String,
+    pub name: String,
+    pub description: String,
+    pub scope: ReviewScope,
+    pub reviewers: Vec<String>,
+    pub state: ReviewState,
+    #[serde(rename(serialize = "createdBy", deserialize = "createdBy"))]
+    pub created_by: String,
+    #[serde(rename(serialize = "createdAt", deserialize = "createdAt"))]
+    pub created_at: String,
+    #[serde(rename(serialize = "closedAt", deserialize = "closedAt"))]
+    pub closed_at: Option<String>,
+    pub items: Vec<ReviewItem>,
+}
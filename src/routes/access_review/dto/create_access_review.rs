@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+use crate::routes::validation::{Validate, Validator};
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateAccessReview {
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub users: Vec<String>,
+    pub reviewers: Vec<String>,
+}
+
+impl Validate for CreateAccessReview {
+    fn validate(&self, v: &mut Validator) {
+        v.name("name", &self.name)
+            .description("description", &self.description)
+            .uuids("roles", &self.roles)
+            .uuids("users", &self.users)
+            .uuids("reviewers", &self.reviewers)
+            .check(
+                "roles",
+                !self.roles.is_empty() || !self.users.is_empty(),
+                "REQUIRED",
+                "An access review needs at least one role or user in scope!",
+            )
+            .check(
+                "reviewers",
+                !self.reviewers.is_empty(),
+                "REQUIRED",
+                "An access review needs at least one reviewer!",
+            );
+    }
+}
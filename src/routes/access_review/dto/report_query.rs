@@ -0,0 +1,7 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct ReportQuery {
+    /// Either `json` (the default) or `csv`
+    pub format: Option<String>,
+}
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+use crate::routes::validation::{Validate, Validator};
+
+#[derive(Serialize, Deserialize)]
+pub struct ReviewDecision {
+    pub comment: Option<String>,
+}
+
+impl Validate for ReviewDecision {
+    fn validate(&self, v: &mut Validator) {
+        if let Some(d) = &self.comment {
+            v.description("comment", d);
+        }
+    }
+}
@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct ReviewItemPath {
+    pub uuid: String,
+    pub item: String,
+}
@@ -0,0 +1,3 @@
+pub mod admin_route;
+pub mod dto;
+pub mod transfer;
@@ -0,0 +1,293 @@
+use std::collections::{HashMap, HashSet};
+
+use actix_web::{get, post, web, HttpMessage, HttpRequest, HttpResponse};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    configuration::app_data_pool::AppDataPool,
+    errors::app_error::AppError,
+    persistence::event::model::event::{Event, DATA_IMPORTED},
+    routes::{
+        admin::{
+            dto::{
+                export_query::ExportQuery, import_query::ImportQuery, import_report::ChangeAction,
+                transfer_document::TransferDocument,
+            },
+            transfer::{export_document, plan_import, users_from_csv, users_to_csv, ImportPlan},
+        },
+        check_constraints, check_granted_permissions,
+        validation::{Validate, Validator},
+    },
+    services::permission::system_permission::SystemPermission,
+};
+
+/// The largest document that can be imported at once
+pub const MAX_IMPORT_SIZE: usize = 16 * 1024 * 1024;
+
+/// Read an import document from a JSON body or, when the content type is `text/csv`, a CSV body
+/// that only contains users
+fn read_document(req: &HttpRequest, body: &[u8]) -> Result<TransferDocument, AppError> {
+    let document = if req.content_type() == "text/csv" {
+        users_from_csv(body)?
+    } else {
+        match serde_json::from_slice::<TransferDocument>(body) {
+            Ok(d) => d,
+            Err(e) => return Err(AppError::validation(&e.to_string())),
+        }
+    };
+
+    let mut validator = Validator::default();
+    document.validate(&mut validator);
+    validator.into_result()?;
+
+    Ok(document)
+}
+
+/// Make the changes of an import plan in one transaction, in an order in which every reference
+/// can be resolved
+async fn apply_import(
+    pool: &AppDataPool,
+    organization: &str,
+    plan: ImportPlan,
+) -> Result<(), AppError> {
+    let db = &pool.database;
+    let changed = |entity: &str, name: &str| {
+        AppError::PreconditionFailed(format!(
+            "{} {} was changed by another request!",
+            entity, name
+        ))
+    };
+
+    let mut transaction = pool.transactions.begin().await?;
+
+    for planned in plan.permissions {
+        let id = planned.entity.id.clone();
+        let name = planned.entity.name.clone();
+        let permission = planned.entity;
+        let res = match planned.action {
+            Some(ChangeAction::Create) => {
+                pool.services
+                    .permission_service
+                    .create(permission, db, transaction.session())
+                    .await?
+            }
+            Some(ChangeAction::Update) => {
+                pool.services
+                    .permission_service
+                    .update(db, organization, &id, permission, transaction.session())
+                    .await?
+            }
+            None => continue,
+        };
+        if res.is_none() {
+            return Err(changed("Permission", &name));
+        }
+    }
+
+    for planned in plan.roles {
+        let id = planned.entity.id.clone();
+        let name = planned.entity.name.clone();
+        let role = planned.entity;
+        let res = match planned.action {
+            Some(ChangeAction::Create) => {
+                pool.services
+                    .role_service
+                    .create(role, db, transaction.session())
+                    .await?
+            }
+            Some(ChangeAction::Update) => {
+                pool.services
+                    .role_service
+                    .update(db, organization, &id, role, transaction.session())
+                    .await?
+            }
+            None => continue,
+        };
+        if res.is_none() {
+            return Err(changed("Role", &name));
+        }
+    }
+
+    for planned in plan.users {
+        let id = planned.entity.id.clone();
+        let name = planned.entity.username.clone();
+        let user = planned.entity;
+        let res = match planned.action {
+            Some(ChangeAction::Create) => {
+                pool.services
+                    .user_service
+                    .create(user, db, transaction.session())
+                    .await?
+            }
+            Some(ChangeAction::Update) if planned.fields.iter().any(|f| f == "passwordHash") => {
+                pool.services
+                    .user_service
+                    .update_with_password(db, organization, &id, user, transaction.session())
+                    .await?
+            }
+            Some(ChangeAction::Update) => {
+                pool.services
+                    .user_service
+                    .update(db, organization, &id, user, transaction.session())
+                    .await?
+            }
+            None => continue,
+        };
+        if res.is_none() {
+            return Err(changed("User", &name));
+        }
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+/// Create and update permissions, roles and users from a document. Entities are matched by name,
+/// so importing the same document again changes nothing. With `?dryRun=true` the changes are only
+/// reported.
+#[post("/import")]
+pub async fn import_data(
+    query: web::Query<ImportQuery>,
+    body: web::Bytes,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::ImportData)
+            .await?;
+
+    let document = read_document(&req, &body)?;
+
+    let permissions = pool
+        .services
+        .permission_service
+        .find_all(&pool.database, &organization)
+        .await?;
+
+    let roles = pool
+        .services
+        .role_service
+        .find_all(&pool.database, &organization)
+        .await?;
+
+    let users = pool
+        .services
+        .user_service
+        .find_all(&pool.database, &organization)
+        .await?;
+
+    let plan = plan_import(document, &organization, permissions, roles, users)?;
+
+    check_granted_permissions(&req, &pool, &caller, &plan.granted).await?;
+
+    for planned in &plan.users {
+        let roles_changed = match planned.action {
+            Some(ChangeAction::Create) => true,
+            Some(ChangeAction::Update) => planned.fields.iter().any(|f| f == "roles"),
+            None => false,
+        };
+        if roles_changed {
+            let roles: HashSet<String> = planned.entity.roles.iter().cloned().collect();
+            check_constraints(&pool, &organization, &planned.entity.username, &roles).await?;
+        }
+    }
+
+    let dry_run = query.dry_run.unwrap_or(false);
+    let report = plan.report(dry_run);
+    if dry_run {
+        return Ok(HttpResponse::Ok().json(report));
+    }
+
+    apply_import(&pool, &organization, plan).await?;
+
+    let mut data = HashMap::new();
+    data.insert(String::from("actorId"), caller.id.clone());
+    data.insert(String::from("changes"), report.changes.len().to_string());
+
+    let event = Event {
+        id: Uuid::new_v4().to_string(),
+        event_type: String::from(DATA_IMPORTED),
+        organization,
+        user_id: Some(caller.id.clone()),
+        message: format!(
+            "{} imported {} changes",
+            caller.username,
+            report.changes.len()
+        ),
+        data,
+        timestamp: Utc::now().to_string(),
+    };
+
+    pool.services
+        .event_service
+        .create(event, &pool.database)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// Export the permissions, roles and users of an organization as a document that can be imported
+/// again or, with `?format=csv`, only the users as CSV
+#[get("/export")]
+pub async fn export_data(
+    query: web::Query<ExportQuery>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::ExportData)
+            .await?;
+
+    let format = query.format.as_deref().unwrap_or("json");
+    if format != "json" && format != "csv" {
+        return Err(AppError::validation(&format!(
+            "Unsupported format {}",
+            format
+        )));
+    }
+
+    let permissions = pool
+        .services
+        .permission_service
+        .find_all(&pool.database, &organization)
+        .await?;
+
+    let roles = pool
+        .services
+        .role_service
+        .find_all(&pool.database, &organization)
+        .await?;
+
+    let users = pool
+        .services
+        .user_service
+        .find_all(&pool.database, &organization)
+        .await?;
+
+    let document = export_document(
+        permissions,
+        roles,
+        users,
+        query.include_passwords.unwrap_or(false),
+    );
+
+    if format == "json" {
+        return Ok(HttpResponse::Ok()
+            .insert_header((
+                "Content-Disposition",
+                format!("attachment; filename=\"export-{}.json\"", organization),
+            ))
+            .json(document));
+    }
+
+    let csv = users_to_csv(&document)?;
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv")
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"users-{}.csv\"", organization),
+        ))
+        .body(csv))
+}
@@ -0,0 +1,4 @@
+pub mod export_query;
+pub mod import_query;
+pub mod import_report;
+pub mod transfer_document;
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct ExportQuery {
+    /// Either `json` (the default), which exports permissions, roles and users, or `csv`, which
+    /// exports users only
+    pub format: Option<String>,
+    /// Include the password hashes of users, so that they can sign in after being imported
+    #[serde(rename(serialize = "includePasswords", deserialize = "includePasswords"))]
+    pub include_passwords: Option<bool>,
+}
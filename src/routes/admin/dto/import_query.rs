@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct ImportQuery {
+    /// Report the changes that an import would make without making them
+    #[serde(rename(serialize = "dryRun", deserialize = "dryRun"))]
+    pub dry_run: Option<bool>,
+}
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ChangeAction {
+    Create,
+    Update,
+}
+
+/// A permission, role or user that an import creates or updates
+#[derive(Serialize, Deserialize)]
+pub struct ImportChange {
+    #[serde(rename(serialize = "type", deserialize = "type"))]
+    pub entity_type: String,
+    pub name: String,
+    pub action: ChangeAction,
+    /// The fields that an update changes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct ImportCounts {
+    pub created: u64,
+    pub updated: u64,
+    pub unchanged: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ImportReport {
+    #[serde(rename(serialize = "dryRun", deserialize = "dryRun"))]
+    pub dry_run: bool,
+    pub permissions: ImportCounts,
+    pub roles: ImportCounts,
+    pub users: ImportCounts,
+    pub changes: Vec<ImportChange>,
+}
@@ -0,0 +1,168 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    routes::validation::{Validate, Validator, MAX_NAME_LENGTH},
+    services::user::password::is_supported_hash,
+};
+
+/// The version of the document format that is written by exports and read by imports
+pub const TRANSFER_DOCUMENT_VERSION: u32 = 1;
+
+/// The permissions, roles and users of an organization. Entities refer to each other by name, so
+/// that a document can be moved between organizations and deployments.
+#[derive(Serialize, Deserialize)]
+pub struct TransferDocument {
+    pub version: u32,
+    #[serde(default)]
+    pub permissions: Vec<TransferPermission>,
+    #[serde(default)]
+    pub roles: Vec<TransferRole>,
+    #[serde(default)]
+    pub users: Vec<TransferUser>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TransferPermission {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TransferRole {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Names of permissions
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TransferUser {
+    pub username: String,
+    #[serde(rename(serialize = "emailAddress", deserialize = "emailAddress"))]
+    pub email_address: String,
+    #[serde(rename(serialize = "firstName", deserialize = "firstName"), default)]
+    pub first_name: String,
+    #[serde(rename(serialize = "lastName", deserialize = "lastName"), default)]
+    pub last_name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Names of roles that the user holds directly
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// Attributes are left alone when they are absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attributes: Option<HashMap<String, Value>>,
+    /// A bcrypt hash or an argon2 PHC string. The password is left alone when it is absent, but
+    /// new users need one.
+    #[serde(
+        rename(serialize = "passwordHash", deserialize = "passwordHash"),
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub password_hash: Option<String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Record an error for every name that occurs more than once
+fn unique_names<'a>(
+    v: &mut Validator,
+    list: &str,
+    field: &str,
+    names: impl Iterator<Item = &'a String>,
+) {
+    let mut seen = HashSet::new();
+    for (i, name) in names.enumerate() {
+        v.check(
+            &format!("{}[{}].{}", list, i, field),
+            seen.insert(name),
+            "DUPLICATE",
+            &format!("{} is listed more than once!", name),
+        );
+    }
+}
+
+impl Validate for TransferDocument {
+    fn validate(&self, v: &mut Validator) {
+        v.check(
+            "version",
+            self.version == TRANSFER_DOCUMENT_VERSION,
+            "VERSION",
+            &format!(
+                "Only version {} documents are supported!",
+                TRANSFER_DOCUMENT_VERSION
+            ),
+        );
+
+        for (i, permission) in self.permissions.iter().enumerate() {
+            v.name(&format!("permissions[{}].name", i), &permission.name)
+                .description(
+                    &format!("permissions[{}].description", i),
+                    &permission.description,
+                );
+        }
+        unique_names(
+            v,
+            "permissions",
+            "name",
+            self.permissions.iter().map(|p| &p.name),
+        );
+
+        for (i, role) in self.roles.iter().enumerate() {
+            v.name(&format!("roles[{}].name", i), &role.name)
+                .description(&format!("roles[{}].description", i), &role.description);
+            for (j, permission) in role.permissions.iter().enumerate() {
+                v.name(&format!("roles[{}].permissions[{}]", i, j), permission);
+            }
+        }
+        unique_names(v, "roles", "name", self.roles.iter().map(|r| &r.name));
+
+        for (i, user) in self.users.iter().enumerate() {
+            v.username(&format!("users[{}].username", i), &user.username)
+                .email(&format!("users[{}].emailAddress", i), &user.email_address)
+                .length(
+                    &format!("users[{}].firstName", i),
+                    &user.first_name,
+                    0,
+                    MAX_NAME_LENGTH,
+                )
+                .length(
+                    &format!("users[{}].lastName", i),
+                    &user.last_name,
+                    0,
+                    MAX_NAME_LENGTH,
+                );
+            for (j, role) in user.roles.iter().enumerate() {
+                v.name(&format!("users[{}].roles[{}]", i, j), role);
+            }
+            if let Some(hash) = &user.password_hash {
+                v.check(
+                    &format!("users[{}].passwordHash", i),
+                    is_supported_hash(hash),
+                    "PASSWORD_HASH",
+                    "Password hashes must be bcrypt hashes or argon2 PHC strings!",
+                );
+            }
+        }
+        unique_names(
+            v,
+            "users",
+            "username",
+            self.users.iter().map(|u| &u.username),
+        );
+        unique_names(
+            v,
+            "users",
+            "emailAddress",
+            self.users.iter().map(|u| &u.email_address),
+        );
+    }
+}
@@ -0,0 +1,575 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    errors::app_error::AppError,
+    persistence::{
+        permission::model::permission::Permission, role::model::role::Role, user::model::user::User,
+    },
+    routes::validation::FieldError,
+};
+
+use super::dto::{
+    import_report::{ChangeAction, ImportChange, ImportCounts, ImportReport},
+    transfer_document::{
+        TransferDocument, TransferPermission, TransferRole, TransferUser, TRANSFER_DOCUMENT_VERSION,
+    },
+};
+
+/// The separator of the role names in the roles column of a CSV export
+const CSV_ROLE_SEPARATOR: char = ';';
+
+/// What an import does to a single entity. Unchanged entities are planned without an action.
+pub struct Planned<T> {
+    pub entity: T,
+    pub action: Option<ChangeAction>,
+    pub fields: Vec<String>,
+}
+
+/// The changes that importing a document into an organization makes
+pub struct ImportPlan {
+    pub permissions: Vec<Planned<Permission>>,
+    pub roles: Vec<Planned<Role>>,
+    pub users: Vec<Planned<User>>,
+    /// Ids and names of the permissions that the import hands out, either by adding them to a
+    /// role or by giving a user a role that contains them
+    pub granted: Vec<(String, String)>,
+}
+
+/// A user in a CSV export, whose roles are names separated by semicolons. Role names that contain
+/// a semicolon can only be transferred with the JSON format.
+#[derive(Serialize, Deserialize)]
+struct UserRecord {
+    username: String,
+    #[serde(rename = "emailAddress")]
+    email_address: String,
+    #[serde(rename = "firstName", default)]
+    first_name: String,
+    #[serde(rename = "lastName", default)]
+    last_name: String,
+    enabled: bool,
+    #[serde(default)]
+    roles: String,
+    #[serde(rename = "passwordHash", default)]
+    password_hash: String,
+}
+
+impl<T> Planned<T> {
+    fn new(entity: T, action: Option<ChangeAction>, fields: Vec<String>) -> Self {
+        Planned {
+            entity,
+            action,
+            fields,
+        }
+    }
+}
+
+fn unknown_reference(errors: &mut Vec<FieldError>, field: String, entity: &str, name: &str) {
+    errors.push(FieldError {
+        field,
+        code: "UNKNOWN_REFERENCE",
+        message: format!("{} {} does not exist!", entity, name),
+    });
+}
+
+fn managed(errors: &mut Vec<FieldError>, field: String, name: &str) {
+    errors.push(FieldError {
+        field,
+        code: "MANAGED",
+        message: format!("{} is managed by the seed manifest!", name),
+    });
+}
+
+fn grant(granted: &mut Vec<(String, String)>, names: &HashMap<String, String>, id: &str) {
+    if let Some(name) = names.get(id) {
+        if !granted.iter().any(|(i, _)| i == id) {
+            granted.push((String::from(id), name.clone()));
+        }
+    }
+}
+
+fn same_members(a: &[String], b: &[String]) -> bool {
+    a.iter().collect::<HashSet<_>>() == b.iter().collect::<HashSet<_>>()
+}
+
+/// Match the entities of a document with the existing entities of an organization by name and
+/// plan the creates and updates that make the organization match the document. Entities that are
+/// not part of the document are left alone, so importing the same document twice is harmless.
+pub fn plan_import(
+    document: TransferDocument,
+    organization: &str,
+    permissions: Vec<Permission>,
+    roles: Vec<Role>,
+    users: Vec<User>,
+) -> Result<ImportPlan, AppError> {
+    let mut errors = vec![];
+
+    let mut existing_permissions: HashMap<String, Permission> = permissions
+        .into_iter()
+        .map(|p| (p.name.clone(), p))
+        .collect();
+    let mut planned_permissions = vec![];
+    for (i, permission) in document.permissions.into_iter().enumerate() {
+        let existing = existing_permissions.remove(&permission.name);
+        let planned = plan_permission(permission, organization, existing);
+        if planned.entity.managed && planned.action.is_some() {
+            managed(
+                &mut errors,
+                format!("permissions[{}].name", i),
+                &planned.entity.name,
+            );
+        }
+        planned_permissions.push(planned);
+    }
+
+    // Names of all permissions after the import, and the other way around
+    let mut permission_ids: HashMap<String, String> = HashMap::new();
+    let mut permission_names: HashMap<String, String> = HashMap::new();
+    for permission in existing_permissions
+        .values()
+        .chain(planned_permissions.iter().map(|p| &p.entity))
+    {
+        permission_ids.insert(permission.name.clone(), permission.id.clone());
+        permission_names.insert(permission.id.clone(), permission.name.clone());
+    }
+
+    let mut granted: Vec<(String, String)> = vec![];
+
+    let mut existing_roles: HashMap<String, Role> =
+        roles.into_iter().map(|r| (r.name.clone(), r)).collect();
+    let mut planned_roles = vec![];
+    for (i, role) in document.roles.into_iter().enumerate() {
+        let mut ids = vec![];
+        for (j, name) in role.permissions.iter().enumerate() {
+            match permission_ids.get(name) {
+                Some(d) => ids.push(d.clone()),
+                None => unknown_reference(
+                    &mut errors,
+                    format!("roles[{}].permissions[{}]", i, j),
+                    "Permission",
+                    name,
+                ),
+            }
+        }
+
+        let existing = existing_roles.remove(&role.name);
+        let held: Vec<String> = existing
+            .as_ref()
+            .map(|r| r.permissions.clone())
+            .unwrap_or_default();
+        for id in ids.iter().filter(|d| !held.contains(d)) {
+            grant(&mut granted, &permission_names, id);
+        }
+
+        let planned = plan_role(role, organization, ids, existing);
+        if planned.entity.managed && planned.action.is_some() {
+            managed(
+                &mut errors,
+                format!("roles[{}].name", i),
+                &planned.entity.name,
+            );
+        }
+        planned_roles.push(planned);
+    }
+
+    let mut role_ids: HashMap<String, String> = HashMap::new();
+    let mut role_permissions: HashMap<String, Vec<String>> = HashMap::new();
+    for role in existing_roles
+        .values()
+        .chain(planned_roles.iter().map(|r| &r.entity))
+    {
+        role_ids.insert(role.name.clone(), role.id.clone());
+        role_permissions.insert(role.id.clone(), role.permissions.clone());
+    }
+
+    let email_owners: HashMap<String, String> = users
+        .iter()
+        .map(|u| (u.email_address.clone(), u.username.clone()))
+        .collect();
+    let mut existing_users: HashMap<String, User> =
+        users.into_iter().map(|u| (u.username.clone(), u)).collect();
+    let mut planned_users = vec![];
+    for (i, user) in document.users.into_iter().enumerate() {
+        let mut ids = vec![];
+        for (j, name) in user.roles.iter().enumerate() {
+            match role_ids.get(name) {
+                Some(d) => ids.push(d.clone()),
+                None => unknown_reference(
+                    &mut errors,
+                    format!("users[{}].roles[{}]", i, j),
+                    "Role",
+                    name,
+                ),
+            }
+        }
+
+        if let Some(owner) = email_owners.get(&user.email_address) {
+            if owner != &user.username {
+                errors.push(FieldError {
+                    field: format!("users[{}].emailAddress", i),
+                    code: "TAKEN",
+                    message: format!("Email address is already taken by {}!", owner),
+                });
+            }
+        }
+
+        let existing = existing_users.remove(&user.username);
+        match &existing {
+            Some(d) if d.service_account => errors.push(FieldError {
+                field: format!("users[{}].username", i),
+                code: "SERVICE_ACCOUNT",
+                message: format!("{} is a service account!", d.username),
+            }),
+            None if user.password_hash.is_none() => errors.push(FieldError {
+                field: format!("users[{}].passwordHash", i),
+                code: "REQUIRED",
+                message: String::from("New users need a password hash!"),
+            }),
+            _ => {}
+        }
+
+        let held: Vec<String> = existing
+            .as_ref()
+            .map(|u| u.roles.clone())
+            .unwrap_or_default();
+        for role in ids.iter().filter(|d| !held.contains(d)) {
+            for id in role_permissions.get(role).into_iter().flatten() {
+                grant(&mut granted, &permission_names, id);
+            }
+        }
+
+        planned_users.push(plan_user(user, organization, ids, existing));
+    }
+
+    if !errors.is_empty() {
+        return Err(AppError::InvalidFields(errors));
+    }
+
+    Ok(ImportPlan {
+        permissions: planned_permissions,
+        roles: planned_roles,
+        users: planned_users,
+        granted,
+    })
+}
+
+fn plan_permission(
+    permission: TransferPermission,
+    organization: &str,
+    existing: Option<Permission>,
+) -> Planned<Permission> {
+    let mut current = match existing {
+        Some(d) => d,
+        None => {
+            return Planned::new(
+                Permission {
+                    id: Uuid::new_v4().to_string(),
+                    name: permission.name,
+                    description: permission.description,
+                    organization: String::from(organization),
+                    version: 0,
+                    managed: false,
+                },
+                Some(ChangeAction::Create),
+                vec![],
+            )
+        }
+    };
+
+    let mut fields = vec![];
+    if current.description != permission.description {
+        current.description = permission.description;
+        fields.push(String::from("description"));
+    }
+
+    let action = (!fields.is_empty()).then_some(ChangeAction::Update);
+    Planned::new(current, action, fields)
+}
+
+fn plan_role(
+    role: TransferRole,
+    organization: &str,
+    permissions: Vec<String>,
+    existing: Option<Role>,
+) -> Planned<Role> {
+    let mut current = match existing {
+        Some(d) => d,
+        None => {
+            return Planned::new(
+                Role {
+                    id: Uuid::new_v4().to_string(),
+                    name: role.name,
+                    description: role.description,
+                    permissions,
+                    approver_permissions: vec![],
+                    organization: String::from(organization),
+                    version: 0,
+                    managed: false,
+                },
+                Some(ChangeAction::Create),
+                vec![],
+            )
+        }
+    };
+
+    let mut fields = vec![];
+    if current.description != role.description {
+        current.description = role.description;
+        fields.push(String::from("description"));
+    }
+    if !same_members(&current.permissions, &permissions) {
+        current.permissions = permissions;
+        fields.push(String::from("permissions"));
+    }
+
+    let action = (!fields.is_empty()).then_some(ChangeAction::Update);
+    Planned::new(current, action, fields)
+}
+
+fn plan_user(
+    user: TransferUser,
+    organization: &str,
+    roles: Vec<String>,
+    existing: Option<User>,
+) -> Planned<User> {
+    let mut current = match existing {
+        Some(d) => d,
+        None => {
+            return Planned::new(
+                User {
+                    id: Uuid::new_v4().to_string(),
+                    username: user.username,
+                    email_address: user.email_address,
+                    password: user.password_hash.unwrap_or_default(),
+                    first_name: user.first_name,
+                    last_name: user.last_name,
+                    enabled: user.enabled,
+                    platform_admin: false,
+                    service_account: false,
+                    roles,
+                    role_assignments: vec![],
+                    created_at: Utc::now().to_string(),
+                    last_active: String::from(""),
+                    attributes: user.attributes.unwrap_or_default(),
+                    organization: String::from(organization),
+                    version: 0,
+                },
+                Some(ChangeAction::Create),
+                vec![],
+            )
+        }
+    };
+
+    let mut fields = vec![];
+    if current.email_address != user.email_address {
+        current.email_address = user.email_address;
+        fields.push(String::from("emailAddress"));
+    }
+    if current.first_name != user.first_name {
+        current.first_name = user.first_name;
+        fields.push(String::from("firstName"));
+    }
+    if current.last_name != user.last_name {
+        current.last_name = user.last_name;
+        fields.push(String::from("lastName"));
+    }
+    if current.enabled != user.enabled {
+        current.enabled = user.enabled;
+        fields.push(String::from("enabled"));
+    }
+    if !same_members(&current.roles, &roles) {
+        current.roles = roles;
+        fields.push(String::from("roles"));
+    }
+    if let Some(d) = user.attributes {
+        if current.attributes != d {
+            current.attributes = d;
+            fields.push(String::from("attributes"));
+        }
+    }
+
+    if let Some(d) = user.password_hash {
+        if current.password != d {
+            current.password = d;
+            fields.push(String::from("passwordHash"));
+        }
+    }
+
+    let action = (!fields.is_empty()).then_some(ChangeAction::Update);
+    Planned::new(current, action, fields)
+}
+
+fn count<T>(planned: &[Planned<T>]) -> ImportCounts {
+    let mut counts = ImportCounts::default();
+    for item in planned {
+        match item.action {
+            Some(ChangeAction::Create) => counts.created += 1,
+            Some(ChangeAction::Update) => counts.updated += 1,
+            None => counts.unchanged += 1,
+        }
+    }
+    counts
+}
+
+fn changes<T>(
+    planned: &[Planned<T>],
+    entity_type: &str,
+    name: impl Fn(&T) -> &str,
+) -> Vec<ImportChange> {
+    planned
+        .iter()
+        .filter_map(|p| {
+            p.action.map(|action| ImportChange {
+                entity_type: String::from(entity_type),
+                name: String::from(name(&p.entity)),
+                action,
+                fields: p.fields.clone(),
+            })
+        })
+        .collect()
+}
+
+impl ImportPlan {
+    /// A report of the changes of the plan, without the entities that it leaves unchanged
+    pub fn report(&self, dry_run: bool) -> ImportReport {
+        let mut all_changes = changes(&self.permissions, "permission", |p| &p.name);
+        all_changes.extend(changes(&self.roles, "role", |r| &r.name));
+        all_changes.extend(changes(&self.users, "user", |u| &u.username));
+
+        ImportReport {
+            dry_run,
+            permissions: count(&self.permissions),
+            roles: count(&self.roles),
+            users: count(&self.users),
+            changes: all_changes,
+        }
+    }
+}
+
+/// Export the permissions, roles and users of an organization, ordered by name. Service accounts
+/// are left out, since they are bound to the deployment that they were created in.
+pub fn export_document(
+    mut permissions: Vec<Permission>,
+    mut roles: Vec<Role>,
+    mut users: Vec<User>,
+    include_passwords: bool,
+) -> TransferDocument {
+    permissions.sort_by(|a, b| a.name.cmp(&b.name));
+    roles.sort_by(|a, b| a.name.cmp(&b.name));
+    users.retain(|u| !u.service_account);
+    users.sort_by(|a, b| a.username.cmp(&b.username));
+
+    let permission_names: HashMap<&str, &str> = permissions
+        .iter()
+        .map(|p| (p.id.as_str(), p.name.as_str()))
+        .collect();
+    let role_names: HashMap<&str, &str> = roles
+        .iter()
+        .map(|r| (r.id.as_str(), r.name.as_str()))
+        .collect();
+    let names = |ids: &[String], lookup: &HashMap<&str, &str>| -> Vec<String> {
+        ids.iter()
+            .filter_map(|d| lookup.get(d.as_str()).map(|n| String::from(*n)))
+            .collect()
+    };
+
+    TransferDocument {
+        version: TRANSFER_DOCUMENT_VERSION,
+        roles: roles
+            .iter()
+            .map(|r| TransferRole {
+                name: r.name.clone(),
+                description: r.description.clone(),
+                permissions: names(&r.permissions, &permission_names),
+            })
+            .collect(),
+        users: users
+            .into_iter()
+            .map(|u| TransferUser {
+                roles: names(&u.roles, &role_names),
+                username: u.username,
+                email_address: u.email_address,
+                first_name: u.first_name,
+                last_name: u.last_name,
+                enabled: u.enabled,
+                attributes: Some(u.attributes),
+                password_hash: include_passwords.then_some(u.password),
+            })
+            .collect(),
+        permissions: permissions
+            .into_iter()
+            .map(|p| TransferPermission {
+                name: p.name,
+                description: p.description,
+            })
+            .collect(),
+    }
+}
+
+/// Read a document that only contains users from a CSV file
+pub fn users_from_csv(body: &[u8]) -> Result<TransferDocument, AppError> {
+    let mut reader = csv::Reader::from_reader(body);
+    let mut users = vec![];
+    for (i, record) in reader.deserialize::<UserRecord>().enumerate() {
+        let record = match record {
+            Ok(d) => d,
+            Err(e) => {
+                return Err(AppError::validation(&format!(
+                    "Invalid CSV record {}: {}",
+                    i + 1,
+                    e
+                )))
+            }
+        };
+
+        users.push(TransferUser {
+            username: record.username,
+            email_address: record.email_address,
+            first_name: record.first_name,
+            last_name: record.last_name,
+            enabled: record.enabled,
+            roles: record
+                .roles
+                .split(CSV_ROLE_SEPARATOR)
+                .map(str::trim)
+                .filter(|r| !r.is_empty())
+                .map(String::from)
+                .collect(),
+            attributes: None,
+            password_hash: Some(record.password_hash).filter(|d| !d.is_empty()),
+        });
+    }
+
+    Ok(TransferDocument {
+        version: TRANSFER_DOCUMENT_VERSION,
+        permissions: vec![],
+        roles: vec![],
+        users,
+    })
+}
+
+/// Write the users of a document to a CSV file
+pub fn users_to_csv(document: &TransferDocument) -> Result<Vec<u8>, AppError> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    for user in &document.users {
+        let record = UserRecord {
+            username: user.username.clone(),
+            email_address: user.email_address.clone(),
+            first_name: user.first_name.clone(),
+            last_name: user.last_name.clone(),
+            enabled: user.enabled,
+            roles: user.roles.join(&CSV_ROLE_SEPARATOR.to_string()),
+            password_hash: user.password_hash.clone().unwrap_or_default(),
+        };
+        if let Err(e) = writer.serialize(record) {
+            return Err(AppError::Internal(e.to_string()));
+        }
+    }
+
+    writer
+        .into_inner()
+        .map_err(|e| AppError::Internal(e.to_string()))
+}
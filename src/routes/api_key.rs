@@ -0,0 +1,2 @@
+pub mod api_key_route;
+pub mod dto;
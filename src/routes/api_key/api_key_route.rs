@@ -0,0 +1,257 @@
+use std::collections::HashSet;
+
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    configuration::app_data_pool::AppDataPool,
+    errors::app_error::AppError,
+    persistence::{
+        api_key::model::api_key::ApiKey,
+        user::model::{role_assignment::parse_timestamp, user::User},
+    },
+    routes::{
+        api_key::dto::{
+            api_key_query::ApiKeyQuery, create_api_key::CreateApiKey,
+            created_api_key::CreatedApiKey,
+        },
+        api_key_restriction,
+        authorization::dto::denial_reason::DenialReason,
+        check_granted_permissions, convert_api_key_to_dto, decode_token, effective_permissions,
+        evaluate_caller_permission,
+        validation::Valid,
+        EntityPath,
+    },
+    services::{
+        api_key::api_key_service::{generate_key, hash_key},
+        permission::system_permission::SystemPermission,
+    },
+};
+
+#[post("/")]
+pub async fn create_api_key(
+    create: Valid<CreateApiKey>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::CreateApiKey)
+            .await?;
+
+    // Impersonators must not be able to mint credentials that outlive the impersonation
+    if matches!(decode_token(&req, &pool), Ok(d) if d.act.is_some()) {
+        return Err(AppError::from(DenialReason::ImpersonationDenied));
+    }
+
+    let owner = check_owner(
+        &req,
+        &pool,
+        &caller,
+        &organization,
+        create.owner_id.as_deref(),
+    )
+    .await?;
+
+    if let Some(expires_at) = &create.expires_at {
+        match parse_timestamp(expires_at) {
+            Some(d) if d > Utc::now() => {}
+            Some(_) => {
+                return Err(AppError::validation("Expiry must be in the future!"));
+            }
+            None => {
+                return Err(AppError::validation(
+                    "Expiry must be an RFC 3339 timestamp!",
+                ));
+            }
+        }
+    }
+
+    let held = effective_permissions(
+        &pool.database,
+        &owner,
+        &pool.services.role_service,
+        &pool.services.permission_service,
+        &pool.services.group_service,
+    )
+    .await?;
+
+    if let Some(permissions) = &create.permissions {
+        for permission in permissions {
+            if !held.contains(permission) {
+                return Err(AppError::validation(&format!(
+                    "The owner of the key does not hold permission {}",
+                    permission
+                )));
+            }
+        }
+    }
+
+    // The key can use every permission of its owner unless it is restricted, so the caller must be
+    // able to grant those permissions, or keys of service accounts would escalate their privileges
+    let exposed = match &create.permissions {
+        Some(d) => d.iter().collect::<HashSet<_>>(),
+        None => held.iter().collect::<HashSet<_>>(),
+    };
+    let granted: Vec<(String, String)> = pool
+        .services
+        .permission_service
+        .find_all(&pool.database, &organization)
+        .await?
+        .into_iter()
+        .filter(|p| exposed.contains(&p.name))
+        .map(|p| (p.id, p.name))
+        .collect();
+    check_granted_permissions(&req, &pool, &caller, &granted).await?;
+
+    // A restricted key can only create keys that are restricted at least as much
+    if let Some(allowed) = api_key_restriction(&req) {
+        let within = match &create.permissions {
+            Some(d) => d.iter().all(|p| allowed.contains(p)),
+            None => false,
+        };
+
+        if !within {
+            return Err(AppError::from(DenialReason::RestrictedApiKey));
+        }
+    }
+
+    let (prefix, key) = generate_key();
+    let api_key = ApiKey {
+        id: Uuid::new_v4().to_string(),
+        organization,
+        owner_id: owner.id,
+        name: create.name.clone(),
+        prefix,
+        hash: hash_key(&key),
+        permissions: create.permissions.clone(),
+        created_by: caller.id,
+        created_at: Utc::now().to_rfc3339(),
+        expires_at: create.expires_at.clone(),
+        last_used: None,
+        revoked_at: None,
+    };
+
+    match pool
+        .services
+        .api_key_service
+        .create(api_key, &pool.database)
+        .await?
+    {
+        Some(d) => Ok(HttpResponse::Ok().json(CreatedApiKey {
+            api_key: convert_api_key_to_dto(d),
+            key,
+        })),
+        None => Err(AppError::storage("Unable to create API key!")),
+    }
+}
+
+/// List the keys of the caller or, with `?ownerId=`, of a service account
+#[get("/")]
+pub async fn get_api_keys(
+    query: web::Query<ApiKeyQuery>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization) = crate::routes::check_organization_user(&req, &pool).await?;
+
+    let owner = check_owner(
+        &req,
+        &pool,
+        &caller,
+        &organization,
+        query.owner_id.as_deref(),
+    )
+    .await?;
+
+    let api_keys = pool
+        .services
+        .api_key_service
+        .find_by_owner(&pool.database, &organization, &owner.id)
+        .await?;
+    Ok(HttpResponse::Ok().json(
+        api_keys
+            .into_iter()
+            .map(convert_api_key_to_dto)
+            .collect::<Vec<_>>(),
+    ))
+}
+
+#[delete("/{uuid}")]
+pub async fn revoke_api_key(
+    path: web::Path<EntityPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (caller, organization) = crate::routes::check_organization_user(&req, &pool).await?;
+
+    let api_key = match pool
+        .services
+        .api_key_service
+        .find_by_uuid(&pool.database, &organization, &path)
+        .await?
+    {
+        Some(d) => d,
+        None => return Err(AppError::not_found("API key not found!")),
+    };
+
+    check_owner(&req, &pool, &caller, &organization, Some(&api_key.owner_id)).await?;
+
+    match pool
+        .services
+        .api_key_service
+        .revoke(
+            &pool.database,
+            &organization,
+            &path,
+            &Utc::now().to_rfc3339(),
+        )
+        .await?
+    {
+        Some(d) => Ok(HttpResponse::Ok().json(convert_api_key_to_dto(d))),
+        None => Err(AppError::conflict("API key was already revoked!")),
+    }
+}
+
+/// Find the owner of the keys that are being managed. Callers manage their own keys, the keys of
+/// service accounts require `CAN_MANAGE_SERVICE_ACCOUNT`.
+async fn check_owner(
+    req: &HttpRequest,
+    pool: &web::Data<AppDataPool>,
+    caller: &User,
+    organization: &str,
+    owner_id: Option<&str>,
+) -> Result<User, AppError> {
+    let owner_id = match owner_id {
+        Some(d) if d != caller.id => d,
+        _ => return Ok(caller.clone()),
+    };
+
+    let owner = match pool
+        .services
+        .user_service
+        .find_by_uuid(&pool.database, organization, owner_id)
+        .await?
+    {
+        Some(d) if d.service_account => d,
+        _ => {
+            return Err(AppError::validation(&format!(
+                "Invalid service account {}",
+                owner_id
+            )));
+        }
+    };
+
+    let trace = evaluate_caller_permission(
+        req,
+        pool,
+        caller,
+        SystemPermission::ManageServiceAccount.name(),
+    )
+    .await;
+    match trace.reason {
+        None => Ok(owner),
+        Some(reason) => Err(AppError::from(reason)),
+    }
+}
@@ -0,0 +1,4 @@
+pub mod api_key;
+pub mod api_key_query;
+pub mod create_api_key;
+pub mod created_api_key;
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: String,
+    pub organization: String,
+    #[serde(rename(serialize = "ownerId", deserialize = "ownerId"))]
+    pub owner_id: String,
+    pub name: String,
+    pub prefix: String,
+    pub permissions: Option<Vec<String>>,
+    #[serde(rename(serialize = "createdBy", deserialize = "createdBy"))]
+    pub created_by: String,
+    #[serde(rename(serialize = "createdAt", deserialize = "createdAt"))]
+    pub created_at: String,
+    #[serde(rename(serialize = "expiresAt", deserialize = "expiresAt"))]
+    pub expires_at: Option<String>,
+    #[serde(rename(serialize = "lastUsed", deserialize = "lastUsed"))]
+    pub last_used: Option<String>,
+    #[serde(rename(serialize = "revokedAt", deserialize = "revokedAt"))]
+    pub revoked_at: Option<String>,
+}
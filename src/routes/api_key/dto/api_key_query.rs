@@ -0,0 +1,8 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct ApiKeyQuery {
+    /// The service account to list the keys of. Defaults to the caller.
+    #[serde(rename(deserialize = "ownerId"))]
+    pub owner_id: Option<String>,
+}
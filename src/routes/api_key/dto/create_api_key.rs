@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+use crate::routes::validation::{Validate, Validator};
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateApiKey {
+    pub name: String,
+    /// The service account that will own the key. Defaults to the caller.
+    #[serde(rename(serialize = "ownerId", deserialize = "ownerId"))]
+    pub owner_id: Option<String>,
+    /// The names of the permissions of the owner that the key is restricted to
+    pub permissions: Option<Vec<String>>,
+    #[serde(rename(serialize = "expiresAt", deserialize = "expiresAt"))]
+    pub expires_at: Option<String>,
+}
+
+impl Validate for CreateApiKey {
+    fn validate(&self, v: &mut Validator) {
+        v.name("name", &self.name);
+        if let Some(d) = &self.owner_id {
+            v.uuid("ownerId", d);
+        }
+        for (i, permission) in self.permissions.iter().flatten().enumerate() {
+            v.required(&format!("permissions[{}]", i), permission);
+        }
+    }
+}
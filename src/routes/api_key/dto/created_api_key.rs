@@ -0,0 +1,11 @@
+use serde::Serialize;
+
+use super::api_key::ApiKey;
+
+/// A newly created key. This is the only time that the key itself is returned.
+#[derive(Serialize)]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
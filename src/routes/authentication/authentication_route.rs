@@ -1,87 +1,273 @@
-use actix_web::{get, post, put, web, HttpRequest, HttpResponse};
-use bcrypt::{hash, verify, DEFAULT_COST};
+use std::collections::HashMap;
+
+use actix_web::{
+    cookie::{time, Cookie, SameSite},
+    delete, get, post, put, web, HttpRequest, HttpResponse,
+};
+use bcrypt::{hash, DEFAULT_COST};
 use chrono::Utc;
 use jsonwebtoken::{EncodingKey, Header};
-use regex::Regex;
 use uuid::Uuid;
 
 use crate::{
     configuration::app_data_pool::AppDataPool,
-    errors::{bad_request::BadRequest, internal_server_error::InternalServerError},
-    persistence::user::model::user::User,
+    errors::app_error::AppError,
+    persistence::{
+        event::model::event::{Event, IMPOSSIBLE_TRAVEL, NEW_DEVICE_LOGIN, NEW_NETWORK_LOGIN},
+        login::model::login_attempt::{LoginAlert, LoginAttempt, LoginOutcome},
+        session::model::session::Session,
+        user::model::user::User,
+    },
     routes::{
         authentication::dto::{
             authentication_request::AuthenticationRequest,
-            authentication_response::{AuthenticationResponse, Claims},
+            authentication_response::{
+                AuthenticationResponse, Claims, CookieAuthenticationResponse,
+            },
+            current_user::{Actor, CurrentUser},
             register_request::RegisterRequest,
             update_request::UpdateRequest,
         },
-        convert_user_to_dto, get_user_uuid_from_token,
+        authorization::dto::denial_reason::DenialReason,
+        check_session, client_ip, convert_login_attempt_to_dto, convert_session_to_dto,
+        convert_user_to_dto, decode_token, request_organization,
         user::dto::update_password::UpdatePassword,
-        EMAIL_REGEX_PATTERN,
+        user_agent,
+        validation::Valid,
+        EntityPath, CSRF_COOKIE, TOKEN_COOKIE,
+    },
+    services::{
+        login::login_service::{fingerprint, network},
+        session::session_service::device_label,
+        user::password::{verify_dummy_password, verify_password},
     },
 };
 
+/// Decode the token of the caller, making sure that it belongs to the organization in the path,
+/// that its session was not revoked and that the actor behind an impersonation token is still
+/// enabled
+async fn current_claims(
+    req: &HttpRequest,
+    pool: &web::Data<AppDataPool>,
+) -> Result<Claims, DenialReason> {
+    let claims = decode_token(req, pool)?;
+    check_session(pool, &claims).await?;
+
+    if let Some(act) = &claims.act {
+        match pool
+            .services
+            .user_service
+            .find_by_uuid(&pool.database, &act.org, &act.sub)
+            .await
+        {
+            Ok(Some(d)) if d.enabled => {}
+            Ok(_) => return Err(DenialReason::InvalidToken),
+            Err(_) => return Err(DenialReason::StorageFailure),
+        }
+    }
+
+    if let Some(x) = req.match_info().get("org") {
+        if x != claims.org {
+            return Err(DenialReason::ForeignOrganization);
+        }
+    }
+
+    Ok(claims)
+}
+
+async fn check_organization_enabled(
+    pool: &web::Data<AppDataPool>,
+    organization: &str,
+) -> Result<(), AppError> {
+    match pool
+        .services
+        .organization_service
+        .find_by_id(&pool.database, organization)
+        .await?
+    {
+        Some(x) if x.enabled => Ok(()),
+        _ => Err(AppError::not_found("Organization not found!")),
+    }
+}
+
+/// Record an attempt to authenticate. Successful logins are compared with the earlier logins of
+/// the user and every alert is recorded as an event and handed to the notifier.
+async fn record_login(
+    pool: &web::Data<AppDataPool>,
+    req: &HttpRequest,
+    organization: &str,
+    username: &str,
+    user_id: Option<&str>,
+    outcome: LoginOutcome,
+) -> Result<(), AppError> {
+    let ip = client_ip(req);
+    let user_agent = user_agent(req);
+    let mut attempt = LoginAttempt {
+        id: Uuid::new_v4().to_string(),
+        organization: String::from(organization),
+        user_id: user_id.map(String::from),
+        username: String::from(username),
+        outcome,
+        fingerprint: fingerprint(user_agent.as_deref()),
+        network: ip.as_deref().and_then(network),
+        country: ip
+            .as_deref()
+            .and_then(|d| pool.services.login_service.locate(d)),
+        ip,
+        user_agent,
+        alerts: vec![],
+        timestamp: Utc::now().to_rfc3339(),
+    };
+
+    if outcome == LoginOutcome::Success {
+        attempt.alerts = pool
+            .services
+            .login_service
+            .detect(&pool.database, &attempt)
+            .await?;
+    }
+
+    pool.services
+        .login_service
+        .create(attempt.clone(), &pool.database)
+        .await?;
+
+    for alert in &attempt.alerts {
+        let (event_type, message) = match alert {
+            LoginAlert::NewDevice => (NEW_DEVICE_LOGIN, "a new device"),
+            LoginAlert::NewNetwork => (NEW_NETWORK_LOGIN, "a new network"),
+            LoginAlert::ImpossibleTravel => (IMPOSSIBLE_TRAVEL, "an unlikely location"),
+        };
+
+        let mut data = HashMap::new();
+        data.insert(String::from("loginId"), attempt.id.clone());
+        for (key, value) in [
+            ("ip", &attempt.ip),
+            ("userAgent", &attempt.user_agent),
+            ("country", &attempt.country),
+        ] {
+            if let Some(d) = value {
+                data.insert(String::from(key), d.clone());
+            }
+        }
+
+        let event = Event {
+            id: Uuid::new_v4().to_string(),
+            event_type: String::from(event_type),
+            organization: attempt.organization.clone(),
+            user_id: attempt.user_id.clone(),
+            message: format!("{} signed in from {}", attempt.username, message),
+            data,
+            timestamp: Utc::now().to_string(),
+        };
+
+        pool.services
+            .event_service
+            .create(event, &pool.database)
+            .await?;
+
+        pool.services.login_service.notify(&attempt, *alert);
+    }
+
+    Ok(())
+}
+
 #[post("/authenticate")]
 pub async fn authenticate(
     pool: web::Data<AppDataPool>,
-    login: web::Json<AuthenticationRequest>,
-) -> HttpResponse {
-    if login.username.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Username cannot be empty!"));
-    }
-    if login.password.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Password cannot be empty!"));
+    login: Valid<AuthenticationRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    if login.cookie && !pool.cookies.enabled {
+        return Err(AppError::validation("Cookie sessions are disabled!"));
     }
 
-    let user = match pool
+    let organization = request_organization(&req, login.organization.as_deref());
+    check_organization_enabled(&pool, &organization).await?;
+
+    let user = pool
         .services
         .user_service
-        .find_by_username(&pool.database, &login.username)
-        .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
+        .find_by_username(&pool.database, &organization, &login.username)
+        .await?;
+
+    let outcome = match &user {
+        None => {
+            verify_dummy_password(&login.password);
+            LoginOutcome::UnknownUser
+        }
+        Some(d) if !d.enabled || d.service_account => {
+            verify_dummy_password(&login.password);
+            LoginOutcome::DisabledUser
         }
+        Some(d) => match verify_password(&login.password, &d.password) {
+            Ok(true) => LoginOutcome::Success,
+            Ok(false) => LoginOutcome::InvalidPassword,
+            Err(e) => return Err(AppError::Crypto(e)),
+        },
     };
 
+    let user_id = user.as_ref().map(|u| u.id.as_str());
+    record_login(
+        &pool,
+        &req,
+        &organization,
+        &login.username,
+        user_id,
+        outcome,
+    )
+    .await?;
+
     let user = match user {
-        None => return HttpResponse::Unauthorized().body(""),
-        Some(d) => {
-            if !d.enabled {
-                return HttpResponse::Unauthorized().body("");
-            }
-            d
+        Some(d) if outcome == LoginOutcome::Success => d,
+        _ => {
+            return Err(AppError::Unauthorized(DenialReason::InvalidCredentials));
         }
     };
 
-    let valid = verify(&login.password, &user.password);
-    match valid {
-        Ok(d) => {
-            if !d {
-                return HttpResponse::Unauthorized().body("");
-            }
-        }
-        Err(_) => return HttpResponse::InternalServerError().body(""),
-    }
-
     let res = pool
         .services
         .user_service
-        .update_last_active(&pool.database, &user.id, &Utc::now().to_string())
+        .update_last_active(
+            &pool.database,
+            &organization,
+            &user.id,
+            &Utc::now().to_string(),
+        )
         .await;
 
-    if let Err(e) = res {
-        return HttpResponse::InternalServerError().json(InternalServerError::new(&e.to_string()));
-    }
+    res?;
 
-    let sub = user.id;
     let iat = Utc::now();
     let exp = iat + chrono::Duration::milliseconds(pool.jwt.expires);
 
-    let claims = Claims::new(sub, iat, exp);
+    let user_agent = user_agent(&req);
+    let session = Session {
+        id: Uuid::new_v4().to_string(),
+        organization: organization.clone(),
+        user_id: user.id.clone(),
+        created_at: iat.to_rfc3339(),
+        last_seen: iat.to_rfc3339(),
+        expires_at: exp.to_rfc3339(),
+        ip: client_ip(&req),
+        device: device_label(user_agent.as_deref()),
+        user_agent,
+        actor_id: None,
+    };
+
+    let session = match pool
+        .services
+        .session_service
+        .create(session, &pool.database)
+        .await?
+    {
+        Some(d) => d,
+        None => {
+            return Err(AppError::storage("Unable to create session!"));
+        }
+    };
+
+    let claims = Claims::new(user.id, organization, iat, exp).with_session(session.id);
 
     let token = jsonwebtoken::encode(
         &Header::default(),
@@ -89,266 +275,267 @@ pub async fn authenticate(
         &EncodingKey::from_secret(pool.jwt.secret.as_ref()),
     );
 
-    match token {
-        Ok(res) => HttpResponse::Ok().json(AuthenticationResponse::new(&res)),
-        Err(_) => HttpResponse::InternalServerError().body(""),
+    let token = token?;
+
+    if !login.cookie {
+        return Ok(HttpResponse::Ok().json(AuthenticationResponse::new(&token)));
     }
+
+    let csrf_token = Uuid::new_v4().to_simple().to_string();
+    Ok(HttpResponse::Ok()
+        .cookie(session_cookie(&pool, TOKEN_COOKIE, &token, true))
+        .cookie(session_cookie(&pool, CSRF_COOKIE, &csrf_token, false))
+        .json(CookieAuthenticationResponse { csrf_token }))
 }
 
-#[post("/register")]
-pub async fn register(
+/// Build a cookie of a browser session that lives as long as the token
+fn session_cookie<'a>(
+    pool: &AppDataPool,
+    name: &'a str,
+    value: &str,
+    http_only: bool,
+) -> Cookie<'a> {
+    let same_site = match pool.cookies.same_site.to_lowercase().as_str() {
+        "strict" => SameSite::Strict,
+        "none" => SameSite::None,
+        _ => SameSite::Lax,
+    };
+
+    Cookie::build(name, String::from(value))
+        .path("/")
+        .http_only(http_only)
+        // Browsers drop cookies with SameSite=None that are not secure
+        .secure(pool.cookies.secure || same_site == SameSite::None)
+        .same_site(same_site)
+        .max_age(time::Duration::milliseconds(pool.jwt.expires))
+        .finish()
+}
+
+/// End the session of the caller and remove the cookies of cookie mode
+#[post("/logout")]
+pub async fn logout(
     pool: web::Data<AppDataPool>,
-    new_user: web::Json<RegisterRequest>,
-) -> HttpResponse {
-    if new_user.username.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Username cannot be empty!"));
-    }
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let claims = match current_claims(&req, &pool).await {
+        Ok(d) => d,
+        Err(reason) => return Err(AppError::from(reason)),
+    };
 
-    if new_user.password.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Password cannot be empty!"));
+    if let Some(sid) = &claims.sid {
+        pool.services
+            .session_service
+            .delete(&pool.database, &claims.org, &claims.sub, sid)
+            .await?;
     }
 
-    if new_user.email_address.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Email address cannot be empty!"));
+    let mut res = HttpResponse::Ok().finish();
+    for (name, http_only) in [(TOKEN_COOKIE, true), (CSRF_COOKIE, false)] {
+        if res
+            .add_removal_cookie(&session_cookie(&pool, name, "", http_only))
+            .is_err()
+        {
+            return Err(AppError::Internal(String::from(
+                "Unable to remove the session cookies",
+            )));
+        }
     }
 
-    let email_regex = Regex::new(EMAIL_REGEX_PATTERN).unwrap();
-    if !email_regex.is_match(&new_user.email_address) {
-        return actix_web::HttpResponse::BadRequest()
-            .json(BadRequest::new("Invalid email address!"));
-    }
+    Ok(res)
+}
 
-    let optional = match pool
+#[post("/register")]
+pub async fn register(
+    pool: web::Data<AppDataPool>,
+    new_user: Valid<RegisterRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let organization = request_organization(&req, new_user.organization.as_deref());
+    check_organization_enabled(&pool, &organization).await?;
+
+    let optional = pool
         .services
         .user_service
-        .find_by_username(&pool.database, &new_user.username)
-        .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    };
+        .find_by_username(&pool.database, &organization, &new_user.username)
+        .await?;
 
     if let Some(_d) = optional {
-        return HttpResponse::BadRequest().json(BadRequest::new("Username is already taken!"));
+        return Err(AppError::conflict("Username is already taken!"));
     }
 
-    let optional = match pool
+    let optional = pool
         .services
         .user_service
-        .find_by_email_address(&pool.database, &new_user.email_address)
-        .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    };
+        .find_by_email_address(&pool.database, &organization, &new_user.email_address)
+        .await?;
 
     if let Some(_d) = optional {
-        return HttpResponse::BadRequest().json(BadRequest::new("Email address is already taken!"));
+        return Err(AppError::conflict("Email address is already taken!"));
     }
 
     let new_user = User {
         id: Uuid::new_v4().to_string(),
         username: String::from(&new_user.username),
         email_address: String::from(&new_user.email_address),
-        password: hash(&new_user.password, DEFAULT_COST).unwrap(),
+        password: hash(&new_user.password, DEFAULT_COST)?,
         first_name: String::from(&new_user.first_name),
         last_name: String::from(&new_user.last_name),
         enabled: true,
+        platform_admin: false,
+        service_account: false,
         roles: vec![],
+        role_assignments: vec![],
         created_at: Utc::now().to_string(),
         last_active: String::from(""),
+        attributes: HashMap::new(),
+        organization,
+        version: 0,
     };
 
-    let res = match pool
+    let res = pool
         .services
         .user_service
-        .create(new_user, &pool.database)
-        .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    };
+        .create(new_user, &pool.database, None)
+        .await?;
 
     let res = match res {
         Some(d) => d,
-        None => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new("Unable to create user!"))
-        }
+        None => return Err(AppError::storage("Unable to create user!")),
     };
 
-    match convert_user_to_dto(
+    let dto = convert_user_to_dto(
         res,
         &pool.database,
         &pool.services.role_service,
         &pool.services.permission_service,
     )
-    .await
-    {
-        Ok(d) => HttpResponse::Ok().json(d),
-        Err(e) => {
-            HttpResponse::InternalServerError().json(InternalServerError::new(&e.to_string()))
-        }
-    }
+    .await?;
+    Ok(HttpResponse::Ok().json(dto))
 }
 
 #[get("/current")]
-pub async fn get_current_user(pool: web::Data<AppDataPool>, req: HttpRequest) -> HttpResponse {
-    let id = get_user_uuid_from_token(&req, &pool);
-
-    let id = match id {
-        Some(d) => d,
-        None => {
-            return HttpResponse::Unauthorized().body("");
-        }
+pub async fn get_current_user(
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let claims = match current_claims(&req, &pool).await {
+        Ok(d) => d,
+        Err(reason) => return Err(AppError::from(reason)),
     };
+    let (id, organization, act) = (claims.sub, claims.org, claims.act);
 
     let user = match pool
         .services
         .user_service
-        .find_by_uuid(&pool.database, &id)
-        .await
+        .find_by_uuid(&pool.database, &organization, &id)
+        .await?
     {
-        Ok(d) => match d {
-            Some(d) => d,
-            None => {
-                return HttpResponse::Unauthorized().body("");
-            }
-        },
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
+        Some(d) => d,
+        None => {
+            return Err(AppError::Unauthorized(DenialReason::UnknownUser));
         }
     };
 
     let user = pool
         .services
         .user_service
-        .update_last_active(&pool.database, &user.id, &Utc::now().to_string())
+        .update_last_active(
+            &pool.database,
+            &organization,
+            &user.id,
+            &Utc::now().to_string(),
+        )
         .await;
 
-    let user = match user {
-        Ok(d) => match d {
-            Some(d) => d,
-            None => {
-                return HttpResponse::NotFound().body("");
-            }
-        },
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
+    let user = match user? {
+        Some(d) => d,
+        None => {
+            return Err(AppError::not_found("User not found!"));
         }
     };
 
-    match convert_user_to_dto(
+    // Surface the real user behind an impersonation token
+    let actor = match act {
+        Some(act) => match pool
+            .services
+            .user_service
+            .find_by_uuid(&pool.database, &act.org, &act.sub)
+            .await?
+        {
+            Some(d) => Some(Actor {
+                id: d.id,
+                username: d.username,
+                organization: d.organization,
+            }),
+            None => return Err(AppError::Unauthorized(DenialReason::UnknownUser)),
+        },
+        None => None,
+    };
+
+    let dto = convert_user_to_dto(
         user,
         &pool.database,
         &pool.services.role_service,
         &pool.services.permission_service,
     )
-    .await
-    {
-        Ok(d) => HttpResponse::Ok().json(d),
-        Err(e) => {
-            HttpResponse::InternalServerError().json(InternalServerError::new(&e.to_string()))
-        }
-    }
+    .await?;
+    Ok(HttpResponse::Ok().json(CurrentUser { user: dto, actor }))
 }
 
 #[put("/current")]
 pub async fn update_current_user(
-    update: web::Json<UpdateRequest>,
+    update: Valid<UpdateRequest>,
     pool: web::Data<AppDataPool>,
     req: HttpRequest,
-) -> HttpResponse {
-    let id = get_user_uuid_from_token(&req, &pool);
-
-    let id = match id {
-        Some(d) => d,
-        None => {
-            return HttpResponse::Unauthorized().body("");
-        }
+) -> Result<HttpResponse, AppError> {
+    let claims = match current_claims(&req, &pool).await {
+        Ok(d) => d,
+        Err(reason) => return Err(AppError::from(reason)),
     };
 
-    let res = pool
-        .services
-        .user_service
-        .update_last_active(&pool.database, &id, &Utc::now().to_string())
-        .await;
-
-    if let Err(e) = res {
-        return HttpResponse::InternalServerError().json(InternalServerError::new(&e.to_string()));
+    // Impersonators must not be able to take over the account
+    if claims.act.is_some() {
+        return Err(AppError::from(DenialReason::ImpersonationDenied));
     }
+    let (id, organization) = (claims.sub, claims.org);
 
-    let mut old_user = match res.unwrap() {
+    let mut old_user = match pool
+        .services
+        .user_service
+        .update_last_active(&pool.database, &organization, &id, &Utc::now().to_string())
+        .await?
+    {
         Some(d) => d,
         None => {
-            return HttpResponse::Unauthorized().body("");
+            return Err(AppError::Unauthorized(DenialReason::UnknownUser));
         }
     };
 
     if !old_user.enabled {
-        return HttpResponse::Unauthorized().body("");
-    }
-
-    if update.username.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Username cannot be empty!"));
+        return Err(AppError::Unauthorized(DenialReason::DisabledUser));
     }
 
-    if update.email_address.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Email address cannot be empty!"));
-    }
-
-    let email_regex = Regex::new(EMAIL_REGEX_PATTERN).unwrap();
-    if !email_regex.is_match(&update.email_address) {
-        return HttpResponse::BadRequest().json(BadRequest::new("Invalid email address!"));
-    }
-
-    let user_by_username = match pool
+    let user_by_username = pool
         .services
         .user_service
-        .find_by_username(&pool.database, &update.username)
-        .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()))
-        }
-    };
+        .find_by_username(&pool.database, &organization, &update.username)
+        .await?;
 
     if let Some(x) = user_by_username {
         if x.id != old_user.id {
-            return HttpResponse::BadRequest().json(BadRequest::new("Username is already taken!"));
+            return Err(AppError::conflict("Username is already taken!"));
         }
     }
 
-    let user_by_email = match pool
+    let user_by_email = pool
         .services
         .user_service
-        .find_by_email_address(&pool.database, &update.email_address)
-        .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()))
-        }
-    };
+        .find_by_email_address(&pool.database, &organization, &update.email_address)
+        .await?;
 
     if let Some(x) = user_by_email {
         if x.id != old_user.id {
-            return HttpResponse::BadRequest()
-                .json(BadRequest::new("Email address is already taken!"));
+            return Err(AppError::conflict("Email address is already taken!"));
         }
     }
 
@@ -357,110 +544,151 @@ pub async fn update_current_user(
     old_user.first_name = update.first_name.clone();
     old_user.last_name = update.last_name.clone();
 
-    let user = match pool
+    let user = pool
         .services
         .user_service
-        .update(&pool.database, &id, old_user)
-        .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()))
-        }
-    };
+        .update(&pool.database, &organization, &id, old_user, None)
+        .await?;
 
     let user = match user {
         Some(d) => d,
         None => {
-            return HttpResponse::NoContent().body("");
+            return Err(AppError::precondition_failed(
+                "User was changed by another request!",
+            ));
         }
     };
 
-    match convert_user_to_dto(
+    let dto = convert_user_to_dto(
         user,
         &pool.database,
         &pool.services.role_service,
         &pool.services.permission_service,
     )
-    .await
-    {
-        Ok(d) => HttpResponse::Ok().json(d),
-        Err(e) => {
-            HttpResponse::InternalServerError().json(InternalServerError::new(&e.to_string()))
-        }
-    }
+    .await?;
+    Ok(HttpResponse::Ok().json(dto))
 }
 
 #[put("/current/password")]
 pub async fn update_current_user_password(
-    update: web::Json<UpdatePassword>,
+    update: Valid<UpdatePassword>,
     pool: web::Data<AppDataPool>,
     req: HttpRequest,
-) -> HttpResponse {
-    let id = get_user_uuid_from_token(&req, &pool);
-
-    let id = match id {
-        Some(d) => d,
-        None => {
-            return HttpResponse::Unauthorized().body("");
-        }
+) -> Result<HttpResponse, AppError> {
+    let claims = match current_claims(&req, &pool).await {
+        Ok(d) => d,
+        Err(reason) => return Err(AppError::from(reason)),
     };
 
-    if update.password.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Password cannot be empty!"));
+    // Impersonators must not be able to take over the account
+    if claims.act.is_some() {
+        return Err(AppError::from(DenialReason::ImpersonationDenied));
     }
+    let (id, organization) = (claims.sub, claims.org);
 
-    match pool
+    if pool
         .services
         .user_service
-        .find_by_uuid(&pool.database, &id)
-        .await
+        .find_by_uuid(&pool.database, &organization, &id)
+        .await?
+        .is_none()
     {
-        Ok(d) => {
-            if d.is_none() {
-                return HttpResponse::NotFound().body("");
-            }
-        }
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()))
-        }
-    };
+        return Err(AppError::not_found("User not found!"));
+    }
 
-    let new_password = hash(&update.password, DEFAULT_COST).unwrap();
+    let new_password = hash(&update.password, DEFAULT_COST)?;
 
-    let user = match pool
+    let user = pool
         .services
         .user_service
-        .update_password(&pool.database, &id, &new_password)
-        .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()))
-        }
-    };
+        .update_password(&pool.database, &organization, &id, &new_password)
+        .await?;
 
     let user = match user {
         Some(d) => d,
         None => {
-            return HttpResponse::NoContent().body("");
+            return Ok(HttpResponse::NoContent().body(""));
         }
     };
 
-    match convert_user_to_dto(
+    let dto = convert_user_to_dto(
         user,
         &pool.database,
         &pool.services.role_service,
         &pool.services.permission_service,
     )
-    .await
+    .await?;
+    Ok(HttpResponse::Ok().json(dto))
+}
+
+/// List the sessions of the caller
+#[get("/current/sessions")]
+pub async fn get_current_sessions(
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let claims = match current_claims(&req, &pool).await {
+        Ok(d) => d,
+        Err(reason) => return Err(AppError::from(reason)),
+    };
+
+    let sessions = pool
+        .services
+        .session_service
+        .find_by_user(&pool.database, &claims.org, &claims.sub)
+        .await?;
+    Ok(HttpResponse::Ok().json(
+        sessions
+            .into_iter()
+            .map(|s| convert_session_to_dto(s, claims.sid.as_deref()))
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// Revoke a session of the caller, which invalidates the tokens that were issued for it
+#[delete("/current/sessions/{uuid}")]
+pub async fn revoke_current_session(
+    path: web::Path<EntityPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let claims = match current_claims(&req, &pool).await {
+        Ok(d) => d,
+        Err(reason) => return Err(AppError::from(reason)),
+    };
+
+    match pool
+        .services
+        .session_service
+        .delete(&pool.database, &claims.org, &claims.sub, &path)
+        .await?
     {
-        Ok(d) => HttpResponse::Ok().json(d),
-        Err(e) => {
-            HttpResponse::InternalServerError().json(InternalServerError::new(&e.to_string()))
-        }
+        0 => Err(AppError::not_found("Session not found!")),
+        _ => Ok(HttpResponse::Ok().body("")),
     }
 }
+
+/// List the most recent login attempts of the caller
+#[get("/current/logins")]
+pub async fn get_current_logins(
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let claims = match current_claims(&req, &pool).await {
+        Ok(d) => d,
+        Err(reason) => return Err(AppError::from(reason)),
+    };
+
+    let attempts = pool
+        .services
+        .login_service
+        .find_by_user(&pool.database, &claims.org, &claims.sub)
+        .await?;
+    Ok(HttpResponse::Ok().json(
+        attempts
+            .into_iter()
+            .map(convert_login_attempt_to_dto)
+            .collect::<Vec<_>>(),
+    ))
+}
@@ -1,4 +1,6 @@
 pub mod authentication_request;
 pub mod authentication_response;
+pub mod current_user;
+pub mod login_attempt;
 pub mod register_request;
 pub mod update_request;
@@ -1,7 +1,21 @@
 use serde::{Deserialize, Serialize};
 
+use crate::routes::validation::{Validate, Validator};
+
 #[derive(Serialize, Deserialize)]
 pub struct AuthenticationRequest {
     pub username: String,
     pub password: String,
+    #[serde(default)]
+    pub organization: Option<String>,
+    /// Store the token in an `HttpOnly` cookie instead of returning it
+    #[serde(default)]
+    pub cookie: bool,
+}
+
+impl Validate for AuthenticationRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("username", &self.username)
+            .required("password", &self.password);
+    }
 }
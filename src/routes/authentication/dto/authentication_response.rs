@@ -1,6 +1,13 @@
 use chrono::{DateTime, Timelike, Utc};
 use serde::{Deserialize, Serialize};
 
+/// The response of `authenticate` in cookie mode. The token itself is only sent as a cookie.
+#[derive(Serialize)]
+pub struct CookieAuthenticationResponse {
+    #[serde(rename(serialize = "csrfToken"))]
+    pub csrf_token: String,
+}
+
 #[derive(Serialize)]
 pub struct AuthenticationResponse {
     pub token: String,
@@ -14,13 +21,27 @@ impl AuthenticationResponse {
     }
 }
 
+/// The party that acts on behalf of the subject of a token (RFC 8693)
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct Actor {
+    pub sub: String,
+    pub org: String,
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
+    #[serde(default)]
+    pub org: String,
     #[serde(with = "jwt_numeric_date")]
     pub iat: DateTime<Utc>,
     #[serde(with = "jwt_numeric_date")]
     pub exp: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Actor>,
+    /// The session that the token belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
 }
 
 impl Claims {
@@ -28,7 +49,7 @@ impl Claims {
     /// again, this function must be used for construction. `DateTime` contains a microsecond field
     /// but JWT timestamps are defined as UNIX timestamps (seconds). This function normalizes the
     /// timestamps.
-    pub fn new(sub: String, iat: DateTime<Utc>, exp: DateTime<Utc>) -> Self {
+    pub fn new(sub: String, org: String, iat: DateTime<Utc>, exp: DateTime<Utc>) -> Self {
         // normalize the timestamps by stripping of microseconds
         let iat = iat
             .date()
@@ -36,7 +57,26 @@ impl Claims {
         let exp = exp
             .date()
             .and_hms_milli(exp.hour(), exp.minute(), exp.second(), 0);
-        Self { sub, iat, exp }
+        Self {
+            sub,
+            org,
+            iat,
+            exp,
+            act: None,
+            sid: None,
+        }
+    }
+
+    /// Tie the token to a session, so that it stops working once the session is revoked
+    pub fn with_session(mut self, sid: String) -> Self {
+        self.sid = Some(sid);
+        self
+    }
+
+    /// Mark the token as an impersonation of the subject by the given actor
+    pub fn with_actor(mut self, actor: Actor) -> Self {
+        self.act = Some(actor);
+        self
     }
 }
 
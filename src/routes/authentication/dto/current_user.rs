@@ -0,0 +1,19 @@
+use serde::Serialize;
+
+use crate::routes::user::dto::user::User as UserDto;
+
+/// The user that acts on behalf of the current user during impersonation
+#[derive(Serialize)]
+pub struct Actor {
+    pub id: String,
+    pub username: String,
+    pub organization: String,
+}
+
+#[derive(Serialize)]
+pub struct CurrentUser {
+    #[serde(flatten)]
+    pub user: UserDto,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor: Option<Actor>,
+}
@@ -0,0 +1,16 @@
+use serde::Serialize;
+
+use crate::persistence::login::model::login_attempt::{LoginAlert, LoginOutcome};
+
+#[derive(Serialize)]
+pub struct LoginAttempt {
+    pub id: String,
+    pub outcome: LoginOutcome,
+    pub ip: Option<String>,
+    #[serde(rename(serialize = "userAgent"))]
+    pub user_agent: Option<String>,
+    pub network: Option<String>,
+    pub country: Option<String>,
+    pub alerts: Vec<LoginAlert>,
+    pub timestamp: String,
+}
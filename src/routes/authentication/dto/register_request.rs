@@ -1,5 +1,7 @@
 use serde::Deserialize;
 
+use crate::routes::validation::{Validate, Validator, MAX_NAME_LENGTH};
+
 #[derive(Deserialize)]
 pub struct RegisterRequest {
     pub username: String,
@@ -10,4 +12,16 @@ pub struct RegisterRequest {
     #[serde(rename(deserialize = "lastName"))]
     pub last_name: String,
     pub password: String,
+    #[serde(default)]
+    pub organization: Option<String>,
+}
+
+impl Validate for RegisterRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.username("username", &self.username)
+            .email("emailAddress", &self.email_address)
+            .password("password", &self.password)
+            .length("firstName", &self.first_name, 0, MAX_NAME_LENGTH)
+            .length("lastName", &self.last_name, 0, MAX_NAME_LENGTH);
+    }
 }
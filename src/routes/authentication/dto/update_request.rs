@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::routes::validation::{Validate, Validator, MAX_NAME_LENGTH};
+
 #[derive(Serialize, Deserialize)]
 pub struct UpdateRequest {
     pub username: String,
@@ -10,3 +12,12 @@ pub struct UpdateRequest {
     #[serde(rename(serialize = "lastName", deserialize = "lastName"))]
     pub last_name: String,
 }
+
+impl Validate for UpdateRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.username("username", &self.username)
+            .email("emailAddress", &self.email_address)
+            .length("firstName", &self.first_name, 0, MAX_NAME_LENGTH)
+            .length("lastName", &self.last_name, 0, MAX_NAME_LENGTH);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod authorization_route;
+pub mod dto;
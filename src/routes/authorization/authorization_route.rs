@@ -0,0 +1,56 @@
+use actix_web::{post, web, HttpRequest, HttpResponse};
+
+use crate::{
+    configuration::app_data_pool::AppDataPool,
+    errors::app_error::AppError,
+    routes::{
+        apply_policies, authorization::dto::explain_request::ExplainRequest,
+        evaluate_user_permission, validation::Valid,
+    },
+    services::permission::system_permission::SystemPermission,
+};
+
+#[post("/explain")]
+pub async fn explain(
+    explain: Valid<ExplainRequest>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ExplainAuthorization,
+    )
+    .await?;
+
+    let user = match pool
+        .services
+        .user_service
+        .find_by_uuid(&pool.database, &organization, &explain.user_id)
+        .await?
+    {
+        Some(d) => d,
+        None => return Err(AppError::not_found("User not found!")),
+    };
+
+    let trace = evaluate_user_permission(
+        &pool.database,
+        &user,
+        &pool.services.role_service,
+        &pool.services.permission_service,
+        &pool.services.group_service,
+        &explain.permission,
+    )
+    .await;
+
+    let trace = apply_policies(
+        &pool,
+        trace,
+        &user,
+        explain.resource.clone(),
+        explain.ip.clone(),
+    )
+    .await;
+
+    Ok(HttpResponse::Ok().json(trace))
+}
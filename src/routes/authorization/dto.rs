@@ -0,0 +1,4 @@
+pub mod authorization_trace;
+pub mod denial_reason;
+pub mod explain_request;
+pub mod offending_grant;
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use crate::services::policy::policy_service::PolicyEvaluation;
+
+use super::denial_reason::DenialReason;
+
+#[derive(Serialize)]
+pub struct AuthorizationTrace {
+    #[serde(rename(serialize = "userId", deserialize = "userId"))]
+    pub user_id: String,
+    pub permission: String,
+    pub granted: bool,
+    pub reason: Option<DenialReason>,
+    #[serde(rename(serialize = "failedStep", deserialize = "failedStep"))]
+    pub failed_step: Option<String>,
+    pub roles: Vec<RoleTrace>,
+    pub policies: Vec<PolicyEvaluation>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RoleTrace {
+    pub id: String,
+    pub name: Option<String>,
+    pub found: bool,
+    #[serde(rename(serialize = "viaGroup", deserialize = "viaGroup"))]
+    pub via_group: Option<String>,
+    #[serde(rename(serialize = "validUntil", deserialize = "validUntil"))]
+    pub valid_until: Option<String>,
+    pub permissions: Vec<PermissionTrace>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PermissionTrace {
+    pub id: String,
+    pub name: Option<String>,
+    pub found: bool,
+    pub matched: bool,
+}
+
+impl AuthorizationTrace {
+    pub fn new(user_id: &str, permission: &str) -> Self {
+        Self {
+            user_id: String::from(user_id),
+            permission: String::from(permission),
+            granted: false,
+            reason: None,
+            failed_step: None,
+            roles: vec![],
+            policies: vec![],
+        }
+    }
+
+    /// Mark the evaluation as denied at the given step
+    pub fn deny(mut self, reason: DenialReason, step: &str) -> Self {
+        self.granted = false;
+        self.reason = Some(reason);
+        self.failed_step = Some(String::from(step));
+        self
+    }
+}
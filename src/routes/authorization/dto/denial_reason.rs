@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DenialReason {
+    MissingToken,
+    InvalidToken,
+    InvalidCredentials,
+    RevokedSession,
+    ExpiredToken,
+    UnknownUser,
+    DisabledUser,
+    DanglingRole,
+    DanglingPermission,
+    MissingPermission,
+    PolicyDenied,
+    ForeignOrganization,
+    PlatformAdminRequired,
+    PrivilegeEscalation,
+    RestrictedApiKey,
+    ImpersonationDenied,
+    InvalidCsrfToken,
+    StorageFailure,
+}
+
+impl DenialReason {
+    /// Whether the denial means the caller could not be authenticated (401) rather than
+    /// being authenticated but lacking access (403)
+    pub fn is_authentication_failure(&self) -> bool {
+        matches!(
+            self,
+            DenialReason::MissingToken
+                | DenialReason::InvalidToken
+                | DenialReason::InvalidCredentials
+                | DenialReason::ExpiredToken
+                | DenialReason::RevokedSession
+                | DenialReason::UnknownUser
+        )
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            DenialReason::MissingToken => "No bearer token was provided!",
+            DenialReason::InvalidToken => "The bearer token is invalid!",
+            DenialReason::InvalidCredentials => "The username or password is invalid!",
+            DenialReason::ExpiredToken => "The bearer token has expired!",
+            DenialReason::RevokedSession => "The session of the bearer token was revoked!",
+            DenialReason::UnknownUser => "The user in the bearer token does not exist!",
+            DenialReason::DisabledUser => "The user is disabled!",
+            DenialReason::DanglingRole => "The user references a role that does not exist!",
+            DenialReason::DanglingPermission => {
+                "A role of the user references a permission that does not exist!"
+            }
+            DenialReason::MissingPermission => "The user does not have the required permission!",
+            DenialReason::PolicyDenied => "Access was denied by a policy!",
+            DenialReason::ForeignOrganization => "The user does not belong to the organization!",
+            DenialReason::PlatformAdminRequired => {
+                "Only platform administrators can perform this action!"
+            }
+            DenialReason::PrivilegeEscalation => {
+                "The user cannot grant roles or permissions that they do not hold themselves!"
+            }
+            DenialReason::RestrictedApiKey => {
+                "The API key is not allowed to use the required permission!"
+            }
+            DenialReason::ImpersonationDenied => {
+                "The user cannot impersonate a user with more privileges or while impersonating!"
+            }
+            DenialReason::InvalidCsrfToken => "The CSRF token is missing or invalid!",
+            DenialReason::StorageFailure => "Unable to evaluate the required permission!",
+        }
+    }
+}
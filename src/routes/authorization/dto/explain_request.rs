@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::routes::validation::{Validate, Validator};
+
+#[derive(Serialize, Deserialize)]
+pub struct ExplainRequest {
+    #[serde(rename(serialize = "userId", deserialize = "userId"))]
+    pub user_id: String,
+    pub permission: String,
+    #[serde(default)]
+    pub resource: Option<Value>,
+    #[serde(default)]
+    pub ip: Option<String>,
+}
+
+impl Validate for ExplainRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.uuid("userId", &self.user_id)
+            .required("permission", &self.permission);
+    }
+}
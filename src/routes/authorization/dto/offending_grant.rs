@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum GrantType {
+    Role,
+    Permission,
+}
+
+/// A role or permission that the caller tried to grant without holding it themselves
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OffendingGrant {
+    #[serde(rename(serialize = "type", deserialize = "type"))]
+    pub grant_type: GrantType,
+    pub id: String,
+    pub name: String,
+    #[serde(rename(serialize = "missingPermissions", deserialize = "missingPermissions"))]
+    pub missing_permissions: Vec<String>,
+}
@@ -0,0 +1,2 @@
+pub mod constraint_route;
+pub mod dto;
@@ -0,0 +1,263 @@
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse};
+use uuid::Uuid;
+
+use crate::{
+    configuration::app_data_pool::AppDataPool,
+    errors::app_error::AppError,
+    persistence::constraint::model::constraint::Constraint,
+    routes::{
+        constraint::dto::{
+            create_constraint::CreateConstraint, update_constraint::UpdateConstraint,
+            violation::Violation,
+        },
+        convert_constraint_to_dto, held_roles,
+        validation::Valid,
+        EntityPath,
+    },
+    services::{
+        constraint::constraint_service::conflicting_roles,
+        permission::system_permission::SystemPermission,
+    },
+};
+
+/// Make sure that the roles of a constraint exist
+async fn validate_constraint(
+    pool: &AppDataPool,
+    organization: &str,
+    roles: &[String],
+) -> Result<(), AppError> {
+    for role in roles {
+        if pool
+            .services
+            .role_service
+            .find_by_uuid(&pool.database, organization, role)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::validation(&format!("Invalid role {}", role)));
+        }
+    }
+
+    Ok(())
+}
+
+#[post("/")]
+pub async fn create_constraint(
+    create: Valid<CreateConstraint>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ManageConstraint,
+    )
+    .await?;
+
+    validate_constraint(&pool, &organization, &create.roles).await?;
+
+    if pool
+        .services
+        .constraint_service
+        .find_by_name(&pool.database, &organization, &create.name)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::conflict(&format!(
+            "Constraint with name {} already exists!",
+            &create.name
+        )));
+    }
+
+    let constraint = Constraint {
+        id: Uuid::new_v4().to_string(),
+        name: create.name.clone(),
+        description: create.description.clone(),
+        roles: create.roles.clone(),
+        organization,
+    };
+
+    match pool
+        .services
+        .constraint_service
+        .create(constraint, &pool.database)
+        .await?
+    {
+        Some(d) => Ok(HttpResponse::Ok().json(convert_constraint_to_dto(d))),
+        None => Err(AppError::storage("Unable to create constraint!")),
+    }
+}
+
+#[get("/")]
+pub async fn get_all_constraints(
+    req: HttpRequest,
+    pool: web::Data<AppDataPool>,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ReadConstraint,
+    )
+    .await?;
+
+    let constraints = pool
+        .services
+        .constraint_service
+        .find_all(&pool.database, &organization)
+        .await?;
+    Ok(HttpResponse::Ok().json(
+        constraints
+            .into_iter()
+            .map(convert_constraint_to_dto)
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// Report every user of the organization that currently violates a constraint
+#[get("/violations")]
+pub async fn get_violations(
+    req: HttpRequest,
+    pool: web::Data<AppDataPool>,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ReadConstraint,
+    )
+    .await?;
+
+    let constraints = pool
+        .services
+        .constraint_service
+        .find_all(&pool.database, &organization)
+        .await?;
+
+    let users = pool
+        .services
+        .user_service
+        .find_all(&pool.database, &organization)
+        .await?;
+
+    let mut violations = vec![];
+    for user in users {
+        let roles = held_roles(&pool.database, &user, &pool.services.group_service).await?;
+
+        for constraint in &constraints {
+            let conflicting = conflicting_roles(constraint, &roles);
+            if !conflicting.is_empty() {
+                violations.push(Violation {
+                    constraint_id: constraint.id.clone(),
+                    constraint_name: constraint.name.clone(),
+                    user_id: user.id.clone(),
+                    username: user.username.clone(),
+                    roles: conflicting,
+                });
+            }
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(violations))
+}
+
+#[get("/{uuid}")]
+pub async fn get_constraint_by_id(
+    path: web::Path<EntityPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ReadConstraint,
+    )
+    .await?;
+
+    match pool
+        .services
+        .constraint_service
+        .find_by_uuid(&pool.database, &organization, &path)
+        .await?
+    {
+        Some(d) => Ok(HttpResponse::Ok().json(convert_constraint_to_dto(d))),
+        None => Err(AppError::not_found("Constraint not found!")),
+    }
+}
+
+#[put("/{uuid}")]
+pub async fn update_constraint(
+    path: web::Path<EntityPath>,
+    update: Valid<UpdateConstraint>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ManageConstraint,
+    )
+    .await?;
+
+    validate_constraint(&pool, &organization, &update.roles).await?;
+
+    let mut constraint = match pool
+        .services
+        .constraint_service
+        .find_by_uuid(&pool.database, &organization, &path)
+        .await?
+    {
+        Some(d) => d,
+        None => return Err(AppError::not_found("Constraint not found!")),
+    };
+
+    if let Some(x) = pool
+        .services
+        .constraint_service
+        .find_by_name(&pool.database, &organization, &update.name)
+        .await?
+    {
+        if x.id != constraint.id {
+            return Err(AppError::conflict(&format!(
+                "Constraint with name {} already exists!",
+                update.name
+            )));
+        }
+    }
+
+    constraint.name = update.name.clone();
+    constraint.description = update.description.clone();
+    constraint.roles = update.roles.clone();
+
+    match pool
+        .services
+        .constraint_service
+        .update(&pool.database, &organization, &path, constraint)
+        .await?
+    {
+        Some(d) => Ok(HttpResponse::Ok().json(convert_constraint_to_dto(d))),
+        None => Ok(HttpResponse::NoContent().body("")),
+    }
+}
+
+#[delete("/{uuid}")]
+pub async fn delete_constraint(
+    path: web::Path<EntityPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ManageConstraint,
+    )
+    .await?;
+
+    pool.services
+        .constraint_service
+        .delete(&pool.database, &organization, &path)
+        .await?;
+
+    Ok(HttpResponse::Ok().body(""))
+}
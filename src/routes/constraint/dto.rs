@@ -0,0 +1,4 @@
+pub mod constraint;
+pub mod create_constraint;
+pub mod update_constraint;
+pub mod violation;
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct Constraint {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub roles: Vec<String>,
+    pub organization: String,
+}
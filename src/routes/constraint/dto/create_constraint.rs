@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+use crate::routes::validation::{Validate, Validator};
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateConstraint {
+    pub name: String,
+    pub description: String,
+    pub roles: Vec<String>,
+}
+
+/// Whether a constraint is made up of at least two roles, none of which is repeated
+pub fn has_distinct_roles(roles: &[String]) -> bool {
+    let mut distinct = roles.to_vec();
+    distinct.sort();
+    distinct.dedup();
+    distinct.len() == roles.len() && roles.len() >= 2
+}
+
+impl Validate for CreateConstraint {
+    fn validate(&self, v: &mut Validator) {
+        v.name("name", &self.name)
+            .description("description", &self.description)
+            .uuids("roles", &self.roles)
+            .check(
+                "roles",
+                has_distinct_roles(&self.roles),
+                "DISTINCT",
+                "A constraint needs at least two distinct roles!",
+            );
+    }
+}
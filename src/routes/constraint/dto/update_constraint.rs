@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+use crate::routes::validation::{Validate, Validator};
+
+use super::create_constraint::has_distinct_roles;
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateConstraint {
+    pub name: String,
+    pub description: String,
+    pub roles: Vec<String>,
+}
+
+impl Validate for UpdateConstraint {
+    fn validate(&self, v: &mut Validator) {
+        v.name("name", &self.name)
+            .description("description", &self.description)
+            .uuids("roles", &self.roles)
+            .check(
+                "roles",
+                has_distinct_roles(&self.roles),
+                "DISTINCT",
+                "A constraint needs at least two distinct roles!",
+            );
+    }
+}
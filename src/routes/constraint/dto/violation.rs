@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// A user that holds more than one role of a separation-of-duties constraint
+#[derive(Serialize, Deserialize)]
+pub struct Violation {
+    #[serde(rename(serialize = "constraintId", deserialize = "constraintId"))]
+    pub constraint_id: String,
+    #[serde(rename(serialize = "constraintName", deserialize = "constraintName"))]
+    pub constraint_name: String,
+    #[serde(rename(serialize = "userId", deserialize = "userId"))]
+    pub user_id: String,
+    pub username: String,
+    pub roles: Vec<String>,
+}
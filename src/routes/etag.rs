@@ -0,0 +1,95 @@
+use actix_web::{
+    http::header::{ETag, EntityTag, Header, IfMatch},
+    HttpRequest,
+};
+
+use crate::errors::app_error::AppError;
+
+/// The `ETag` header of a version of an entity
+pub fn etag(version: i64) -> ETag {
+    ETag(EntityTag::new_strong(version.to_string()))
+}
+
+/// Check the `If-Match` header of a request against the current version of an entity. Returns the
+/// version that a following write has to be conditional on, or `None` when the request does not
+/// ask for one.
+pub fn check_if_match(req: &HttpRequest, version: i64) -> Result<Option<i64>, AppError> {
+    if !req.headers().contains_key(IfMatch::name()) {
+        return Ok(None);
+    }
+
+    // Entity tags that cannot be parsed are dropped, which leaves no items at all
+    let items = match IfMatch::parse(req) {
+        Ok(IfMatch::Any) => return Ok(None),
+        Ok(IfMatch::Items(d)) if !d.is_empty() => d,
+        _ => return Err(AppError::validation("Invalid If-Match header!")),
+    };
+
+    let current = EntityTag::new_strong(version.to_string());
+    if items.iter().any(|d| d.strong_eq(&current)) {
+        Ok(Some(version))
+    } else {
+        Err(AppError::precondition_failed(
+            "The resource has been changed since it was read!",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    fn if_match(value: &str) -> HttpRequest {
+        TestRequest::default()
+            .insert_header((IfMatch::name(), value))
+            .to_http_request()
+    }
+
+    #[test]
+    fn etag_is_the_strong_version() {
+        assert_eq!(etag(7).to_string(), "\"7\"");
+    }
+
+    #[test]
+    fn requests_without_if_match_are_unconditional() {
+        let req = TestRequest::default().to_http_request();
+
+        assert!(matches!(check_if_match(&req, 3), Ok(None)));
+    }
+
+    #[test]
+    fn wildcard_is_unconditional() {
+        assert!(matches!(check_if_match(&if_match("*"), 3), Ok(None)));
+    }
+
+    #[test]
+    fn matching_tag_returns_the_version() {
+        assert!(matches!(check_if_match(&if_match("\"3\""), 3), Ok(Some(3))));
+        assert!(matches!(
+            check_if_match(&if_match("\"1\", \"3\""), 3),
+            Ok(Some(3))
+        ));
+    }
+
+    #[test]
+    fn stale_or_weak_tags_fail_the_precondition() {
+        assert!(matches!(
+            check_if_match(&if_match("\"2\""), 3),
+            Err(AppError::PreconditionFailed(_))
+        ));
+        assert!(matches!(
+            check_if_match(&if_match("W/\"3\""), 3),
+            Err(AppError::PreconditionFailed(_))
+        ));
+    }
+
+    #[test]
+    fn malformed_header_is_invalid() {
+        assert!(matches!(
+            check_if_match(&if_match("3"), 3),
+            Err(AppError::Validation(_))
+        ));
+    }
+}
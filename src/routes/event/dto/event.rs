@@ -0,0 +1,16 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct Event {
+    pub id: String,
+    #[serde(rename(serialize = "type", deserialize = "type"))]
+    pub event_type: String,
+    pub organization: String,
+    #[serde(rename(serialize = "userId", deserialize = "userId"))]
+    pub user_id: Option<String>,
+    pub message: String,
+    pub data: HashMap<String, String>,
+    pub timestamp: String,
+}
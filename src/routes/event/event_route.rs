@@ -0,0 +1,29 @@
+use actix_web::{get, web, HttpRequest, HttpResponse};
+
+use crate::{
+    configuration::app_data_pool::AppDataPool, errors::app_error::AppError,
+    routes::convert_event_to_dto, services::permission::system_permission::SystemPermission,
+};
+
+#[get("/")]
+pub async fn get_all_events(
+    req: HttpRequest,
+    pool: web::Data<AppDataPool>,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::ReadEvent)
+            .await?;
+
+    let events = pool
+        .services
+        .event_service
+        .find_all(&pool.database, &organization)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(
+        events
+            .into_iter()
+            .map(convert_event_to_dto)
+            .collect::<Vec<_>>(),
+    ))
+}
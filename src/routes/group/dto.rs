@@ -0,0 +1,4 @@
+pub mod create_group;
+pub mod group;
+pub mod membership_path;
+pub mod update_group;
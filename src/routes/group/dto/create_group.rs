@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+use crate::routes::validation::{Validate, Validator};
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateGroup {
+    pub name: String,
+    pub description: String,
+    pub roles: Vec<String>,
+}
+
+impl Validate for CreateGroup {
+    fn validate(&self, v: &mut Validator) {
+        v.name("name", &self.name)
+            .description("description", &self.description)
+            .uuids("roles", &self.roles);
+    }
+}
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+use crate::routes::role::dto::role::Role as RoleDto;
+
+#[derive(Serialize, Deserialize)]
+pub struct Group {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub members: Vec<String>,
+    pub groups: Vec<String>,
+    pub roles: Vec<RoleDto>,
+    pub organization: String,
+}
@@ -0,0 +1,8 @@
+use serde::Deserialize;
+
+/// Path of a single member (user, nested group or role) of a group
+#[derive(Deserialize)]
+pub struct MembershipPath {
+    pub uuid: String,
+    pub member: String,
+}
@@ -0,0 +1,513 @@
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse};
+use uuid::Uuid;
+
+use crate::{
+    configuration::app_data_pool::AppDataPool,
+    errors::app_error::AppError,
+    persistence::group::model::group::Group,
+    routes::{
+        check_grants, check_user_constraints, convert_group_to_dto,
+        group::dto::{
+            create_group::CreateGroup, membership_path::MembershipPath, update_group::UpdateGroup,
+        },
+        validation::Valid,
+        EntityPath,
+    },
+    services::{
+        group::group_service::{GROUPS_FIELD, MEMBERS_FIELD, ROLES_FIELD},
+        permission::system_permission::SystemPermission,
+    },
+};
+
+#[post("/")]
+pub async fn create_group(
+    create: Valid<CreateGroup>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::CreateGroup)
+            .await?;
+
+    if pool
+        .services
+        .group_service
+        .find_by_name(&pool.database, &organization, &create.name)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::conflict(&format!(
+            "Group with name {} already exists!",
+            &create.name
+        )));
+    }
+
+    validate_roles(&pool, &organization, &create.roles).await?;
+
+    check_grants(&req, &pool, &caller, &organization, &create.roles, &[]).await?;
+
+    let new_group = Group {
+        id: Uuid::new_v4().to_string(),
+        name: create.name.clone(),
+        description: create.description.clone(),
+        members: vec![],
+        groups: vec![],
+        roles: create.roles.clone(),
+        organization,
+    };
+
+    let res = pool
+        .services
+        .group_service
+        .create(new_group, &pool.database)
+        .await?;
+
+    match res {
+        Some(d) => group_response(&pool, d).await,
+        None => Err(AppError::storage("Unable to create group!")),
+    }
+}
+
+#[get("/")]
+pub async fn get_all_groups(
+    req: HttpRequest,
+    pool: web::Data<AppDataPool>,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::ReadGroup)
+            .await?;
+
+    let groups = pool
+        .services
+        .group_service
+        .find_all(&pool.database, &organization)
+        .await?;
+
+    let mut group_dto = vec![];
+    for group in groups {
+        let dto = convert_group_to_dto(
+            group,
+            &pool.database,
+            &pool.services.role_service,
+            &pool.services.permission_service,
+        )
+        .await?;
+        group_dto.push(dto)
+    }
+
+    Ok(HttpResponse::Ok().json(group_dto))
+}
+
+#[get("/{uuid}")]
+pub async fn get_group_by_id(
+    path: web::Path<EntityPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::ReadGroup)
+            .await?;
+
+    match pool
+        .services
+        .group_service
+        .find_by_uuid(&pool.database, &organization, &path)
+        .await?
+    {
+        Some(d) => group_response(&pool, d).await,
+        None => Err(AppError::not_found("Group not found!")),
+    }
+}
+
+#[put("/{uuid}")]
+pub async fn update_group(
+    path: web::Path<EntityPath>,
+    update: Valid<UpdateGroup>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (caller, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::UpdateGroup)
+            .await?;
+
+    let mut res = match pool
+        .services
+        .group_service
+        .find_by_uuid(&pool.database, &organization, &path)
+        .await?
+    {
+        Some(x) => x,
+        None => return Err(AppError::not_found("Group not found!")),
+    };
+
+    if let Some(x) = pool
+        .services
+        .group_service
+        .find_by_name(&pool.database, &organization, &update.name)
+        .await?
+    {
+        if x.id != res.id {
+            return Err(AppError::conflict(&format!(
+                "Group with name {} already exists!",
+                update.name
+            )));
+        }
+    }
+
+    validate_roles(&pool, &organization, &update.roles).await?;
+
+    let added_roles: Vec<String> = update
+        .roles
+        .iter()
+        .filter(|r| !res.roles.contains(r))
+        .cloned()
+        .collect();
+    check_grants(&req, &pool, &caller, &organization, &added_roles, &[]).await?;
+
+    check_member_constraints(&pool, &organization, &path, &added_roles).await?;
+
+    res.name = update.name.clone();
+    res.description = update.description.clone();
+    res.roles = update.roles.clone();
+
+    match pool
+        .services
+        .group_service
+        .update(&pool.database, &organization, &path, res)
+        .await?
+    {
+        Some(d) => group_response(&pool, d).await,
+        None => Ok(HttpResponse::NoContent().body("")),
+    }
+}
+
+#[delete("/{uuid}")]
+pub async fn delete_group(
+    path: web::Path<EntityPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::DeleteGroup)
+            .await?;
+
+    let mut transaction = pool.transactions.begin().await?;
+
+    pool.services
+        .group_service
+        .pull_from_all(
+            &pool.database,
+            &organization,
+            GROUPS_FIELD,
+            &path,
+            transaction.session(),
+        )
+        .await?;
+
+    pool.services
+        .group_service
+        .delete(&pool.database, &organization, &path, transaction.session())
+        .await?;
+
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().body(""))
+}
+
+#[put("/{uuid}/members/{member}")]
+pub async fn add_member(
+    path: web::Path<MembershipPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::UpdateGroup)
+            .await?;
+
+    let user = match pool
+        .services
+        .user_service
+        .find_by_uuid(&pool.database, &organization, &path.member)
+        .await?
+    {
+        Some(d) => d,
+        None => {
+            return Err(AppError::validation(&format!(
+                "Invalid user {}",
+                path.member
+            )));
+        }
+    };
+
+    // The new member receives the roles of the group and of every group that contains it
+    let roles = inherited_roles(&pool, &organization, &path.uuid).await?;
+
+    check_grants(&req, &pool, &caller, &organization, &roles, &[]).await?;
+
+    check_user_constraints(&pool, &user, &roles).await?;
+
+    modify_membership(&pool, &organization, &path, MEMBERS_FIELD, true).await
+}
+
+#[delete("/{uuid}/members/{member}")]
+pub async fn remove_member(
+    path: web::Path<MembershipPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::UpdateGroup)
+            .await?;
+
+    modify_membership(&pool, &organization, &path, MEMBERS_FIELD, false).await
+}
+
+#[put("/{uuid}/groups/{member}")]
+pub async fn add_nested_group(
+    path: web::Path<MembershipPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::UpdateGroup)
+            .await?;
+
+    if path.uuid == path.member {
+        return Err(AppError::validation("A group cannot contain itself!"));
+    }
+
+    match pool
+        .services
+        .group_service
+        .find_by_uuid(&pool.database, &organization, &path.member)
+        .await?
+    {
+        Some(_) => {}
+        None => {
+            return Err(AppError::validation(&format!(
+                "Invalid group {}",
+                path.member
+            )));
+        }
+    };
+
+    // The nested group may not (indirectly) contain the group that it is added to
+    if pool
+        .services
+        .group_service
+        .find_ancestor_ids(&pool.database, &organization, &path.uuid)
+        .await?
+        .contains(&path.member)
+    {
+        return Err(AppError::validation(&format!(
+            "Adding group {} would create a cycle!",
+            path.member
+        )));
+    }
+
+    // Every user inside the nested group receives the roles of the group that it is added to
+    let roles = inherited_roles(&pool, &organization, &path.uuid).await?;
+
+    check_grants(&req, &pool, &caller, &organization, &roles, &[]).await?;
+
+    check_member_constraints(&pool, &organization, &path.member, &roles).await?;
+
+    modify_membership(&pool, &organization, &path, GROUPS_FIELD, true).await
+}
+
+#[delete("/{uuid}/groups/{member}")]
+pub async fn remove_nested_group(
+    path: web::Path<MembershipPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::UpdateGroup)
+            .await?;
+
+    modify_membership(&pool, &organization, &path, GROUPS_FIELD, false).await
+}
+
+#[put("/{uuid}/roles/{member}")]
+pub async fn add_role(
+    path: web::Path<MembershipPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::UpdateGroup)
+            .await?;
+
+    validate_roles(&pool, &organization, std::slice::from_ref(&path.member)).await?;
+
+    check_grants(
+        &req,
+        &pool,
+        &caller,
+        &organization,
+        std::slice::from_ref(&path.member),
+        &[],
+    )
+    .await?;
+
+    check_member_constraints(
+        &pool,
+        &organization,
+        &path.uuid,
+        std::slice::from_ref(&path.member),
+    )
+    .await?;
+
+    modify_membership(&pool, &organization, &path, ROLES_FIELD, true).await
+}
+
+#[delete("/{uuid}/roles/{member}")]
+pub async fn remove_role(
+    path: web::Path<MembershipPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::UpdateGroup)
+            .await?;
+
+    modify_membership(&pool, &organization, &path, ROLES_FIELD, false).await
+}
+
+/// Make sure that every role exists inside the organization
+async fn validate_roles(
+    pool: &AppDataPool,
+    organization: &str,
+    roles: &[String],
+) -> Result<(), AppError> {
+    for role in roles {
+        if pool
+            .services
+            .role_service
+            .find_by_uuid(&pool.database, organization, role)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::validation(&format!("Invalid role {}", role)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Collect the roles that members of a group inherit from the group and from every group that
+/// contains it
+async fn inherited_roles(
+    pool: &web::Data<AppDataPool>,
+    organization: &str,
+    group_id: &str,
+) -> Result<Vec<String>, AppError> {
+    let groups = pool
+        .services
+        .group_service
+        .find_with_ancestors(&pool.database, organization, group_id)
+        .await?;
+
+    let mut roles: Vec<String> = vec![];
+    for group in groups {
+        for role in group.roles {
+            if !roles.contains(&role) {
+                roles.push(role);
+            }
+        }
+    }
+
+    Ok(roles)
+}
+
+/// Make sure that none of the users inside a group, directly or through nested groups, violates
+/// a separation-of-duties constraint once they receive the given roles
+async fn check_member_constraints(
+    pool: &web::Data<AppDataPool>,
+    organization: &str,
+    group_id: &str,
+    added_roles: &[String],
+) -> Result<(), AppError> {
+    if added_roles.is_empty() {
+        return Ok(());
+    }
+
+    let groups = pool
+        .services
+        .group_service
+        .find_with_descendants(&pool.database, organization, group_id)
+        .await?;
+
+    let mut members: Vec<String> = vec![];
+    for group in groups {
+        for member in group.members {
+            if !members.contains(&member) {
+                members.push(member);
+            }
+        }
+    }
+
+    for member in members {
+        if let Some(d) = pool
+            .services
+            .user_service
+            .find_by_uuid(&pool.database, organization, &member)
+            .await?
+        {
+            check_user_constraints(pool, &d, added_roles).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Add the member to, or remove it from, one of the array fields of a group
+async fn modify_membership(
+    pool: &AppDataPool,
+    organization: &str,
+    path: &MembershipPath,
+    field: &str,
+    add: bool,
+) -> Result<HttpResponse, AppError> {
+    let service = &pool.services.group_service;
+    let res = if add {
+        service
+            .add_to_set(
+                &pool.database,
+                organization,
+                &path.uuid,
+                field,
+                &path.member,
+            )
+            .await
+    } else {
+        service
+            .pull(
+                &pool.database,
+                organization,
+                &path.uuid,
+                field,
+                &path.member,
+            )
+            .await
+    };
+
+    match res? {
+        Some(d) => group_response(pool, d).await,
+        None => Err(AppError::not_found("Group not found!")),
+    }
+}
+
+async fn group_response(pool: &AppDataPool, group: Group) -> Result<HttpResponse, AppError> {
+    let dto = convert_group_to_dto(
+        group,
+        &pool.database,
+        &pool.services.role_service,
+        &pool.services.permission_service,
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(dto))
+}
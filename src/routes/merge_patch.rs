@@ -0,0 +1,149 @@
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+use futures::future::LocalBoxFuture;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+
+use crate::errors::app_error::AppError;
+
+use super::validation::{Validate, Validator};
+
+/// An RFC 7396 merge patch request body
+pub struct MergePatch(pub Value);
+
+impl MergePatch {
+    /// Patch the current state of an update request body and validate the result, exactly like
+    /// a body that was sent in full
+    pub fn apply<T: Serialize + DeserializeOwned + Validate>(
+        &self,
+        current: &T,
+    ) -> Result<T, AppError> {
+        let mut target = match serde_json::to_value(current) {
+            Ok(d) => d,
+            Err(e) => return Err(AppError::Internal(e.to_string())),
+        };
+        merge_patch(&mut target, &self.0);
+
+        let patched: T = match serde_json::from_value(target) {
+            Ok(d) => d,
+            Err(e) => return Err(AppError::validation(&e.to_string())),
+        };
+
+        let mut validator = Validator::default();
+        patched.validate(&mut validator);
+        validator.into_result()?;
+
+        Ok(patched)
+    }
+}
+
+impl FromRequest for MergePatch {
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let json = web::Json::<Value>::from_request(req, payload);
+        Box::pin(async move {
+            match json.await {
+                Ok(d) if d.is_object() => Ok(MergePatch(d.into_inner())),
+                Ok(_) => Err(AppError::validation("A merge patch must be a JSON object!").into()),
+                Err(e) => Err(AppError::validation(&e.to_string()).into()),
+            }
+        })
+    }
+}
+
+/// Apply a merge patch to a document. Members of the patch replace those of the document, nested
+/// objects are merged recursively and `null` removes a member.
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let patch = match patch.as_object() {
+        Some(d) => d,
+        None => {
+            *target = patch.clone();
+            return;
+        }
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+
+    if let Value::Object(members) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                members.remove(key);
+            } else {
+                merge_patch(members.entry(key.as_str()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn patched(target: Value, patch: Value) -> Value {
+        let mut target = target;
+        merge_patch(&mut target, &patch);
+        target
+    }
+
+    #[test]
+    fn members_are_replaced_added_and_removed() {
+        assert_eq!(
+            patched(
+                json!({ "a": "b", "c": "d", "e": 1 }),
+                json!({ "a": "z", "c": null, "f": true })
+            ),
+            json!({ "a": "z", "e": 1, "f": true })
+        );
+    }
+
+    #[test]
+    fn nested_objects_are_merged_recursively() {
+        assert_eq!(
+            patched(
+                json!({ "attributes": { "department": "sales", "level": 2 } }),
+                json!({ "attributes": { "level": 3, "department": null, "site": "ams" } })
+            ),
+            json!({ "attributes": { "level": 3, "site": "ams" } })
+        );
+    }
+
+    #[test]
+    fn arrays_and_scalars_are_replaced_as_a_whole() {
+        assert_eq!(
+            patched(
+                json!({ "permissions": ["a", "b"] }),
+                json!({ "permissions": ["c"] })
+            ),
+            json!({ "permissions": ["c"] })
+        );
+        assert_eq!(
+            patched(json!({ "a": "b" }), json!({ "a": { "b": "c" } })),
+            json!({ "a": { "b": "c" } })
+        );
+        assert_eq!(
+            patched(json!({ "a": { "b": "c" } }), json!({ "a": 1 })),
+            json!({ "a": 1 })
+        );
+    }
+
+    #[test]
+    fn a_patch_that_is_not_an_object_replaces_the_target() {
+        assert_eq!(patched(json!({ "a": "b" }), json!(["c"])), json!(["c"]));
+        assert_eq!(
+            patched(json!(["a"]), json!({ "a": "b" })),
+            json!({ "a": "b" })
+        );
+    }
+
+    #[test]
+    fn an_empty_patch_changes_nothing() {
+        let target = json!({ "a": { "b": null }, "c": [1, 2] });
+
+        assert_eq!(patched(target.clone(), json!({})), target);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod create_organization;
+pub mod organization;
+pub mod update_organization;
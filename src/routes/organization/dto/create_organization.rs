@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use crate::routes::validation::{Validate, Validator, ORGANIZATION_ID_REGEX};
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateOrganization {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+}
+
+impl Validate for CreateOrganization {
+    fn validate(&self, v: &mut Validator) {
+        v.check(
+            "id",
+            ORGANIZATION_ID_REGEX.is_match(&self.id),
+            "FORMAT",
+            "Invalid organization ID!",
+        )
+        .name("name", &self.name)
+        .description("description", &self.description);
+    }
+}
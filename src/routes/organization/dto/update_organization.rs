@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+use crate::routes::validation::{Validate, Validator};
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateOrganization {
+    pub name: String,
+    pub description: String,
+    pub enabled: bool,
+}
+
+impl Validate for UpdateOrganization {
+    fn validate(&self, v: &mut Validator) {
+        v.name("name", &self.name)
+            .description("description", &self.description);
+    }
+}
@@ -0,0 +1,201 @@
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse};
+use chrono::Utc;
+
+use crate::{
+    configuration::app_data_pool::AppDataPool,
+    errors::app_error::AppError,
+    persistence::organization::model::organization::{Organization, DEFAULT_ORGANIZATION},
+    routes::{
+        check_platform_admin, convert_organization_to_dto,
+        organization::dto::{
+            create_organization::CreateOrganization, update_organization::UpdateOrganization,
+        },
+        validation::Valid,
+    },
+    services::permission::system_permission::SystemPermission,
+};
+
+#[post("/")]
+pub async fn create_organization(
+    create: Valid<CreateOrganization>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    check_platform_admin(&req, &pool).await?;
+
+    if pool
+        .services
+        .organization_service
+        .find_by_id(&pool.database, &create.id)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::conflict(&format!(
+            "Organization with ID {} already exists!",
+            &create.id
+        )));
+    }
+
+    let new_organization = Organization {
+        id: create.id.clone(),
+        name: create.name.clone(),
+        description: create.description.clone(),
+        enabled: true,
+        created_at: Utc::now().to_string(),
+    };
+
+    let res = pool
+        .services
+        .organization_service
+        .create(new_organization, &pool.database)
+        .await?;
+
+    let res = match res {
+        Some(d) => d,
+        None => return Err(AppError::storage("Unable to create organization!")),
+    };
+
+    pool.services
+        .ensure_system_permissions(&pool.database, &res.id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(convert_organization_to_dto(res)))
+}
+
+#[get("/")]
+pub async fn get_all_organizations(
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    check_platform_admin(&req, &pool).await?;
+
+    let res = pool
+        .services
+        .organization_service
+        .find_all(&pool.database)
+        .await?;
+
+    let organizations: Vec<_> = res.into_iter().map(convert_organization_to_dto).collect();
+
+    Ok(HttpResponse::Ok().json(organizations))
+}
+
+#[get("/{id}")]
+pub async fn find_by_id(
+    path: web::Path<String>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    check_platform_admin(&req, &pool).await?;
+
+    match pool
+        .services
+        .organization_service
+        .find_by_id(&pool.database, &path)
+        .await?
+    {
+        Some(d) => Ok(HttpResponse::Ok().json(convert_organization_to_dto(d))),
+        None => Err(AppError::not_found("Organization not found!")),
+    }
+}
+
+#[put("/{id}")]
+pub async fn update_organization(
+    path: web::Path<String>,
+    update: Valid<UpdateOrganization>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    check_platform_admin(&req, &pool).await?;
+
+    if !update.enabled && path.as_str() == DEFAULT_ORGANIZATION {
+        return Err(AppError::validation(
+            "The default organization cannot be disabled!",
+        ));
+    }
+
+    let mut old_organization = match pool
+        .services
+        .organization_service
+        .find_by_id(&pool.database, &path)
+        .await?
+    {
+        Some(d) => d,
+        None => return Err(AppError::not_found("Organization not found!")),
+    };
+
+    old_organization.name = update.name.clone();
+    old_organization.description = update.description.clone();
+    old_organization.enabled = update.enabled;
+
+    let res = pool
+        .services
+        .organization_service
+        .update(&pool.database, &path, old_organization)
+        .await?;
+
+    match res {
+        Some(d) => Ok(HttpResponse::Ok().json(convert_organization_to_dto(d))),
+        None => Ok(HttpResponse::NoContent().body("")),
+    }
+}
+
+#[delete("/{id}")]
+pub async fn delete_organization(
+    path: web::Path<String>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    check_platform_admin(&req, &pool).await?;
+
+    if path.as_str() == DEFAULT_ORGANIZATION {
+        return Err(AppError::validation(
+            "The default organization cannot be deleted!",
+        ));
+    }
+
+    let users = pool
+        .services
+        .user_service
+        .find_all(&pool.database, &path)
+        .await;
+    let roles = pool
+        .services
+        .role_service
+        .find_all(&pool.database, &path)
+        .await;
+    let permissions = pool
+        .services
+        .permission_service
+        .find_all(&pool.database, &path)
+        .await;
+
+    // System permissions exist in every organization and are deleted together with it
+    match (users, roles, permissions) {
+        (Ok(u), Ok(r), Ok(p)) => {
+            let custom = p
+                .iter()
+                .any(|p| SystemPermission::from_name(&p.name).is_none());
+            if !u.is_empty() || !r.is_empty() || custom {
+                return Err(AppError::validation(&format!(
+                    "Organization {} still has users, roles or permissions!",
+                    path
+                )));
+            }
+        }
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+            return Err(AppError::from(e));
+        }
+    }
+
+    let deleted = pool
+        .services
+        .delete_organization(&pool.database, &pool.transactions, &path)
+        .await?;
+
+    if deleted == 0 {
+        return Err(AppError::not_found("Organization not found!"));
+    }
+
+    Ok(HttpResponse::Ok().body(""))
+}
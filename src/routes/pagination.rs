@@ -0,0 +1,90 @@
+use actix_web::{HttpRequest, HttpResponse};
+use mongodb::bson::doc;
+use serde::Serialize;
+
+use crate::{errors::app_error::AppError, persistence::page::PageRequest};
+
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+pub const MAX_PAGE_SIZE: i64 = 500;
+
+/// Turn the paging parameters of a request into a `PageRequest`. `sort` names one of the given
+/// fields and is prefixed with `-` for a descending order. Results are always ordered by id last,
+/// so that pages are stable.
+pub fn page_request(
+    offset: Option<u64>,
+    limit: Option<i64>,
+    sort: Option<&str>,
+    fields: &[&str],
+) -> Result<PageRequest, AppError> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(AppError::validation(&format!(
+            "Limit must be between 1 and {}!",
+            MAX_PAGE_SIZE
+        )));
+    }
+
+    let mut order = doc! {};
+    if let Some(sort) = sort {
+        let (field, direction) = match sort.strip_prefix('-') {
+            Some(d) => (d, -1),
+            None => (sort, 1),
+        };
+
+        if !fields.contains(&field) {
+            return Err(AppError::validation(&format!("Cannot sort by {}", field)));
+        }
+        order.insert(field, direction);
+    }
+    order.insert("_id", 1);
+
+    Ok(PageRequest {
+        sort: order,
+        offset: offset.unwrap_or(0),
+        limit,
+    })
+}
+
+/// Respond with a page of results. The number of results of the whole query is returned in the
+/// `X-Total-Count` header and the neighbouring pages are linked in the `Link` header.
+pub fn page_response<T: Serialize>(
+    req: &HttpRequest,
+    items: Vec<T>,
+    total: u64,
+    page: &PageRequest,
+) -> HttpResponse {
+    let limit = page.limit as u64;
+
+    // Keep every other parameter of the request, such as the filters and the sort order
+    let query: Vec<&str> = req
+        .query_string()
+        .split('&')
+        .filter(|p| {
+            let key = p.split('=').next().unwrap_or_default();
+            !p.is_empty() && key != "offset" && key != "limit"
+        })
+        .collect();
+
+    let link = |offset: u64, rel: &str| {
+        let mut params = query.clone();
+        let paging = format!("offset={}&limit={}", offset, limit);
+        params.push(&paging);
+        format!("<{}?{}>; rel=\"{}\"", req.path(), params.join("&"), rel)
+    };
+
+    let mut links = vec![link(0, "first")];
+    if page.offset > 0 {
+        links.push(link(page.offset.saturating_sub(limit), "prev"));
+    }
+    if page.offset + limit < total {
+        links.push(link(page.offset + limit, "next"));
+    }
+    if total > 0 {
+        links.push(link((total - 1) / limit * limit, "last"));
+    }
+
+    HttpResponse::Ok()
+        .insert_header(("X-Total-Count", total.to_string()))
+        .insert_header(("Link", links.join(", ")))
+        .json(items)
+}
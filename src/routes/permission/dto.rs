@@ -1,3 +1,5 @@
 pub mod create_permission;
 pub mod permission;
+pub mod permission_query;
+pub mod system_permission;
 pub mod update_permission;
@@ -1,8 +1,17 @@
 use serde::{Deserialize, Serialize};
 
+use crate::routes::validation::{Validate, Validator};
+
 #[derive(Serialize, Deserialize)]
 pub struct CreatePermission {
     pub name: String,
     pub description: String,
     pub permissions: Vec<String>,
 }
+
+impl Validate for CreatePermission {
+    fn validate(&self, v: &mut Validator) {
+        v.name("name", &self.name)
+            .description("description", &self.description);
+    }
+}
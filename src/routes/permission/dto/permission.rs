@@ -5,4 +5,9 @@ pub struct Permission {
     pub id: String,
     pub name: String,
     pub description: String,
+    pub organization: String,
+    pub version: i64,
+    pub managed: bool,
+    /// Part of the built-in catalog, which means that it cannot be renamed or deleted
+    pub system: bool,
 }
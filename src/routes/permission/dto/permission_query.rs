@@ -0,0 +1,10 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct PermissionQuery {
+    pub offset: Option<u64>,
+    pub limit: Option<i64>,
+    pub sort: Option<String>,
+    /// Prefix of the name
+    pub name: Option<String>,
+}
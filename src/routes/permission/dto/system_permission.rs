@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+/// A permission that guards the endpoints of the service itself
+#[derive(Serialize, Deserialize)]
+pub struct SystemPermission {
+    /// The id of the permission in the organization, which is used to add it to roles
+    pub id: Option<String>,
+    pub name: String,
+    pub description: String,
+}
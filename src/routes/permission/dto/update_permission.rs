@@ -1,7 +1,16 @@
 use serde::{Deserialize, Serialize};
 
+use crate::routes::validation::{Validate, Validator};
+
 #[derive(Serialize, Deserialize)]
 pub struct UpdatePermission {
     pub name: String,
     pub description: String,
 }
+
+impl Validate for UpdatePermission {
+    fn validate(&self, v: &mut Validator) {
+        v.name("name", &self.name)
+            .description("description", &self.description);
+    }
+}
@@ -1,260 +1,348 @@
-use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse};
+use std::collections::HashMap;
+
+use actix_web::{delete, get, patch, post, put, web, HttpRequest, HttpResponse};
 use uuid::Uuid;
 
 use crate::{
     configuration::app_data_pool::AppDataPool,
-    errors::{bad_request::BadRequest, internal_server_error::InternalServerError},
+    errors::app_error::AppError,
     persistence::permission::model::permission::Permission,
     routes::{
         convert_permission_to_dto,
+        etag::{check_if_match, etag},
+        merge_patch::MergePatch,
+        pagination::{page_request, page_response},
         permission::dto::{
-            create_permission::CreatePermission, update_permission::UpdatePermission,
+            create_permission::CreatePermission, permission_query::PermissionQuery,
+            system_permission::SystemPermission as SystemPermissionDto,
+            update_permission::UpdatePermission,
         },
+        validation::Valid,
+        EntityPath,
     },
+    services::permission::system_permission::SystemPermission,
 };
 
 #[post("/")]
 pub async fn create_permission(
-    create: web::Json<CreatePermission>,
+    create: Valid<CreatePermission>,
     pool: web::Data<AppDataPool>,
     req: HttpRequest,
-) -> HttpResponse {
-    if !crate::routes::check_user_permissions(&req, &pool, "CAN_CREATE_PERMISSION").await {
-        return HttpResponse::Unauthorized().body("");
-    }
-
-    if create.name.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Name cannot be empty!"));
-    }
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::CreatePermission,
+    )
+    .await?;
 
-    match pool
+    if let Some(x) = pool
         .services
         .permission_service
-        .find_by_name(&pool.database, &create.name)
-        .await
+        .find_by_name(&pool.database, &organization, &create.name)
+        .await?
     {
-        Ok(d) => {
-            if let Some(x) = d {
-                return HttpResponse::BadRequest().json(BadRequest::new(&format!(
-                    "Permission with name {} already exists!",
-                    x.name
-                )));
-            }
-        }
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    };
+        return Err(AppError::conflict(&format!(
+            "Permission with name {} already exists!",
+            x.name
+        )));
+    }
 
     let new_permission = Permission {
         id: Uuid::new_v4().to_string(),
         name: create.name.clone(),
         description: create.description.clone(),
+        organization,
+        version: 0,
+        managed: false,
     };
 
-    let res = match pool
+    let res = pool
         .services
         .permission_service
-        .create(new_permission, &pool.database)
-        .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    };
+        .create(new_permission, &pool.database, None)
+        .await?;
 
     let res = match res {
         Some(d) => d,
-        None => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new("Unable to create permission!"))
-        }
+        None => return Err(AppError::storage("Unable to create permission!")),
     };
 
-    HttpResponse::Ok().json(convert_permission_to_dto(res))
+    Ok(HttpResponse::Ok().json(convert_permission_to_dto(res)))
 }
 
 #[get("/")]
-pub async fn get_all_permissions(pool: web::Data<AppDataPool>, req: HttpRequest) -> HttpResponse {
-    if !crate::routes::check_user_permissions(&req, &pool, "CAN_READ_PERMISSION").await {
-        return HttpResponse::Unauthorized().body("");
-    }
+pub async fn get_all_permissions(
+    query: web::Query<PermissionQuery>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ReadPermission,
+    )
+    .await?;
 
-    let res = match pool
+    let page = page_request(query.offset, query.limit, query.sort.as_deref(), &["name"])?;
+
+    let res = pool
         .services
         .permission_service
-        .find_all(&pool.database)
-        .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    };
+        .find_page(&pool.database, &organization, query.name.as_deref(), &page)
+        .await?;
 
-    let mut permissions = vec![];
-    for permisison in res {
-        permissions.push(convert_permission_to_dto(permisison));
-    }
+    let permissions = res
+        .items
+        .into_iter()
+        .map(convert_permission_to_dto)
+        .collect::<Vec<_>>();
+
+    Ok(page_response(&req, permissions, res.total, &page))
+}
+
+/// List the built-in permissions that guard the endpoints of the service, together with their ids
+/// in the organization
+#[get("/system")]
+pub async fn get_system_permissions(
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ReadPermission,
+    )
+    .await?;
 
-    HttpResponse::Ok().json(permissions)
+    let ids: HashMap<String, String> = pool
+        .services
+        .permission_service
+        .find_all(&pool.database, &organization)
+        .await?
+        .into_iter()
+        .map(|p| (p.name, p.id))
+        .collect();
+
+    let permissions = SystemPermission::ALL
+        .iter()
+        .map(|p| SystemPermissionDto {
+            id: ids.get(p.name()).cloned(),
+            name: String::from(p.name()),
+            description: String::from(p.description()),
+        })
+        .collect::<Vec<_>>();
+
+    Ok(HttpResponse::Ok().json(permissions))
 }
 
 #[get("/{uuid}")]
 pub async fn find_by_uuid(
-    path: web::Path<String>,
+    path: web::Path<EntityPath>,
     pool: web::Data<AppDataPool>,
     req: HttpRequest,
-) -> HttpResponse {
-    if !crate::routes::check_user_permissions(&req, &pool, "CAN_READ_PERMISSION").await {
-        return HttpResponse::Unauthorized().body("");
-    }
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ReadPermission,
+    )
+    .await?;
 
     let res = match pool
         .services
         .permission_service
-        .find_by_uuid(&pool.database, &path)
-        .await
+        .find_by_uuid(&pool.database, &organization, &path)
+        .await?
     {
-        Ok(d) => match d {
-            Some(d) => d,
-            None => return HttpResponse::NotFound().body(""),
-        },
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
+        Some(d) => d,
+        None => return Err(AppError::not_found("Permission not found!")),
     };
 
-    HttpResponse::Ok().json(convert_permission_to_dto(res))
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(res.version))
+        .json(convert_permission_to_dto(res)))
 }
 
 #[put("/{uuid}")]
 pub async fn update_permission(
     pool: web::Data<AppDataPool>,
-    update: web::Json<UpdatePermission>,
-    path: web::Path<String>,
+    update: Valid<UpdatePermission>,
+    path: web::Path<EntityPath>,
     req: HttpRequest,
-) -> HttpResponse {
-    if !crate::routes::check_user_permissions(&req, &pool, "CAN_UPDATE_PERMISSION").await {
-        return HttpResponse::Unauthorized().body("");
-    }
+) -> Result<HttpResponse, AppError> {
+    let (organization, permission) =
+        find_permission_for_update(&req, &pool, &path.into_inner().uuid).await?;
 
-    if update.name.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Name cannot be empty!"));
-    }
+    apply_permission_update(&pool, &organization, permission, update.into_inner()).await
+}
 
-    let mut old_permission = match pool
+#[patch("/{uuid}")]
+pub async fn patch_permission(
+    pool: web::Data<AppDataPool>,
+    patch: MergePatch,
+    path: web::Path<EntityPath>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (organization, permission) =
+        find_permission_for_update(&req, &pool, &path.into_inner().uuid).await?;
+
+    let current = UpdatePermission {
+        name: permission.name.clone(),
+        description: permission.description.clone(),
+    };
+    let update = patch.apply(&current)?;
+
+    apply_permission_update(&pool, &organization, permission, update).await
+}
+
+#[delete("/{uuid}")]
+pub async fn delete_permission(
+    path: web::Path<EntityPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::DeletePermission,
+    )
+    .await?;
+
+    let permission = match pool
         .services
         .permission_service
-        .find_by_uuid(&pool.database, &path)
-        .await
+        .find_by_uuid(&pool.database, &organization, &path)
+        .await?
     {
-        Ok(d) => match d {
-            Some(d) => d,
-            None => return HttpResponse::NotFound().body(""),
-        },
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
+        Some(x) => x,
+        None => return Err(AppError::not_found("Permission not found!")),
     };
 
+    if permission.managed {
+        return Err(managed_permission(&permission));
+    }
+
+    if SystemPermission::from_name(&permission.name).is_some() {
+        return Err(AppError::conflict(&format!(
+            "System permission {} cannot be deleted!",
+            permission.name
+        )));
+    }
+
+    let version = check_if_match(&req, permission.version)?;
+
     match pool
         .services
-        .permission_service
-        .find_by_name(&pool.database, &update.name)
-        .await
+        .delete_permission(
+            &pool.database,
+            &pool.transactions,
+            &organization,
+            &path,
+            version,
+        )
+        .await?
     {
-        Ok(d) => {
-            if let Some(x) = d {
-                if x.id != old_permission.id {
-                    return HttpResponse::BadRequest().json(BadRequest::new(&format!(
-                        "Permission with name {} already exists!",
-                        update.name
-                    )));
-                }
-            }
-        }
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    };
+        0 if version.is_some() => Err(AppError::precondition_failed(
+            "Permission was changed by another request!",
+        )),
+        _ => Ok(HttpResponse::Ok().body("")),
+    }
+}
 
-    old_permission.name = update.name.clone();
-    old_permission.description = update.description.clone();
+/// Permissions that are owned by the seed manifest can only be changed by changing the manifest
+fn managed_permission(permission: &Permission) -> AppError {
+    AppError::conflict(&format!(
+        "Permission {} is managed by the seed manifest!",
+        permission.name
+    ))
+}
 
-    let res = match pool
+/// Find a permission that the caller is about to update, after checking the permissions of the
+/// caller and the preconditions of the request
+async fn find_permission_for_update(
+    req: &HttpRequest,
+    pool: &web::Data<AppDataPool>,
+    uuid: &str,
+) -> Result<(String, Permission), AppError> {
+    let (_, organization) = crate::routes::check_organization_permissions(
+        req,
+        pool,
+        SystemPermission::UpdatePermission,
+    )
+    .await?;
+
+    let permission = match pool
         .services
         .permission_service
-        .update(&pool.database, &path, old_permission)
-        .await
+        .find_by_uuid(&pool.database, &organization, uuid)
+        .await?
     {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    };
-
-    let res = match res {
         Some(d) => d,
-        None => return HttpResponse::NoContent().body(""),
+        None => return Err(AppError::not_found("Permission not found!")),
     };
 
-    HttpResponse::Ok().json(convert_permission_to_dto(res))
+    if permission.managed {
+        return Err(managed_permission(&permission));
+    }
+
+    check_if_match(req, permission.version)?;
+
+    Ok((organization, permission))
 }
 
-#[delete("/{uuid}")]
-pub async fn delete_permission(
-    path: web::Path<String>,
-    pool: web::Data<AppDataPool>,
-    req: HttpRequest,
-) -> HttpResponse {
-    if !crate::routes::check_user_permissions(&req, &pool, "CAN_DELETE_PERMISSION").await {
-        return HttpResponse::Unauthorized().body("");
+/// Apply a full update to a permission, which either comes from a `PUT` or from a patched `PATCH`
+/// body
+async fn apply_permission_update(
+    pool: &web::Data<AppDataPool>,
+    organization: &str,
+    mut permission: Permission,
+    update: UpdatePermission,
+) -> Result<HttpResponse, AppError> {
+    let path = permission.id.clone();
+
+    if update.name != permission.name && SystemPermission::from_name(&permission.name).is_some() {
+        return Err(AppError::conflict(&format!(
+            "System permission {} cannot be renamed!",
+            permission.name
+        )));
     }
 
-    let mut roles_with_permission = match pool
+    if let Some(x) = pool
         .services
-        .role_service
-        .find_by_permission_id(&pool.database, &path)
-        .await
+        .permission_service
+        .find_by_name(&pool.database, organization, &update.name)
+        .await?
     {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    };
-
-    for role in &mut roles_with_permission {
-        role.permissions.retain(|x| *x != path.to_string());
-
-        let response = pool
-            .services
-            .role_service
-            .update(&pool.database, &role.id, role.clone())
-            .await;
-        if let Err(e) = response {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
+        if x.id != permission.id {
+            return Err(AppError::conflict(&format!(
+                "Permission with name {} already exists!",
+                update.name
+            )));
         }
     }
 
-    if let Err(e) = pool
+    permission.name = update.name.clone();
+    permission.description = update.description.clone();
+
+    let res = pool
         .services
         .permission_service
-        .delete(&pool.database, &path)
-        .await
-    {
-        return HttpResponse::InternalServerError().json(InternalServerError::new(&e.to_string()));
+        .update(&pool.database, organization, &path, permission, None)
+        .await?;
+
+    let res = match res {
+        Some(d) => d,
+        None => {
+            return Err(AppError::precondition_failed(
+                "Permission was changed by another request!",
+            ))
+        }
     };
 
-    HttpResponse::Ok().body("")
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(res.version))
+        .json(convert_permission_to_dto(res)))
 }
@@ -0,0 +1,3 @@
+pub mod create_policy;
+pub mod policy;
+pub mod update_policy;
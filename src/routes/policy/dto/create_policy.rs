@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    persistence::policy::model::policy::{Condition, Effect},
+    routes::validation::{Validate, Validator},
+    services::policy::policy_service::validate_condition,
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct CreatePolicy {
+    pub name: String,
+    pub description: String,
+    pub effect: Effect,
+    pub actions: Vec<String>,
+    pub conditions: Vec<Condition>,
+    pub enabled: bool,
+}
+
+/// The rules that policies are created and updated with
+pub fn validate_policy(
+    v: &mut Validator,
+    name: &str,
+    description: &str,
+    actions: &[String],
+    conditions: &[Condition],
+) {
+    v.name("name", name)
+        .description("description", description)
+        .check(
+            "actions",
+            !actions.is_empty(),
+            "REQUIRED",
+            "Actions cannot be empty!",
+        );
+    for (i, action) in actions.iter().enumerate() {
+        v.required(&format!("actions[{}]", i), action);
+    }
+    for (i, condition) in conditions.iter().enumerate() {
+        if let Err(e) = validate_condition(condition) {
+            v.check(&format!("conditions[{}]", i), false, "INVALID", &e);
+        }
+    }
+}
+
+impl Validate for CreatePolicy {
+    fn validate(&self, v: &mut Validator) {
+        validate_policy(
+            v,
+            &self.name,
+            &self.description,
+            &self.actions,
+            &self.conditions,
+        );
+    }
+}
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+use crate::persistence::policy::model::policy::{Condition, Effect};
+
+#[derive(Serialize, Deserialize)]
+pub struct Policy {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub effect: Effect,
+    pub actions: Vec<String>,
+    pub conditions: Vec<Condition>,
+    pub enabled: bool,
+    pub organization: String,
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    persistence::policy::model::policy::{Condition, Effect},
+    routes::validation::{Validate, Validator},
+};
+
+use super::create_policy::validate_policy;
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdatePolicy {
+    pub name: String,
+    pub description: String,
+    pub effect: Effect,
+    pub actions: Vec<String>,
+    pub conditions: Vec<Condition>,
+    pub enabled: bool,
+}
+
+impl Validate for UpdatePolicy {
+    fn validate(&self, v: &mut Validator) {
+        validate_policy(
+            v,
+            &self.name,
+            &self.description,
+            &self.actions,
+            &self.conditions,
+        );
+    }
+}
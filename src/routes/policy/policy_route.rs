@@ -0,0 +1,173 @@
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse};
+use uuid::Uuid;
+
+use crate::{
+    configuration::app_data_pool::AppDataPool,
+    errors::app_error::AppError,
+    persistence::policy::model::policy::Policy,
+    routes::{
+        convert_policy_to_dto,
+        policy::dto::{create_policy::CreatePolicy, update_policy::UpdatePolicy},
+        validation::Valid,
+    },
+    services::permission::system_permission::SystemPermission,
+};
+
+#[post("/")]
+pub async fn create_policy(
+    create: Valid<CreatePolicy>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::ManagePolicy)
+            .await?;
+
+    if pool
+        .services
+        .policy_service
+        .find_by_name(&pool.database, &organization, &create.name)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::conflict(&format!(
+            "Policy with name {} already exists!",
+            &create.name
+        )));
+    }
+
+    let new_policy = Policy {
+        id: Uuid::new_v4().to_string(),
+        name: create.name.clone(),
+        description: create.description.clone(),
+        effect: create.effect,
+        actions: create.actions.clone(),
+        conditions: create.conditions.clone(),
+        enabled: create.enabled,
+        organization,
+    };
+
+    let res = pool
+        .services
+        .policy_service
+        .create(new_policy, &pool.database)
+        .await?;
+
+    match res {
+        Some(d) => Ok(HttpResponse::Ok().json(convert_policy_to_dto(d))),
+        None => Err(AppError::storage("Unable to create policy!")),
+    }
+}
+
+#[get("/")]
+pub async fn get_all_policies(
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::ManagePolicy)
+            .await?;
+
+    let res = pool
+        .services
+        .policy_service
+        .find_all(&pool.database, &organization)
+        .await?;
+
+    let policies: Vec<_> = res.into_iter().map(convert_policy_to_dto).collect();
+
+    Ok(HttpResponse::Ok().json(policies))
+}
+
+#[get("/{uuid}")]
+pub async fn find_by_uuid(
+    path: web::Path<String>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::ManagePolicy)
+            .await?;
+
+    match pool
+        .services
+        .policy_service
+        .find_by_uuid(&pool.database, &organization, &path)
+        .await?
+    {
+        Some(d) => Ok(HttpResponse::Ok().json(convert_policy_to_dto(d))),
+        None => Err(AppError::not_found("Policy not found!")),
+    }
+}
+
+#[put("/{uuid}")]
+pub async fn update_policy(
+    path: web::Path<String>,
+    update: Valid<UpdatePolicy>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::ManagePolicy)
+            .await?;
+
+    let mut old_policy = match pool
+        .services
+        .policy_service
+        .find_by_uuid(&pool.database, &organization, &path)
+        .await?
+    {
+        Some(d) => d,
+        None => return Err(AppError::not_found("Policy not found!")),
+    };
+
+    if let Some(x) = pool
+        .services
+        .policy_service
+        .find_by_name(&pool.database, &organization, &update.name)
+        .await?
+    {
+        if x.id != old_policy.id {
+            return Err(AppError::conflict(&format!(
+                "Policy with name {} already exists!",
+                update.name
+            )));
+        }
+    }
+
+    old_policy.name = update.name.clone();
+    old_policy.description = update.description.clone();
+    old_policy.effect = update.effect;
+    old_policy.actions = update.actions.clone();
+    old_policy.conditions = update.conditions.clone();
+    old_policy.enabled = update.enabled;
+
+    let res = pool
+        .services
+        .policy_service
+        .update(&pool.database, &organization, &path, old_policy)
+        .await?;
+
+    match res {
+        Some(d) => Ok(HttpResponse::Ok().json(convert_policy_to_dto(d))),
+        None => Ok(HttpResponse::NoContent().body("")),
+    }
+}
+
+#[delete("/{uuid}")]
+pub async fn delete_policy(
+    path: web::Path<String>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::ManagePolicy)
+            .await?;
+
+    pool.services
+        .policy_service
+        .delete(&pool.database, &organization, &path)
+        .await?;
+
+    Ok(HttpResponse::Ok().body(""))
+}
@@ -0,0 +1,8 @@
+pub mod check_request;
+pub mod check_response;
+pub mod expand_request;
+pub mod namespace;
+pub mod read_query;
+pub mod tuple;
+pub mod update_namespace;
+pub mod write_request;
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+use crate::routes::validation::{Validate, Validator};
+
+#[derive(Serialize, Deserialize)]
+pub struct CheckRequest {
+    pub object: String,
+    pub relation: String,
+    pub subject: String,
+}
+
+impl Validate for CheckRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("object", &self.object)
+            .required("relation", &self.relation)
+            .required("subject", &self.subject);
+    }
+}
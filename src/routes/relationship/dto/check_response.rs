@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct CheckResponse {
+    pub allowed: bool,
+}
+
+impl CheckResponse {
+    pub fn new(allowed: bool) -> Self {
+        Self { allowed }
+    }
+}
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+use crate::routes::validation::{Validate, Validator};
+
+#[derive(Serialize, Deserialize)]
+pub struct ExpandRequest {
+    pub object: String,
+    pub relation: String,
+}
+
+impl Validate for ExpandRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("object", &self.object)
+            .required("relation", &self.relation);
+    }
+}
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+use crate::persistence::relationship::model::namespace::Relation;
+
+#[derive(Serialize, Deserialize)]
+pub struct Namespace {
+    pub name: String,
+    pub relations: Vec<Relation>,
+}
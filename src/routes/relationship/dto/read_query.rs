@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct ReadQuery {
+    pub namespace: Option<String>,
+    #[serde(rename(serialize = "objectId", deserialize = "objectId"))]
+    pub object_id: Option<String>,
+    pub relation: Option<String>,
+    pub subject: Option<String>,
+}
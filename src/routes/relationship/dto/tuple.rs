@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct Tuple {
+    pub object: String,
+    pub relation: String,
+    pub subject: String,
+}
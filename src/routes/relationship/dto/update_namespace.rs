@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    persistence::relationship::model::namespace::Relation,
+    routes::validation::{Validate, Validator},
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateNamespace {
+    pub relations: Vec<Relation>,
+}
+
+impl Validate for UpdateNamespace {
+    fn validate(&self, v: &mut Validator) {
+        for (i, relation) in self.relations.iter().enumerate() {
+            v.name(&format!("relations[{}].name", i), &relation.name);
+        }
+    }
+}
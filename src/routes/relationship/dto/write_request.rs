@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+use super::tuple::Tuple;
+
+use crate::routes::validation::{Validate, Validator};
+
+#[derive(Serialize, Deserialize)]
+pub struct WriteRequest {
+    #[serde(default)]
+    pub writes: Vec<Tuple>,
+    #[serde(default)]
+    pub deletes: Vec<Tuple>,
+}
+
+impl Validate for WriteRequest {
+    fn validate(&self, v: &mut Validator) {
+        for (field, tuples) in [("writes", &self.writes), ("deletes", &self.deletes)] {
+            for (i, tuple) in tuples.iter().enumerate() {
+                v.required(&format!("{}[{}].object", field, i), &tuple.object)
+                    .required(&format!("{}[{}].relation", field, i), &tuple.relation)
+                    .required(&format!("{}[{}].subject", field, i), &tuple.subject);
+            }
+        }
+    }
+}
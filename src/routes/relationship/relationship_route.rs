@@ -0,0 +1,325 @@
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse};
+
+use crate::{
+    configuration::app_data_pool::AppDataPool,
+    errors::app_error::AppError,
+    persistence::relationship::model::{namespace::Namespace, relation_tuple::RelationTuple},
+    routes::{
+        convert_namespace_to_dto, convert_tuple_to_dto,
+        relationship::dto::{
+            check_request::CheckRequest, check_response::CheckResponse,
+            expand_request::ExpandRequest, read_query::ReadQuery, tuple::Tuple,
+            update_namespace::UpdateNamespace, write_request::WriteRequest,
+        },
+        validation::Valid,
+    },
+    services::{
+        permission::system_permission::SystemPermission,
+        relationship::relationship_service::{parse_object, validate_namespace, SubjectRef},
+    },
+};
+
+fn parse_tuple(organization: &str, tuple: &Tuple) -> Result<RelationTuple, String> {
+    let (namespace, object_id) = match parse_object(&tuple.object) {
+        Some(d) => d,
+        None => return Err(format!("Invalid object {}", tuple.object)),
+    };
+
+    let subject = match SubjectRef::parse(&tuple.subject) {
+        Some(d) => d,
+        None => return Err(format!("Invalid subject {}", tuple.subject)),
+    };
+
+    if tuple.relation.is_empty() {
+        return Err(String::from("Relation cannot be empty!"));
+    }
+
+    Ok(RelationTuple::new(
+        organization,
+        &namespace,
+        &object_id,
+        &tuple.relation,
+        &subject.namespace,
+        &subject.id,
+        subject.relation.as_deref(),
+    ))
+}
+
+#[post("/check")]
+pub async fn check(
+    check: Valid<CheckRequest>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ReadRelationship,
+    )
+    .await?;
+
+    let (namespace, object_id) = match parse_object(&check.object) {
+        Some(d) => d,
+        None => {
+            return Err(AppError::validation(&format!(
+                "Invalid object {}",
+                check.object
+            )))
+        }
+    };
+
+    let subject = match SubjectRef::parse(&check.subject) {
+        Some(d) => d,
+        None => {
+            return Err(AppError::validation(&format!(
+                "Invalid subject {}",
+                check.subject
+            )))
+        }
+    };
+
+    let allowed = pool
+        .services
+        .relationship_service
+        .check(
+            &pool.database,
+            &organization,
+            &namespace,
+            &object_id,
+            &check.relation,
+            &subject,
+        )
+        .await?;
+    Ok(HttpResponse::Ok().json(CheckResponse::new(allowed)))
+}
+
+#[post("/expand")]
+pub async fn expand(
+    expand: Valid<ExpandRequest>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ReadRelationship,
+    )
+    .await?;
+
+    let (namespace, object_id) = match parse_object(&expand.object) {
+        Some(d) => d,
+        None => {
+            return Err(AppError::validation(&format!(
+                "Invalid object {}",
+                expand.object
+            )))
+        }
+    };
+
+    let tree = pool
+        .services
+        .relationship_service
+        .expand(
+            &pool.database,
+            &organization,
+            &namespace,
+            &object_id,
+            &expand.relation,
+        )
+        .await?;
+    Ok(HttpResponse::Ok().json(tree))
+}
+
+#[get("/tuples")]
+pub async fn read_tuples(
+    query: web::Query<ReadQuery>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ReadRelationship,
+    )
+    .await?;
+
+    let subject = match &query.subject {
+        Some(x) => match SubjectRef::parse(x) {
+            Some(d) => Some(d),
+            None => return Err(AppError::validation(&format!("Invalid subject {}", x))),
+        },
+        None => None,
+    };
+
+    let res = pool
+        .services
+        .relationship_service
+        .read(
+            &pool.database,
+            &organization,
+            query.namespace.as_deref(),
+            query.object_id.as_deref(),
+            query.relation.as_deref(),
+            subject.as_ref(),
+        )
+        .await?;
+
+    let tuples: Vec<_> = res.into_iter().map(convert_tuple_to_dto).collect();
+
+    Ok(HttpResponse::Ok().json(tuples))
+}
+
+#[post("/tuples")]
+pub async fn write_tuples(
+    write: Valid<WriteRequest>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::WriteRelationship,
+    )
+    .await?;
+
+    let mut writes = vec![];
+    for tuple in &write.writes {
+        match parse_tuple(&organization, tuple) {
+            Ok(d) => writes.push(d),
+            Err(e) => return Err(AppError::validation(&e)),
+        }
+    }
+
+    let mut deletes = vec![];
+    for tuple in &write.deletes {
+        match parse_tuple(&organization, tuple) {
+            Ok(d) => deletes.push(d),
+            Err(e) => return Err(AppError::validation(&e)),
+        }
+    }
+
+    pool.services
+        .relationship_service
+        .write(
+            &pool.database,
+            &pool.transactions,
+            &organization,
+            writes,
+            deletes,
+        )
+        .await?;
+    Ok(HttpResponse::Ok().body(""))
+}
+
+#[get("/namespaces")]
+pub async fn get_all_namespaces(
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ReadRelationship,
+    )
+    .await?;
+
+    let res = pool
+        .services
+        .relationship_service
+        .find_all_namespaces(&pool.database, &organization)
+        .await?;
+
+    let namespaces: Vec<_> = res.into_iter().map(convert_namespace_to_dto).collect();
+
+    Ok(HttpResponse::Ok().json(namespaces))
+}
+
+#[get("/namespaces/{name}")]
+pub async fn find_namespace_by_name(
+    path: web::Path<String>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ReadRelationship,
+    )
+    .await?;
+
+    match pool
+        .services
+        .relationship_service
+        .find_namespace_by_name(&pool.database, &organization, &path)
+        .await?
+    {
+        Some(d) => Ok(HttpResponse::Ok().json(convert_namespace_to_dto(d))),
+        None => Err(AppError::not_found("Namespace not found!")),
+    }
+}
+
+#[put("/namespaces/{name}")]
+pub async fn save_namespace(
+    path: web::Path<String>,
+    update: Valid<UpdateNamespace>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ManageNamespace,
+    )
+    .await?;
+
+    let namespace = Namespace::new(&organization, &path, update.relations.clone());
+
+    if let Err(e) = validate_namespace(&namespace) {
+        return Err(AppError::validation(&e));
+    }
+
+    let res = pool
+        .services
+        .relationship_service
+        .save_namespace(&pool.database, namespace)
+        .await?;
+
+    match res {
+        Some(d) => Ok(HttpResponse::Ok().json(convert_namespace_to_dto(d))),
+        None => Err(AppError::storage("Unable to save namespace!")),
+    }
+}
+
+#[delete("/namespaces/{name}")]
+pub async fn delete_namespace(
+    path: web::Path<String>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ManageNamespace,
+    )
+    .await?;
+
+    if !pool
+        .services
+        .relationship_service
+        .read(&pool.database, &organization, Some(&path), None, None, None)
+        .await?
+        .is_empty()
+    {
+        return Err(AppError::validation(&format!(
+            "Namespace {} still has relation tuples!",
+            path
+        )));
+    }
+
+    pool.services
+        .relationship_service
+        .delete_namespace(&pool.database, &organization, &path)
+        .await?;
+
+    Ok(HttpResponse::Ok().body(""))
+}
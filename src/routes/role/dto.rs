@@ -1,3 +1,5 @@
 pub mod create_role;
 pub mod role;
+pub mod role_permission_path;
+pub mod role_query;
 pub mod update_role;
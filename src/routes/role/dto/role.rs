@@ -8,4 +8,9 @@ pub struct Role {
     pub name: String,
     pub description: String,
     pub permissions: Vec<PermissionDto>,
+    #[serde(rename = "approverPermissions")]
+    pub approver_permissions: Vec<String>,
+    pub organization: String,
+    pub version: i64,
+    pub managed: bool,
 }
@@ -0,0 +1,8 @@
+use serde::Deserialize;
+
+/// Path of a single permission of a role
+#[derive(Deserialize)]
+pub struct RolePermissionPath {
+    pub uuid: String,
+    pub permission: String,
+}
@@ -1,8 +1,22 @@
 use serde::{Deserialize, Serialize};
 
+use crate::routes::validation::{Validate, Validator};
+
 #[derive(Serialize, Deserialize)]
 pub struct UpdateRole {
     pub name: String,
     pub description: String,
     pub permissions: Vec<String>,
+    /// The permissions whose holders decide on access requests for this role
+    #[serde(rename = "approverPermissions", default)]
+    pub approver_permissions: Vec<String>,
+}
+
+impl Validate for UpdateRole {
+    fn validate(&self, v: &mut Validator) {
+        v.name("name", &self.name)
+            .description("description", &self.description)
+            .uuids("permissions", &self.permissions)
+            .uuids("approverPermissions", &self.approver_permissions);
+    }
 }
@@ -1,316 +1,454 @@
-use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse};
+use actix_web::{delete, get, patch, post, put, web, HttpRequest, HttpResponse};
 use uuid::Uuid;
 
 use crate::{
     configuration::app_data_pool::AppDataPool,
-    errors::{bad_request::BadRequest, internal_server_error::InternalServerError},
-    persistence::role::model::role::Role,
+    errors::app_error::AppError,
+    persistence::{role::model::role::Role, user::model::user::User},
     routes::{
-        convert_role_to_dto,
-        role::dto::{create_role::CreateRole, update_role::UpdateRole},
+        check_grants, convert_role_to_dto,
+        etag::{check_if_match, etag},
+        merge_patch::MergePatch,
+        pagination::{page_request, page_response},
+        role::dto::{
+            create_role::CreateRole, role_permission_path::RolePermissionPath,
+            role_query::RoleQuery, update_role::UpdateRole,
+        },
+        validation::Valid,
+        EntityPath,
     },
+    services::permission::system_permission::SystemPermission,
 };
 
 #[post("/")]
 pub async fn create_new_role(
-    create: web::Json<CreateRole>,
+    create: Valid<CreateRole>,
     pool: web::Data<AppDataPool>,
     req: HttpRequest,
-) -> HttpResponse {
-    if !crate::routes::check_user_permissions(&req, &pool, "CAN_CREATE_ROLE").await {
-        return HttpResponse::Unauthorized().body("");
-    }
-    if create.name.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Name cannot be empty!"));
-    }
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::CreateRole)
+            .await?;
 
-    match pool
+    if pool
         .services
         .role_service
-        .find_by_name(&pool.database, &create.name)
-        .await
+        .find_by_name(&pool.database, &organization, &create.name)
+        .await?
+        .is_some()
     {
-        Ok(d) => {
-            if d.is_some() {
-                return HttpResponse::BadRequest().json(BadRequest::new(&format!(
-                    "Role with name {} already exists!",
-                    &create.name
-                )));
-            }
-        }
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    };
+        return Err(AppError::conflict(&format!(
+            "Role with name {} already exists!",
+            &create.name
+        )));
+    }
 
     for permission in &create.permissions {
-        match pool
+        if pool
             .services
             .permission_service
-            .find_by_uuid(&pool.database, permission)
-            .await
+            .find_by_uuid(&pool.database, &organization, permission)
+            .await?
+            .is_none()
         {
-            Ok(d) => {
-                if d.is_none() {
-                    return HttpResponse::BadRequest().json(BadRequest::new(&format!(
-                        "Invalid permission {}",
-                        permission
-                    )));
-                }
-            }
-            Err(e) => {
-                return HttpResponse::InternalServerError()
-                    .json(InternalServerError::new(&e.to_string()));
-            }
-        };
+            return Err(AppError::validation(&format!(
+                "Invalid permission {}",
+                permission
+            )));
+        }
     }
 
+    for approver in &create.approver_permissions {
+        if pool
+            .services
+            .permission_service
+            .find_by_uuid(&pool.database, &organization, approver)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::validation(&format!(
+                "Invalid approver permission {}",
+                approver
+            )));
+        }
+    }
+
+    check_grants(
+        &req,
+        &pool,
+        &caller,
+        &organization,
+        &[],
+        &create.permissions,
+    )
+    .await?;
+
     let new_role = Role {
         id: Uuid::new_v4().to_string(),
         name: create.name.clone(),
         description: create.description.clone(),
         permissions: create.permissions.clone(),
+        approver_permissions: create.approver_permissions.clone(),
+        organization,
+        version: 0,
+        managed: false,
     };
 
-    let res = match pool
+    let res = pool
         .services
         .role_service
-        .create(new_role, &pool.database)
-        .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    };
+        .create(new_role, &pool.database, None)
+        .await?;
 
     let res = match res {
         Some(d) => d,
-        None => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new("Unable to create role!"))
-        }
+        None => return Err(AppError::storage("Unable to create role!")),
     };
 
-    match convert_role_to_dto(res, &pool.database, &pool.services.permission_service).await {
-        Ok(d) => HttpResponse::Ok().json(d),
-        Err(e) => {
-            HttpResponse::InternalServerError().json(InternalServerError::new(&e.to_string()))
-        }
-    }
+    let dto = convert_role_to_dto(res, &pool.database, &pool.services.permission_service).await?;
+    Ok(HttpResponse::Ok().json(dto))
 }
 
 #[get("/")]
-pub async fn get_all_roles(req: HttpRequest, pool: web::Data<AppDataPool>) -> HttpResponse {
-    if !crate::routes::check_user_permissions(&req, &pool, "CAN_READ_ROLE").await {
-        return HttpResponse::Unauthorized().body("");
-    }
+pub async fn get_all_roles(
+    query: web::Query<RoleQuery>,
+    req: HttpRequest,
+    pool: web::Data<AppDataPool>,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::ReadRole)
+            .await?;
 
-    let roles = match pool.services.role_service.find_all(&pool.database).await {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    };
+    let page = page_request(query.offset, query.limit, query.sort.as_deref(), &["name"])?;
+
+    let roles = pool
+        .services
+        .role_service
+        .find_page(&pool.database, &organization, query.name.as_deref(), &page)
+        .await?;
 
     let mut role_dto = vec![];
-    for role in roles {
-        match convert_role_to_dto(role, &pool.database, &pool.services.permission_service).await {
-            Ok(d) => {
-                role_dto.push(d);
-            }
-            Err(e) => {
-                return HttpResponse::InternalServerError()
-                    .json(InternalServerError::new(&e.to_string()));
-            }
-        }
+    for role in roles.items {
+        let d =
+            convert_role_to_dto(role, &pool.database, &pool.services.permission_service).await?;
+        role_dto.push(d);
     }
 
-    HttpResponse::Ok().json(role_dto)
+    Ok(page_response(&req, role_dto, roles.total, &page))
 }
 
 #[get("/{uuid}")]
 pub async fn get_role_by_id(
-    path: web::Path<String>,
+    path: web::Path<EntityPath>,
     pool: web::Data<AppDataPool>,
     req: HttpRequest,
-) -> HttpResponse {
-    if !crate::routes::check_user_permissions(&req, &pool, "CAN_READ_ROLE").await {
-        return HttpResponse::Unauthorized().body("");
-    }
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::ReadRole)
+            .await?;
 
-    let res = match pool
+    let res = pool
         .services
         .role_service
-        .find_by_uuid(&pool.database, &path)
-        .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    };
+        .find_by_uuid(&pool.database, &organization, &path)
+        .await?;
 
     let role = match res {
         Some(d) => d,
         None => {
-            return HttpResponse::NotFound().body("");
+            return Err(AppError::not_found("Role not found!"));
         }
     };
 
-    match convert_role_to_dto(role, &pool.database, &pool.services.permission_service).await {
-        Ok(d) => HttpResponse::Ok().json(d),
-        Err(e) => {
-            HttpResponse::InternalServerError().json(InternalServerError::new(&e.to_string()))
-        }
-    }
+    let dto = convert_role_to_dto(role, &pool.database, &pool.services.permission_service).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(dto.version))
+        .json(dto))
 }
 
 #[put("/{uuid}")]
 pub async fn update_role(
-    path: web::Path<String>,
-    update: web::Json<UpdateRole>,
+    path: web::Path<EntityPath>,
+    update: Valid<UpdateRole>,
     pool: web::Data<AppDataPool>,
     req: HttpRequest,
-) -> HttpResponse {
-    if !crate::routes::check_user_permissions(&req, &pool, "CAN_UPDATE_ROLE").await {
-        return HttpResponse::Unauthorized().body("");
-    }
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization, role) =
+        find_role_for_update(&req, &pool, &path.into_inner().uuid).await?;
+
+    apply_role_update(
+        &req,
+        &pool,
+        &caller,
+        &organization,
+        role,
+        update.into_inner(),
+    )
+    .await
+}
 
-    if update.name.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Name cannot be empty!"));
-    }
+#[patch("/{uuid}")]
+pub async fn patch_role(
+    path: web::Path<EntityPath>,
+    patch: MergePatch,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization, role) =
+        find_role_for_update(&req, &pool, &path.into_inner().uuid).await?;
+
+    let current = UpdateRole {
+        name: role.name.clone(),
+        description: role.description.clone(),
+        permissions: role.permissions.clone(),
+        approver_permissions: role.approver_permissions.clone(),
+    };
+    let update = patch.apply(&current)?;
+
+    apply_role_update(&req, &pool, &caller, &organization, role, update).await
+}
 
-    let mut res = match pool
+#[post("/{uuid}/permissions/{permission}")]
+pub async fn add_permission(
+    path: web::Path<RolePermissionPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization, _) = find_role_for_update(&req, &pool, &path.uuid).await?;
+
+    match pool
         .services
-        .role_service
-        .find_by_uuid(&pool.database, &path)
-        .await
+        .permission_service
+        .find_by_uuid(&pool.database, &organization, &path.permission)
+        .await?
     {
-        Ok(d) => match d {
-            Some(x) => x,
-            None => return HttpResponse::NotFound().body(""),
-        },
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
+        Some(_) => {}
+        None => return Err(AppError::not_found("Permission not found!")),
     };
 
-    match pool
+    check_grants(
+        &req,
+        &pool,
+        &caller,
+        &organization,
+        &[],
+        std::slice::from_ref(&path.permission),
+    )
+    .await?;
+
+    let role = pool
         .services
         .role_service
-        .find_by_name(&pool.database, &update.name)
-        .await
-    {
-        Ok(d) => {
-            if let Some(x) = d {
-                if x.id != res.id {
-                    return HttpResponse::BadRequest().json(BadRequest::new(&format!(
-                        "Role with name {} already exists!",
-                        update.name
-                    )));
-                }
-            }
-        }
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    };
+        .add_permission(&pool.database, &organization, &path.uuid, &path.permission)
+        .await;
+    versioned_role_response(&pool, role).await
+}
 
-    for permission in &update.permissions {
-        let p = pool
-            .services
-            .permission_service
-            .find_by_uuid(&pool.database, permission)
-            .await;
+#[delete("/{uuid}/permissions/{permission}")]
+pub async fn remove_permission(
+    path: web::Path<RolePermissionPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization, _) = find_role_for_update(&req, &pool, &path.uuid).await?;
 
-        if let Err(e) = p {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    }
+    let role = pool
+        .services
+        .role_service
+        .remove_permission(&pool.database, &organization, &path.uuid, &path.permission)
+        .await;
+    versioned_role_response(&pool, role).await
+}
 
-    res.name = update.name.clone();
-    res.description = update.description.clone();
-    res.permissions = update.permissions.clone();
+#[delete("/{uuid}")]
+pub async fn delete_role(
+    path: web::Path<EntityPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::DeleteRole)
+            .await?;
 
-    let res = match pool
+    let role = match pool
         .services
         .role_service
-        .update(&pool.database, &path, res)
-        .await
+        .find_by_uuid(&pool.database, &organization, &path)
+        .await?
     {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
+        Some(x) => x,
+        None => return Err(AppError::not_found("Role not found!")),
     };
 
-    let res = match res {
-        Some(d) => d,
-        None => return HttpResponse::NoContent().body(""),
-    };
+    if role.managed {
+        return Err(managed_role(&role));
+    }
 
-    match convert_role_to_dto(res, &pool.database, &pool.services.permission_service).await {
-        Ok(d) => HttpResponse::Ok().json(d),
-        Err(e) => {
-            HttpResponse::InternalServerError().json(InternalServerError::new(&e.to_string()))
-        }
+    let version = check_if_match(&req, role.version)?;
+
+    match pool
+        .services
+        .delete_role(
+            &pool.database,
+            &pool.transactions,
+            &organization,
+            &path,
+            version,
+        )
+        .await?
+    {
+        0 if version.is_some() => Err(AppError::precondition_failed(
+            "Role was changed by another request!",
+        )),
+        _ => Ok(HttpResponse::Ok().body("")),
     }
 }
 
-#[delete("/{uuid}")]
-pub async fn delete_role(
-    path: web::Path<String>,
-    pool: web::Data<AppDataPool>,
-    req: HttpRequest,
-) -> HttpResponse {
-    if !crate::routes::check_user_permissions(&req, &pool, "CAN_DELETE_ROLE").await {
-        return HttpResponse::Unauthorized().body("");
+/// Roles that are owned by the seed manifest can only be changed by changing the manifest
+fn managed_role(role: &Role) -> AppError {
+    AppError::conflict(&format!(
+        "Role {} is managed by the seed manifest!",
+        role.name
+    ))
+}
+
+/// Find a role that the caller is about to update, after checking the permissions of the caller
+/// and the preconditions of the request
+async fn find_role_for_update(
+    req: &HttpRequest,
+    pool: &web::Data<AppDataPool>,
+    uuid: &str,
+) -> Result<(User, String, Role), AppError> {
+    let (caller, organization) =
+        crate::routes::check_organization_permissions(req, pool, SystemPermission::UpdateRole)
+            .await?;
+
+    let role = match pool
+        .services
+        .role_service
+        .find_by_uuid(&pool.database, &organization, uuid)
+        .await?
+    {
+        Some(x) => x,
+        None => return Err(AppError::not_found("Role not found!")),
+    };
+
+    if role.managed {
+        return Err(managed_role(&role));
     }
 
-    let mut users_with_role = match pool
+    check_if_match(req, role.version)?;
+
+    Ok((caller, organization, role))
+}
+
+/// Apply a full update to a role, which either comes from a `PUT` or from a patched `PATCH` body
+async fn apply_role_update(
+    req: &HttpRequest,
+    pool: &web::Data<AppDataPool>,
+    caller: &User,
+    organization: &str,
+    mut res: Role,
+    update: UpdateRole,
+) -> Result<HttpResponse, AppError> {
+    let path = res.id.clone();
+
+    if let Some(x) = pool
         .services
-        .user_service
-        .find_by_role_id(&pool.database, &path)
-        .await
+        .role_service
+        .find_by_name(&pool.database, organization, &update.name)
+        .await?
     {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
+        if x.id != res.id {
+            return Err(AppError::conflict(&format!(
+                "Role with name {} already exists!",
+                update.name
+            )));
         }
-    };
+    }
 
-    for user in &mut users_with_role {
-        user.roles.retain(|x| *x != path.to_string());
+    for permission in &update.permissions {
+        match pool
+            .services
+            .permission_service
+            .find_by_uuid(&pool.database, organization, permission)
+            .await?
+        {
+            Some(_) => {}
+            None => {
+                return Err(AppError::validation(&format!(
+                    "Invalid permission {}",
+                    permission
+                )))
+            }
+        }
+    }
 
-        let response = pool
+    for approver in &update.approver_permissions {
+        match pool
             .services
-            .user_service
-            .update(&pool.database, &user.id, user.clone())
-            .await;
-        if let Err(e) = response {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
+            .permission_service
+            .find_by_uuid(&pool.database, organization, approver)
+            .await?
+        {
+            Some(_) => {}
+            None => {
+                return Err(AppError::validation(&format!(
+                    "Invalid approver permission {}",
+                    approver
+                )))
+            }
         }
     }
 
-    if let Err(e) = pool
+    let added_permissions: Vec<String> = update
+        .permissions
+        .iter()
+        .filter(|p| !res.permissions.contains(p))
+        .cloned()
+        .collect();
+    check_grants(req, pool, caller, organization, &[], &added_permissions).await?;
+
+    res.name = update.name.clone();
+    res.description = update.description.clone();
+    res.permissions = update.permissions.clone();
+    res.approver_permissions = update.approver_permissions.clone();
+
+    let res = pool
         .services
         .role_service
-        .delete(&pool.database, &path)
-        .await
-    {
-        return HttpResponse::InternalServerError().json(InternalServerError::new(&e.to_string()));
+        .update(&pool.database, organization, &path, res, None)
+        .await?;
+
+    let res = match res {
+        Some(d) => d,
+        None => {
+            return Err(AppError::precondition_failed(
+                "Role was changed by another request!",
+            ))
+        }
+    };
+
+    let dto = convert_role_to_dto(res, &pool.database, &pool.services.permission_service).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(dto.version))
+        .json(dto))
+}
+
+/// Respond with a role after an atomic update, together with its new version
+async fn versioned_role_response(
+    pool: &AppDataPool,
+    role: Result<Option<Role>, mongodb::error::Error>,
+) -> Result<HttpResponse, AppError> {
+    let role = match role? {
+        Some(d) => d,
+        None => return Err(AppError::not_found("Role not found!")),
     };
 
-    HttpResponse::Ok().body("")
+    let dto = convert_role_to_dto(role, &pool.database, &pool.services.permission_service).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(dto.version))
+        .json(dto))
 }
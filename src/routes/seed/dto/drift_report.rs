@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use crate::services::seed::seeder::DriftStatus;
+
+/// A permission, role or user that differs from the seed manifest
+#[derive(Serialize, Deserialize)]
+pub struct DriftEntry {
+    #[serde(rename(serialize = "type", deserialize = "type"))]
+    pub entity_type: String,
+    pub name: String,
+    pub status: DriftStatus,
+    /// The fields that differ from the manifest
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DriftReport {
+    pub organization: String,
+    #[serde(rename(serialize = "inSync", deserialize = "inSync"))]
+    pub in_sync: bool,
+    pub drift: Vec<DriftEntry>,
+}
@@ -0,0 +1,41 @@
+use actix_web::{get, web, HttpRequest, HttpResponse};
+
+use crate::{
+    configuration::app_data_pool::AppDataPool,
+    errors::app_error::AppError,
+    routes::{
+        check_platform_admin,
+        seed::dto::drift_report::{DriftEntry, DriftReport},
+    },
+    services::seed::seeder,
+};
+
+/// Report how the organization of the seed manifest differs from the manifest
+#[get("/drift")]
+pub async fn get_drift(
+    req: HttpRequest,
+    pool: web::Data<AppDataPool>,
+) -> Result<HttpResponse, AppError> {
+    check_platform_admin(&req, &pool).await?;
+
+    let manifest = match &pool.manifest {
+        Some(d) => d,
+        None => return Err(AppError::not_found("No seed manifest was configured!")),
+    };
+
+    let drift = seeder::drift(&pool.services, &pool.database, manifest).await?;
+
+    Ok(HttpResponse::Ok().json(DriftReport {
+        organization: manifest.organization.clone(),
+        in_sync: drift.is_empty(),
+        drift: drift
+            .into_iter()
+            .map(|d| DriftEntry {
+                entity_type: String::from(d.entity_type),
+                name: d.name,
+                status: d.status,
+                fields: d.fields,
+            })
+            .collect(),
+    }))
+}
@@ -0,0 +1,22 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::routes::validation::{Validate, Validator};
+
+#[derive(Deserialize, Serialize)]
+pub struct CreateServiceAccount {
+    pub username: String,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub attributes: HashMap<String, Value>,
+}
+
+impl Validate for CreateServiceAccount {
+    fn validate(&self, v: &mut Validator) {
+        v.username("username", &self.username)
+            .uuids("roles", &self.roles);
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::HashSet;
+
+use actix_web::{get, post, web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    configuration::app_data_pool::AppDataPool,
+    errors::app_error::AppError,
+    persistence::user::model::user::User,
+    routes::{
+        check_constraints, check_grants, convert_user_to_dto, create_user_with_roles,
+        service_account::dto::create_service_account::CreateServiceAccount, validation::Valid,
+    },
+    services::permission::system_permission::SystemPermission,
+};
+
+/// Create a service account. Service accounts have no password or email address and can only
+/// authenticate with API keys.
+#[post("/")]
+pub async fn create_service_account(
+    create: Valid<CreateServiceAccount>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ManageServiceAccount,
+    )
+    .await?;
+
+    if pool
+        .services
+        .user_service
+        .find_by_username(&pool.database, &organization, &create.username)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::conflict("Username is already taken!"));
+    }
+
+    for role in &create.roles {
+        if pool
+            .services
+            .role_service
+            .find_by_uuid(&pool.database, &organization, role)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::validation(&format!("Invalid role {}", role)));
+        }
+    }
+
+    check_grants(&req, &pool, &caller, &organization, &create.roles, &[]).await?;
+
+    let roles: HashSet<String> = create.roles.iter().cloned().collect();
+    check_constraints(&pool, &organization, &create.username, &roles).await?;
+
+    let account = User {
+        id: Uuid::new_v4().to_string(),
+        username: create.username.clone(),
+        email_address: String::from(""),
+        password: String::from(""),
+        first_name: String::from(""),
+        last_name: String::from(""),
+        enabled: true,
+        platform_admin: false,
+        service_account: true,
+        roles: create.roles.clone(),
+        role_assignments: vec![],
+        created_at: Utc::now().to_string(),
+        last_active: String::from(""),
+        attributes: create.attributes.clone(),
+        organization,
+        version: 0,
+    };
+
+    let account = create_user_with_roles(&pool, account).await?;
+
+    let dto = convert_user_to_dto(
+        account,
+        &pool.database,
+        &pool.services.role_service,
+        &pool.services.permission_service,
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(dto))
+}
+
+#[get("/")]
+pub async fn get_all_service_accounts(
+    req: HttpRequest,
+    pool: web::Data<AppDataPool>,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ManageServiceAccount,
+    )
+    .await?;
+
+    let users = pool
+        .services
+        .user_service
+        .find_all(&pool.database, &organization)
+        .await?;
+
+    let mut accounts = vec![];
+    for user in users.into_iter().filter(|u| u.service_account) {
+        let dto = convert_user_to_dto(
+            user,
+            &pool.database,
+            &pool.services.role_service,
+            &pool.services.permission_service,
+        )
+        .await?;
+        accounts.push(dto);
+    }
+
+    Ok(HttpResponse::Ok().json(accounts))
+}
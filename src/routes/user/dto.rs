@@ -1,4 +1,11 @@
+pub mod create_role_assignment;
 pub mod create_user;
+pub mod role_assignment;
+pub mod role_assignment_path;
+pub mod session;
+pub mod session_path;
 pub mod update_password;
 pub mod update_user;
 pub mod user;
+pub mod user_query;
+pub mod user_search_query;
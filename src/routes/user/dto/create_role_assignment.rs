@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use crate::routes::validation::{Validate, Validator};
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateRoleAssignment {
+    #[serde(rename(serialize = "roleId", deserialize = "roleId"))]
+    pub role_id: String,
+    #[serde(rename(serialize = "validFrom", deserialize = "validFrom"))]
+    pub valid_from: Option<String>,
+    #[serde(rename(serialize = "validUntil", deserialize = "validUntil"))]
+    pub valid_until: String,
+    pub reason: String,
+}
+
+impl Validate for CreateRoleAssignment {
+    fn validate(&self, v: &mut Validator) {
+        v.uuid("roleId", &self.role_id)
+            .required("validUntil", &self.valid_until)
+            .required("reason", &self.reason)
+            .description("reason", &self.reason);
+    }
+}
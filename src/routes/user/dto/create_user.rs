@@ -1,4 +1,9 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::routes::validation::{Validate, Validator, MAX_NAME_LENGTH};
 
 #[derive(Deserialize, Serialize)]
 pub struct CreateUser {
@@ -11,4 +16,17 @@ pub struct CreateUser {
     #[serde(rename(serialize = "lastName", deserialize = "lastName"))]
     pub last_name: String,
     pub roles: Vec<String>,
+    #[serde(default)]
+    pub attributes: HashMap<String, Value>,
+}
+
+impl Validate for CreateUser {
+    fn validate(&self, v: &mut Validator) {
+        v.username("username", &self.username)
+            .email("emailAddress", &self.email_address)
+            .password("password", &self.password)
+            .length("firstName", &self.first_name, 0, MAX_NAME_LENGTH)
+            .length("lastName", &self.last_name, 0, MAX_NAME_LENGTH)
+            .uuids("roles", &self.roles);
+    }
 }
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+use crate::routes::role::dto::role::Role as RoleDto;
+
+#[derive(Serialize, Deserialize)]
+pub struct RoleAssignment {
+    pub role: RoleDto,
+    #[serde(rename(serialize = "validFrom", deserialize = "validFrom"))]
+    pub valid_from: Option<String>,
+    #[serde(rename(serialize = "validUntil", deserialize = "validUntil"))]
+    pub valid_until: String,
+    pub reason: String,
+    #[serde(rename(serialize = "assignedBy", deserialize = "assignedBy"))]
+    pub assigned_by: String,
+    pub active: bool,
+}
@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct RoleAssignmentPath {
+    pub uuid: String,
+    pub role: String,
+}
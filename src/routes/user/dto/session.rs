@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct Session {
+    pub id: String,
+    #[serde(rename(serialize = "userId", deserialize = "userId"))]
+    pub user_id: String,
+    #[serde(rename(serialize = "createdAt", deserialize = "createdAt"))]
+    pub created_at: String,
+    #[serde(rename(serialize = "lastSeen", deserialize = "lastSeen"))]
+    pub last_seen: String,
+    #[serde(rename(serialize = "expiresAt", deserialize = "expiresAt"))]
+    pub expires_at: String,
+    pub ip: Option<String>,
+    #[serde(rename(serialize = "userAgent", deserialize = "userAgent"))]
+    pub user_agent: Option<String>,
+    pub device: String,
+    #[serde(rename(serialize = "actorId", deserialize = "actorId"))]
+    pub actor_id: Option<String>,
+    /// Whether this is the session of the request
+    pub current: bool,
+}
@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct SessionPath {
+    pub uuid: String,
+    pub session: String,
+}
@@ -1,6 +1,14 @@
 use serde::{Deserialize, Serialize};
 
+use crate::routes::validation::{Validate, Validator};
+
 #[derive(Serialize, Deserialize)]
 pub struct UpdatePassword {
     pub password: String,
 }
+
+impl Validate for UpdatePassword {
+    fn validate(&self, v: &mut Validator) {
+        v.password("password", &self.password);
+    }
+}
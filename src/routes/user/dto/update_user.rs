@@ -1,4 +1,9 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::routes::validation::{Validate, Validator, MAX_NAME_LENGTH};
 
 #[derive(Serialize, Deserialize)]
 pub struct UpdateUser {
@@ -10,5 +15,22 @@ pub struct UpdateUser {
     #[serde(rename(serialize = "lastName", deserialize = "lastName"))]
     pub last_name: String,
     pub enabled: bool,
+    #[serde(
+        rename(serialize = "platformAdmin", deserialize = "platformAdmin"),
+        default
+    )]
+    pub platform_admin: Option<bool>,
     pub roles: Vec<String>,
+    #[serde(default)]
+    pub attributes: Option<HashMap<String, Value>>,
+}
+
+impl Validate for UpdateUser {
+    fn validate(&self, v: &mut Validator) {
+        v.username("username", &self.username)
+            .email("emailAddress", &self.email_address)
+            .length("firstName", &self.first_name, 0, MAX_NAME_LENGTH)
+            .length("lastName", &self.last_name, 0, MAX_NAME_LENGTH)
+            .uuids("roles", &self.roles);
+    }
 }
@@ -1,7 +1,12 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::routes::role::dto::role::Role as RoleDto;
 
+use super::role_assignment::RoleAssignment as RoleAssignmentDto;
+
 #[derive(Serialize, Deserialize)]
 pub struct User {
     pub id: String,
@@ -13,9 +18,18 @@ pub struct User {
     #[serde(rename(serialize = "lastName", deserialize = "lastName"))]
     pub last_name: String,
     pub enabled: bool,
+    #[serde(rename(serialize = "platformAdmin", deserialize = "platformAdmin"))]
+    pub platform_admin: bool,
+    #[serde(rename(serialize = "serviceAccount", deserialize = "serviceAccount"))]
+    pub service_account: bool,
     pub roles: Vec<RoleDto>,
+    #[serde(rename(serialize = "roleAssignments", deserialize = "roleAssignments"))]
+    pub role_assignments: Vec<RoleAssignmentDto>,
     #[serde(rename(serialize = "createdAt", deserialize = "createdAt"))]
     pub created_at: String,
     #[serde(rename(serialize = "lastActive", deserialize = "lastActive"))]
     pub last_active: String,
+    pub attributes: HashMap<String, Value>,
+    pub organization: String,
+    pub version: i64,
 }
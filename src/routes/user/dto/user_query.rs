@@ -0,0 +1,21 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct UserQuery {
+    pub offset: Option<u64>,
+    pub limit: Option<i64>,
+    pub sort: Option<String>,
+    /// Prefix of the username
+    pub username: Option<String>,
+    /// Prefix of the email address
+    pub email: Option<String>,
+    pub enabled: Option<bool>,
+    /// Id of a role that the users hold directly
+    pub role: Option<String>,
+    /// RFC 3339 timestamp that the users were created at or after
+    #[serde(rename(deserialize = "createdFrom"))]
+    pub created_from: Option<String>,
+    /// RFC 3339 timestamp that the users were created at or before
+    #[serde(rename(deserialize = "createdTo"))]
+    pub created_to: Option<String>,
+}
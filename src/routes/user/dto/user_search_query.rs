@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct UserSearchQuery {
+    /// Words to search for in the username, email address and names of users
+    pub q: String,
+    pub offset: Option<u64>,
+    pub limit: Option<i64>,
+}
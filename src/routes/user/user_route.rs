@@ -1,342 +1,478 @@
-use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse};
+use std::collections::{HashMap, HashSet};
+
+use actix_web::{delete, get, patch, post, put, web, HttpRequest, HttpResponse};
 use bcrypt::{hash, DEFAULT_COST};
 use chrono::Utc;
-use regex::Regex;
+use jsonwebtoken::{EncodingKey, Header};
 use uuid::Uuid;
 
 use crate::{
     configuration::app_data_pool::AppDataPool,
-    errors::{bad_request::BadRequest, internal_server_error::InternalServerError},
-    persistence::user::model::user::User,
+    errors::app_error::AppError,
+    persistence::{
+        event::model::event::{Event, IMPERSONATION_STARTED},
+        session::model::session::Session,
+        user::model::{
+            role_assignment::{parse_timestamp, RoleAssignment},
+            user::User,
+            user_filter::UserFilter,
+        },
+    },
     routes::{
-        convert_user_to_dto,
+        api_key_restriction,
+        authentication::dto::authentication_response::{Actor, AuthenticationResponse, Claims},
+        authorization::dto::denial_reason::DenialReason,
+        check_constraints, check_grants, check_resource_policies, check_user_constraints,
+        client_ip, convert_session_to_dto, convert_user_to_dto, create_user_with_roles,
+        decode_token, effective_permissions,
+        etag::{check_if_match, etag},
+        merge_patch::MergePatch,
+        pagination::{page_request, page_response},
         user::dto::{
-            create_user::CreateUser, update_password::UpdatePassword, update_user::UpdateUser,
+            create_role_assignment::CreateRoleAssignment, create_user::CreateUser,
+            role_assignment_path::RoleAssignmentPath, session_path::SessionPath,
+            update_password::UpdatePassword, update_user::UpdateUser, user_query::UserQuery,
+            user_search_query::UserSearchQuery,
         },
-        EMAIL_REGEX_PATTERN,
+        user_agent,
+        validation::Valid,
+        EntityPath,
+    },
+    services::{
+        permission::system_permission::SystemPermission, policy::policy_service::user_attributes,
+        session::session_service::device_label,
     },
 };
 
 #[post("/")]
 pub async fn create_user(
-    create_user: web::Json<CreateUser>,
+    create_user: Valid<CreateUser>,
     pool: web::Data<AppDataPool>,
     req: HttpRequest,
-) -> HttpResponse {
-    if !crate::routes::check_user_permissions(&req, &pool, "CAN_CREATE_USER").await {
-        return HttpResponse::Unauthorized().body("");
-    }
-    if create_user.username.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Username cannot be empty!"));
-    }
-
-    if create_user.password.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Password cannot be empty!"));
-    }
-
-    if create_user.email_address.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Email address cannot be empty!"));
-    }
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::CreateUser)
+            .await?;
 
-    let email_regex = Regex::new(EMAIL_REGEX_PATTERN).unwrap();
-    if !email_regex.is_match(&create_user.email_address) {
-        return HttpResponse::BadRequest().json(BadRequest::new("Invalid email address!"));
-    }
-
-    let optional = match pool
+    let optional = pool
         .services
         .user_service
-        .find_by_username(&pool.database, &create_user.username)
-        .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    };
+        .find_by_username(&pool.database, &organization, &create_user.username)
+        .await?;
 
     if let Some(_d) = optional {
-        return HttpResponse::BadRequest().json(BadRequest::new("Username is already taken!"));
+        return Err(AppError::conflict("Username is already taken!"));
     }
 
-    let optional = match pool
+    let optional = pool
         .services
         .user_service
-        .find_by_email_address(&pool.database, &create_user.email_address)
-        .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    };
+        .find_by_email_address(&pool.database, &organization, &create_user.email_address)
+        .await?;
 
     if let Some(_d) = optional {
-        return HttpResponse::BadRequest().json(BadRequest::new("Email address is already taken!"));
+        return Err(AppError::conflict("Email address is already taken!"));
     }
 
     // Check if the roles that are given actually exist
     for role in &create_user.roles {
-        match pool
+        if pool
             .services
             .role_service
-            .find_by_uuid(&pool.database, role)
-            .await
+            .find_by_uuid(&pool.database, &organization, role)
+            .await?
+            .is_none()
         {
-            Ok(d) => {
-                if d.is_none() {
-                    return HttpResponse::BadRequest()
-                        .json(BadRequest::new(&format!("Invalid role {}", role)));
-                }
-            }
-            Err(e) => {
-                return HttpResponse::InternalServerError()
-                    .json(InternalServerError::new(&e.to_string()));
-            }
+            return Err(AppError::validation(&format!("Invalid role {}", role)));
         }
     }
 
+    check_grants(&req, &pool, &caller, &organization, &create_user.roles, &[]).await?;
+
+    let roles: HashSet<String> = create_user.roles.iter().cloned().collect();
+    check_constraints(&pool, &organization, &create_user.username, &roles).await?;
+
     let new_user = User {
         id: Uuid::new_v4().to_string(),
         username: String::from(&create_user.username),
         email_address: String::from(&create_user.email_address),
-        password: hash(&create_user.password, DEFAULT_COST).unwrap(),
+        password: hash(&create_user.password, DEFAULT_COST)?,
         first_name: String::from(&create_user.first_name),
         last_name: String::from(&create_user.last_name),
         enabled: true,
+        platform_admin: false,
+        service_account: false,
         roles: create_user.roles.clone(),
+        role_assignments: vec![],
         created_at: Utc::now().to_string(),
         last_active: String::from(""),
+        attributes: create_user.attributes.clone(),
+        organization,
+        version: 0,
     };
 
-    let res = match pool
-        .services
-        .user_service
-        .create(new_user, &pool.database)
-        .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    };
-
-    let user = match res {
-        Some(d) => d,
-        None => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new("Unable to create user!"))
-        }
-    };
+    let user = create_user_with_roles(&pool, new_user).await?;
 
-    let user = match convert_user_to_dto(
+    let user = convert_user_to_dto(
         user,
         &pool.database,
         &pool.services.role_service,
         &pool.services.permission_service,
     )
-    .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    };
+    .await?;
 
-    HttpResponse::Ok().json(user)
+    Ok(HttpResponse::Ok().json(user))
 }
 
 #[get("/")]
-pub async fn find_all_users(pool: web::Data<AppDataPool>, req: HttpRequest) -> HttpResponse {
-    if !crate::routes::check_user_permissions(&req, &pool, "CAN_READ_USER").await {
-        return HttpResponse::Unauthorized().body("");
-    }
-    let users = match pool.services.user_service.find_all(&pool.database).await {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()))
+pub async fn find_all_users(
+    query: web::Query<UserQuery>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::ReadUser)
+            .await?;
+
+    let page = page_request(
+        query.offset,
+        query.limit,
+        query.sort.as_deref(),
+        &[
+            "username",
+            "emailAddress",
+            "firstName",
+            "lastName",
+            "createdAt",
+            "lastActive",
+        ],
+    )?;
+
+    // Creation timestamps are stored in the format of `to_string`
+    let mut created = vec![];
+    for value in [&query.created_from, &query.created_to] {
+        match value.as_deref().map(parse_timestamp) {
+            Some(Some(d)) => created.push(Some(d.to_string())),
+            Some(None) => {
+                return Err(AppError::validation(
+                    "Creation dates must be RFC 3339 timestamps!",
+                ));
+            }
+            None => created.push(None),
         }
+    }
+
+    let filter = UserFilter {
+        username: query.username.clone(),
+        email_address: query.email.clone(),
+        enabled: query.enabled,
+        role: query.role.clone(),
+        created_to: created.pop().flatten(),
+        created_from: created.pop().flatten(),
     };
 
-    if users.is_empty() {
-        HttpResponse::NotFound().body("");
+    let users = pool
+        .services
+        .user_service
+        .find_page(&pool.database, &organization, &filter, &page)
+        .await?;
+
+    let mut user_dto = vec![];
+    for user in users.items {
+        let user = convert_user_to_dto(
+            user,
+            &pool.database,
+            &pool.services.role_service,
+            &pool.services.permission_service,
+        )
+        .await?;
+        user_dto.push(user);
     }
 
+    Ok(page_response(&req, user_dto, users.total, &page))
+}
+
+#[get("/search")]
+pub async fn search_users(
+    query: web::Query<UserSearchQuery>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::ReadUser)
+            .await?;
+
+    if query.q.trim().is_empty() {
+        return Err(AppError::validation("Search query cannot be empty!"));
+    }
+
+    let page = page_request(query.offset, query.limit, None, &[])?;
+
+    let users = pool
+        .services
+        .user_service
+        .search(&pool.database, &organization, &query.q, &page)
+        .await?;
+
     let mut user_dto = vec![];
-    for user in users {
-        let user = match convert_user_to_dto(
+    for user in users.items {
+        let user = convert_user_to_dto(
             user,
             &pool.database,
             &pool.services.role_service,
             &pool.services.permission_service,
         )
-        .await
-        {
-            Ok(d) => d,
-            Err(e) => {
-                return HttpResponse::InternalServerError()
-                    .json(InternalServerError::new(&e.to_string()));
-            }
-        };
+        .await?;
         user_dto.push(user);
     }
 
-    HttpResponse::Ok().json(user_dto)
+    Ok(page_response(&req, user_dto, users.total, &page))
 }
 
 #[get("/{uuid}")]
 pub async fn find_by_uuid(
     pool: web::Data<AppDataPool>,
-    path: web::Path<String>,
+    path: web::Path<EntityPath>,
     req: HttpRequest,
-) -> HttpResponse {
-    if !crate::routes::check_user_permissions(&req, &pool, "CAN_READ_USER").await {
-        return HttpResponse::Unauthorized().body("");
-    }
-    let user = match pool
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (caller, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::ReadUser)
+            .await?;
+
+    let user = pool
         .services
         .user_service
-        .find_by_uuid(&pool.database, &path)
-        .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()))
-        }
-    };
+        .find_by_uuid(&pool.database, &organization, &path)
+        .await?;
 
     let user = match user {
         Some(d) => d,
         None => {
-            return HttpResponse::NotFound().body("");
+            return Err(AppError::not_found("User not found!"));
         }
     };
 
-    let user = match convert_user_to_dto(
+    check_resource_policies(
+        &req,
+        &pool,
+        &caller,
+        SystemPermission::ReadUser,
+        user_attributes(&user),
+    )
+    .await?;
+
+    let user = convert_user_to_dto(
         user,
         &pool.database,
         &pool.services.role_service,
         &pool.services.permission_service,
     )
-    .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()));
-        }
-    };
+    .await?;
 
-    HttpResponse::Ok().json(user)
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(user.version))
+        .json(user))
 }
 
 #[put("/{uuid}")]
 pub async fn update_by_uuid(
-    update: web::Json<UpdateUser>,
+    update: Valid<UpdateUser>,
     pool: web::Data<AppDataPool>,
-    path: web::Path<String>,
+    path: web::Path<EntityPath>,
     req: HttpRequest,
-) -> HttpResponse {
-    if !crate::routes::check_user_permissions(&req, &pool, "CAN_UPDATE_USER").await {
-        return HttpResponse::Unauthorized().body("");
-    }
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization, old_user) =
+        find_user_for_update(&req, &pool, &path.into_inner().uuid).await?;
+
+    apply_user_update(
+        &req,
+        &pool,
+        &caller,
+        &organization,
+        old_user,
+        update.into_inner(),
+    )
+    .await
+}
 
-    if path.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Invalid UUID"));
-    }
+#[patch("/{uuid}")]
+pub async fn patch_by_uuid(
+    patch: MergePatch,
+    pool: web::Data<AppDataPool>,
+    path: web::Path<EntityPath>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization, old_user) =
+        find_user_for_update(&req, &pool, &path.into_inner().uuid).await?;
+
+    let current = UpdateUser {
+        username: old_user.username.clone(),
+        email_address: old_user.email_address.clone(),
+        first_name: old_user.first_name.clone(),
+        last_name: old_user.last_name.clone(),
+        enabled: old_user.enabled,
+        platform_admin: Some(old_user.platform_admin),
+        roles: old_user.roles.clone(),
+        attributes: Some(old_user.attributes.clone()),
+    };
+    let update = patch.apply(&current)?;
 
-    if update.username.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Username cannot be empty!"));
-    }
+    apply_user_update(&req, &pool, &caller, &organization, old_user, update).await
+}
 
-    if update.email_address.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Email address cannot be empty!"));
-    }
+#[post("/{uuid}/roles/{role}")]
+pub async fn add_role(
+    path: web::Path<RoleAssignmentPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization, old_user) = find_user_for_update(&req, &pool, &path.uuid).await?;
+
+    match pool
+        .services
+        .role_service
+        .find_by_uuid(&pool.database, &organization, &path.role)
+        .await?
+    {
+        Some(_) => {}
+        None => return Err(AppError::not_found("Role not found!")),
+    };
+
+    check_grants(
+        &req,
+        &pool,
+        &caller,
+        &organization,
+        std::slice::from_ref(&path.role),
+        &[],
+    )
+    .await?;
+
+    let mut prospective_user = old_user;
+    prospective_user.roles.push(path.role.clone());
+    check_user_constraints(&pool, &prospective_user, &[]).await?;
+
+    let user = pool
+        .services
+        .user_service
+        .add_role(&pool.database, &organization, &path.uuid, &path.role)
+        .await;
+    versioned_user_response(&pool, user).await
+}
+
+#[delete("/{uuid}/roles/{role}")]
+pub async fn remove_role(
+    path: web::Path<RoleAssignmentPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization, _) = find_user_for_update(&req, &pool, &path.uuid).await?;
+
+    let user = pool
+        .services
+        .user_service
+        .remove_role(&pool.database, &organization, &path.uuid, &path.role)
+        .await;
+    versioned_user_response(&pool, user).await
+}
 
-    let email_regex = Regex::new(EMAIL_REGEX_PATTERN).unwrap();
-    if !email_regex.is_match(&update.email_address) {
-        return HttpResponse::BadRequest().json(BadRequest::new("Invalid email address!"));
+/// Find a user that the caller is about to update, after checking the permissions of the caller
+/// and the preconditions of the request
+async fn find_user_for_update(
+    req: &HttpRequest,
+    pool: &web::Data<AppDataPool>,
+    uuid: &str,
+) -> Result<(User, String, User), AppError> {
+    let (caller, organization) =
+        crate::routes::check_organization_permissions(req, pool, SystemPermission::UpdateUser)
+            .await?;
+
+    if uuid.is_empty() {
+        return Err(AppError::validation("Invalid UUID"));
     }
 
-    let mut old_user = match pool
+    let old_user = match pool
         .services
         .user_service
-        .find_by_uuid(&pool.database, &path)
-        .await
+        .find_by_uuid(&pool.database, &organization, uuid)
+        .await?
     {
-        Ok(d) => match d {
-            Some(d) => d,
-            None => return HttpResponse::NotFound().body(""),
-        },
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()))
-        }
+        Some(d) => d,
+        None => return Err(AppError::not_found("User not found!")),
     };
 
+    check_resource_policies(
+        req,
+        pool,
+        &caller,
+        SystemPermission::UpdateUser,
+        user_attributes(&old_user),
+    )
+    .await?;
+
+    check_if_match(req, old_user.version)?;
+
+    Ok((caller, organization, old_user))
+}
+
+/// Apply a full update to a user, which either comes from a `PUT` or from a patched `PATCH` body
+async fn apply_user_update(
+    req: &HttpRequest,
+    pool: &web::Data<AppDataPool>,
+    caller: &User,
+    organization: &str,
+    mut old_user: User,
+    update: UpdateUser,
+) -> Result<HttpResponse, AppError> {
+    let path = old_user.id.clone();
+
     // Check if the roles that are given actually exist
     for role in &update.roles {
-        match pool
+        if pool
             .services
             .role_service
-            .find_by_uuid(&pool.database, role)
-            .await
+            .find_by_uuid(&pool.database, organization, role)
+            .await?
+            .is_none()
         {
-            Ok(d) => {
-                if d.is_none() {
-                    return HttpResponse::BadRequest()
-                        .json(BadRequest::new(&format!("Invalid role {}", role)));
-                }
-            }
-            Err(e) => {
-                return HttpResponse::InternalServerError()
-                    .json(InternalServerError::new(&e.to_string()));
-            }
+            return Err(AppError::validation(&format!("Invalid role {}", role)));
         }
     }
 
-    let user_by_username = match pool
+    let added_roles: Vec<String> = update
+        .roles
+        .iter()
+        .filter(|r| !old_user.roles.contains(r))
+        .cloned()
+        .collect();
+    check_grants(req, pool, caller, organization, &added_roles, &[]).await?;
+
+    let mut prospective_user = old_user.clone();
+    prospective_user.roles = update.roles.clone();
+    check_user_constraints(pool, &prospective_user, &[]).await?;
+
+    let user_by_username = pool
         .services
         .user_service
-        .find_by_username(&pool.database, &update.username)
-        .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()))
-        }
-    };
+        .find_by_username(&pool.database, organization, &update.username)
+        .await?;
 
     if let Some(x) = user_by_username {
-        if x.id != path.to_string() {
-            return HttpResponse::BadRequest().json(BadRequest::new("Username is already taken!"));
+        if x.id != path {
+            return Err(AppError::conflict("Username is already taken!"));
         }
     }
 
-    let user_by_email = match pool
+    let user_by_email = pool
         .services
         .user_service
-        .find_by_email_address(&pool.database, &update.email_address)
-        .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()))
-        }
-    };
+        .find_by_email_address(&pool.database, organization, &update.email_address)
+        .await?;
 
     if let Some(x) = user_by_email {
-        if x.id != path.to_string() {
-            return HttpResponse::BadRequest()
-                .json(BadRequest::new("Email address is already taken!"));
+        if x.id != path {
+            return Err(AppError::conflict("Email address is already taken!"));
         }
     }
 
@@ -346,133 +482,511 @@ pub async fn update_by_uuid(
     old_user.last_name = update.last_name.clone();
     old_user.enabled = update.enabled;
     old_user.roles = update.roles.clone();
+    if let Some(x) = &update.attributes {
+        old_user.attributes = x.clone();
+    }
+    if let Some(x) = update.platform_admin {
+        if x != old_user.platform_admin && !caller.platform_admin {
+            return Err(AppError::from(DenialReason::PlatformAdminRequired));
+        }
+        old_user.platform_admin = x;
+    }
 
-    let user = match pool
+    let user = pool
         .services
         .user_service
-        .update(&pool.database, &path, old_user)
-        .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()))
-        }
-    };
+        .update(&pool.database, organization, &path, old_user, None)
+        .await?;
 
     let user = match user {
         Some(d) => d,
         None => {
-            return HttpResponse::NoContent().body("");
+            return Err(AppError::precondition_failed(
+                "User was changed by another request!",
+            ));
         }
     };
 
-    match convert_user_to_dto(
+    let dto = convert_user_to_dto(
         user,
         &pool.database,
         &pool.services.role_service,
         &pool.services.permission_service,
     )
-    .await
-    {
-        Ok(d) => HttpResponse::Ok().json(d),
-        Err(e) => {
-            HttpResponse::InternalServerError().json(InternalServerError::new(&e.to_string()))
-        }
-    }
+    .await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(dto.version))
+        .json(dto))
 }
 
 #[put("/{uuid}/password")]
 pub async fn update_password(
-    update: web::Json<UpdatePassword>,
+    update: Valid<UpdatePassword>,
     pool: web::Data<AppDataPool>,
-    path: web::Path<String>,
+    path: web::Path<EntityPath>,
     req: HttpRequest,
-) -> HttpResponse {
-    if !crate::routes::check_user_permissions(&req, &pool, "CAN_UPDATE_USER").await {
-        return HttpResponse::Unauthorized().body("");
-    }
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (caller, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::UpdateUser)
+            .await?;
 
     if path.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Invalid UUID"));
+        return Err(AppError::validation("Invalid UUID"));
     }
 
-    if update.password.is_empty() {
-        return HttpResponse::BadRequest().json(BadRequest::new("Password cannot be empty!"));
-    }
-
-    match pool
+    let old_user = match pool
         .services
         .user_service
-        .find_by_uuid(&pool.database, &path)
-        .await
+        .find_by_uuid(&pool.database, &organization, &path)
+        .await?
     {
-        Ok(d) => {
-            if d.is_none() {
-                return HttpResponse::NotFound().body("");
-            }
+        Some(d) => d,
+        None => return Err(AppError::not_found("User not found!")),
+    };
+
+    check_resource_policies(
+        &req,
+        &pool,
+        &caller,
+        SystemPermission::UpdateUser,
+        user_attributes(&old_user),
+    )
+    .await?;
+
+    let new_password = hash(&update.password, DEFAULT_COST)?;
+
+    let user = pool
+        .services
+        .user_service
+        .update_password(&pool.database, &organization, &path, &new_password)
+        .await?;
+
+    let user = match user {
+        Some(d) => d,
+        None => {
+            return Ok(HttpResponse::NoContent().body(""));
         }
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()))
+    };
+
+    let dto = convert_user_to_dto(
+        user,
+        &pool.database,
+        &pool.services.role_service,
+        &pool.services.permission_service,
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(dto))
+}
+
+#[post("/{uuid}/role-assignments")]
+pub async fn assign_role(
+    create: Valid<CreateRoleAssignment>,
+    pool: web::Data<AppDataPool>,
+    path: web::Path<EntityPath>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (caller, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::UpdateUser)
+            .await?;
+
+    let valid_until = match parse_timestamp(&create.valid_until) {
+        Some(d) => d,
+        None => {
+            return Err(AppError::validation(
+                "Valid until must be an RFC 3339 timestamp!",
+            ))
         }
     };
 
-    let new_password = hash(&update.password, DEFAULT_COST).unwrap();
+    if valid_until <= Utc::now() {
+        return Err(AppError::validation("Valid until must be in the future!"));
+    }
 
-    let user = match pool
+    if let Some(valid_from) = &create.valid_from {
+        match parse_timestamp(valid_from) {
+            Some(d) => {
+                if d >= valid_until {
+                    return Err(AppError::validation(
+                        "Valid from must be before valid until!",
+                    ));
+                }
+            }
+            None => {
+                return Err(AppError::validation(
+                    "Valid from must be an RFC 3339 timestamp!",
+                ))
+            }
+        }
+    }
+
+    if pool
+        .services
+        .role_service
+        .find_by_uuid(&pool.database, &organization, &create.role_id)
+        .await?
+        .is_none()
+    {
+        return Err(AppError::validation(&format!(
+            "Invalid role {}",
+            create.role_id
+        )));
+    }
+
+    let old_user = match pool
+        .services
+        .user_service
+        .find_by_uuid(&pool.database, &organization, &path)
+        .await?
+    {
+        Some(d) => d,
+        None => return Err(AppError::not_found("User not found!")),
+    };
+
+    check_resource_policies(
+        &req,
+        &pool,
+        &caller,
+        SystemPermission::UpdateUser,
+        user_attributes(&old_user),
+    )
+    .await?;
+
+    check_grants(
+        &req,
+        &pool,
+        &caller,
+        &organization,
+        std::slice::from_ref(&create.role_id),
+        &[],
+    )
+    .await?;
+
+    check_user_constraints(&pool, &old_user, std::slice::from_ref(&create.role_id)).await?;
+
+    let assignment = RoleAssignment {
+        role_id: create.role_id.clone(),
+        valid_from: create.valid_from.clone(),
+        valid_until: create.valid_until.clone(),
+        reason: create.reason.clone(),
+        assigned_by: caller.id,
+    };
+
+    let user = pool
+        .services
+        .user_service
+        .add_role_assignment(&pool.database, &organization, &path, assignment, None)
+        .await?;
+
+    user_response(&pool, user).await
+}
+
+#[delete("/{uuid}/role-assignments/{role}")]
+pub async fn revoke_role_assignment(
+    pool: web::Data<AppDataPool>,
+    path: web::Path<RoleAssignmentPath>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (caller, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::UpdateUser)
+            .await?;
+
+    let old_user = match pool
+        .services
+        .user_service
+        .find_by_uuid(&pool.database, &organization, &path.uuid)
+        .await?
+    {
+        Some(d) => d,
+        None => return Err(AppError::not_found("User not found!")),
+    };
+
+    check_resource_policies(
+        &req,
+        &pool,
+        &caller,
+        SystemPermission::UpdateUser,
+        user_attributes(&old_user),
+    )
+    .await?;
+
+    let user = pool
+        .services
+        .user_service
+        .remove_role_assignment(&pool.database, &organization, &path.uuid, &path.role, None)
+        .await?;
+
+    user_response(&pool, user).await
+}
+
+#[get("/{uuid}/sessions")]
+pub async fn get_sessions(
+    pool: web::Data<AppDataPool>,
+    path: web::Path<EntityPath>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::ReadSession)
+            .await?;
+
+    let sessions = pool
+        .services
+        .session_service
+        .find_by_user(&pool.database, &organization, &path)
+        .await?;
+    Ok(HttpResponse::Ok().json(
+        sessions
+            .into_iter()
+            .map(|s| convert_session_to_dto(s, None))
+            .collect::<Vec<_>>(),
+    ))
+}
+
+#[delete("/{uuid}/sessions/{session}")]
+pub async fn revoke_session(
+    pool: web::Data<AppDataPool>,
+    path: web::Path<SessionPath>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (_, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::RevokeSession)
+            .await?;
+
+    match pool
+        .services
+        .session_service
+        .delete(&pool.database, &organization, &path.uuid, &path.session)
+        .await?
+    {
+        0 => Err(AppError::not_found("Session not found!")),
+        _ => Ok(HttpResponse::Ok().body("")),
+    }
+}
+
+/// Issue a short-lived token for another user that carries the caller as its actor. Callers can
+/// only impersonate users whose permissions they hold themselves.
+#[post("/{uuid}/impersonate")]
+pub async fn impersonate(
+    path: web::Path<EntityPath>,
+    pool: web::Data<AppDataPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (caller, organization) = crate::routes::check_organization_permissions(
+        &req,
+        &pool,
+        SystemPermission::ImpersonateUser,
+    )
+    .await?;
+
+    // Impersonation cannot be chained
+    if matches!(decode_token(&req, &pool), Ok(d) if d.act.is_some()) {
+        return Err(AppError::from(DenialReason::ImpersonationDenied));
+    }
+
+    let target = match pool
         .services
         .user_service
-        .update_password(&pool.database, &path, &new_password)
-        .await
+        .find_by_uuid(&pool.database, &organization, &path)
+        .await?
     {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .json(InternalServerError::new(&e.to_string()))
+        Some(d) => d,
+        None => return Err(AppError::not_found("User not found!")),
+    };
+
+    if target.id == caller.id {
+        return Err(AppError::validation("You cannot impersonate yourself!"));
+    }
+
+    if !target.enabled {
+        return Err(AppError::validation(
+            "Disabled users cannot be impersonated!",
+        ));
+    }
+
+    if target.platform_admin && !caller.platform_admin {
+        return Err(AppError::from(DenialReason::ImpersonationDenied));
+    }
+
+    let mut held = vec![];
+    for user in [&caller, &target] {
+        held.push(
+            effective_permissions(
+                &pool.database,
+                user,
+                &pool.services.role_service,
+                &pool.services.permission_service,
+                &pool.services.group_service,
+            )
+            .await?,
+        );
+    }
+
+    // A restricted API key only lends the permissions that it is restricted to
+    if let Some(allowed) = api_key_restriction(&req) {
+        held[0].retain(|p| allowed.contains(p));
+    }
+
+    if !held[1].is_subset(&held[0]) {
+        return Err(AppError::from(DenialReason::ImpersonationDenied));
+    }
+
+    let iat = Utc::now();
+    let exp = iat + chrono::Duration::milliseconds(pool.jwt.impersonation_expires);
+
+    let user_agent = user_agent(&req);
+    let session = Session {
+        id: Uuid::new_v4().to_string(),
+        organization: organization.clone(),
+        user_id: target.id.clone(),
+        created_at: iat.to_rfc3339(),
+        last_seen: iat.to_rfc3339(),
+        expires_at: exp.to_rfc3339(),
+        ip: client_ip(&req),
+        device: device_label(user_agent.as_deref()),
+        user_agent,
+        actor_id: Some(caller.id.clone()),
+    };
+
+    let session = match pool
+        .services
+        .session_service
+        .create(session, &pool.database)
+        .await?
+    {
+        Some(d) => d,
+        None => {
+            return Err(AppError::storage("Unable to create session!"));
         }
     };
 
+    let claims = Claims::new(target.id.clone(), organization.clone(), iat, exp)
+        .with_session(session.id)
+        .with_actor(Actor {
+            sub: caller.id.clone(),
+            org: caller.organization.clone(),
+        });
+
+    let token = jsonwebtoken::encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(pool.jwt.secret.as_ref()),
+    )?;
+
+    let mut data = HashMap::new();
+    data.insert(String::from("actorId"), caller.id.clone());
+    data.insert(String::from("actorUsername"), caller.username.clone());
+    data.insert(String::from("expiresAt"), exp.to_rfc3339());
+
+    let event = Event {
+        id: Uuid::new_v4().to_string(),
+        event_type: String::from(IMPERSONATION_STARTED),
+        organization,
+        user_id: Some(target.id.clone()),
+        message: format!(
+            "{} started impersonating {}",
+            caller.username, target.username
+        ),
+        data,
+        timestamp: iat.to_string(),
+    };
+
+    pool.services
+        .event_service
+        .create(event, &pool.database)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(AuthenticationResponse::new(&token)))
+}
+
+/// Respond with a user after an atomic update, together with its new version
+async fn versioned_user_response(
+    pool: &AppDataPool,
+    user: Result<Option<User>, mongodb::error::Error>,
+) -> Result<HttpResponse, AppError> {
+    let user = match user? {
+        Some(d) => d,
+        None => return Err(AppError::not_found("User not found!")),
+    };
+
+    let dto = convert_user_to_dto(
+        user,
+        &pool.database,
+        &pool.services.role_service,
+        &pool.services.permission_service,
+    )
+    .await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(dto.version))
+        .json(dto))
+}
+
+async fn user_response(pool: &AppDataPool, user: Option<User>) -> Result<HttpResponse, AppError> {
     let user = match user {
         Some(d) => d,
         None => {
-            return HttpResponse::NoContent().body("");
+            return Ok(HttpResponse::NoContent().body(""));
         }
     };
 
-    match convert_user_to_dto(
+    let dto = convert_user_to_dto(
         user,
         &pool.database,
         &pool.services.role_service,
         &pool.services.permission_service,
     )
-    .await
-    {
-        Ok(d) => HttpResponse::Ok().json(d),
-        Err(e) => {
-            HttpResponse::InternalServerError().json(InternalServerError::new(&e.to_string()))
-        }
-    }
+    .await?;
+    Ok(HttpResponse::Ok().json(dto))
 }
 
 #[delete("/{uuid}")]
 pub async fn delete_by_uuid(
     pool: web::Data<AppDataPool>,
-    path: web::Path<String>,
+    path: web::Path<EntityPath>,
     req: HttpRequest,
-) -> HttpResponse {
-    if !crate::routes::check_user_permissions(&req, &pool, "CAN_DELETE_USER").await {
-        return HttpResponse::Unauthorized().body("");
-    }
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner().uuid;
+    let (caller, organization) =
+        crate::routes::check_organization_permissions(&req, &pool, SystemPermission::DeleteUser)
+            .await?;
 
-    if let Err(e) = pool
+    let user = match pool
         .services
         .user_service
-        .delete(&pool.database, &path)
-        .await
+        .find_by_uuid(&pool.database, &organization, &path)
+        .await?
     {
-        return HttpResponse::InternalServerError().json(InternalServerError::new(&e.to_string()));
+        Some(x) => x,
+        None => return Err(AppError::not_found("User not found!")),
     };
 
-    HttpResponse::Ok().body("")
+    check_resource_policies(
+        &req,
+        &pool,
+        &caller,
+        SystemPermission::DeleteUser,
+        user_attributes(&user),
+    )
+    .await?;
+
+    let version = check_if_match(&req, user.version)?;
+
+    match pool
+        .services
+        .delete_user(
+            &pool.database,
+            &pool.transactions,
+            &organization,
+            &path,
+            version,
+        )
+        .await?
+    {
+        0 if version.is_some() => Err(AppError::precondition_failed(
+            "User was changed by another request!",
+        )),
+        _ => Ok(HttpResponse::Ok().body("")),
+    }
 }
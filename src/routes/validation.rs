@@ -0,0 +1,317 @@
+use std::ops::Deref;
+
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+use futures::future::LocalBoxFuture;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{de::DeserializeOwned, Serialize};
+use uuid::Uuid;
+
+use crate::errors::app_error::AppError;
+
+use super::{EMAIL_REGEX_PATTERN, ORGANIZATION_ID_REGEX_PATTERN};
+
+pub const MAX_NAME_LENGTH: usize = 128;
+pub const MAX_DESCRIPTION_LENGTH: usize = 1024;
+pub const MIN_USERNAME_LENGTH: usize = 3;
+pub const MAX_USERNAME_LENGTH: usize = 64;
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+pub const MAX_PASSWORD_LENGTH: usize = 128;
+
+pub static EMAIL_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(EMAIL_REGEX_PATTERN).unwrap());
+pub static ORGANIZATION_ID_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(ORGANIZATION_ID_REGEX_PATTERN).unwrap());
+static USERNAME_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Za-z0-9._@\-]+$").unwrap());
+
+/// A field of a request body that failed validation
+#[derive(Serialize, Debug)]
+pub struct FieldError {
+    pub field: String,
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// Declares the validation rules of a request body
+pub trait Validate {
+    fn validate(&self, v: &mut Validator);
+}
+
+/// Collects the errors of every field instead of stopping at the first one
+#[derive(Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    /// Record an error for a field when a custom rule does not hold
+    pub fn check(
+        &mut self,
+        field: &str,
+        valid: bool,
+        code: &'static str,
+        message: &str,
+    ) -> &mut Self {
+        if !valid {
+            self.errors.push(FieldError {
+                field: String::from(field),
+                code,
+                message: String::from(message),
+            });
+        }
+        self
+    }
+
+    pub fn required(&mut self, field: &str, value: &str) -> &mut Self {
+        self.check(
+            field,
+            !value.trim().is_empty(),
+            "REQUIRED",
+            &format!("{} cannot be empty!", field),
+        )
+    }
+
+    pub fn length(&mut self, field: &str, value: &str, min: usize, max: usize) -> &mut Self {
+        let length = value.chars().count();
+        self.check(
+            field,
+            (min..=max).contains(&length),
+            "LENGTH",
+            &format!(
+                "{} must be between {} and {} characters long!",
+                field, min, max
+            ),
+        )
+    }
+
+    /// A mandatory name of at most `MAX_NAME_LENGTH` characters
+    pub fn name(&mut self, field: &str, value: &str) -> &mut Self {
+        if value.trim().is_empty() {
+            return self.required(field, value);
+        }
+        self.length(field, value, 1, MAX_NAME_LENGTH)
+    }
+
+    pub fn description(&mut self, field: &str, value: &str) -> &mut Self {
+        self.length(field, value, 0, MAX_DESCRIPTION_LENGTH)
+    }
+
+    pub fn email(&mut self, field: &str, value: &str) -> &mut Self {
+        if value.is_empty() {
+            return self.required(field, value);
+        }
+        self.check(
+            field,
+            EMAIL_REGEX.is_match(value),
+            "EMAIL",
+            "Invalid email address!",
+        )
+    }
+
+    pub fn username(&mut self, field: &str, value: &str) -> &mut Self {
+        if value.is_empty() {
+            return self.required(field, value);
+        }
+        self.length(field, value, MIN_USERNAME_LENGTH, MAX_USERNAME_LENGTH)
+            .check(
+                field,
+                USERNAME_REGEX.is_match(value),
+                "USERNAME",
+                "Username can only contain letters, digits and the characters . _ @ -",
+            )
+    }
+
+    pub fn password(&mut self, field: &str, value: &str) -> &mut Self {
+        if value.is_empty() {
+            return self.required(field, value);
+        }
+        self.length(field, value, MIN_PASSWORD_LENGTH, MAX_PASSWORD_LENGTH)
+    }
+
+    pub fn uuid(&mut self, field: &str, value: &str) -> &mut Self {
+        self.check(
+            field,
+            Uuid::parse_str(value).is_ok(),
+            "UUID",
+            &format!("{} must be a UUID!", field),
+        )
+    }
+
+    /// Every value of a list of references must be a UUID
+    pub fn uuids(&mut self, field: &str, values: &[String]) -> &mut Self {
+        for (i, value) in values.iter().enumerate() {
+            self.uuid(&format!("{}[{}]", field, i), value);
+        }
+        self
+    }
+
+    pub fn into_result(self) -> Result<(), AppError> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::InvalidFields(self.errors))
+        }
+    }
+}
+
+/// A JSON request body that is validated before the handler is called
+pub struct Valid<T>(pub T);
+
+impl<T> Valid<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Valid<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned + Validate + 'static> FromRequest for Valid<T> {
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let json = web::Json::<T>::from_request(req, payload);
+        Box::pin(async move {
+            let body = match json.await {
+                Ok(d) => d.into_inner(),
+                Err(e) => return Err(AppError::validation(&e.to_string()).into()),
+            };
+
+            let mut validator = Validator::default();
+            body.validate(&mut validator);
+            validator.into_result()?;
+
+            Ok(Valid(body))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(validate: impl FnOnce(&mut Validator)) -> Vec<(String, &'static str)> {
+        let mut validator = Validator::default();
+        validate(&mut validator);
+        validator
+            .errors
+            .into_iter()
+            .map(|e| (e.field, e.code))
+            .collect()
+    }
+
+    #[test]
+    fn valid_fields_produce_no_errors() {
+        let mut validator = Validator::default();
+        validator
+            .name("name", "Readers")
+            .description("description", "")
+            .email("emailAddress", "jane@example.com")
+            .username("username", "jane.doe@acme")
+            .password("password", "correct horse")
+            .uuid("id", "0b6a1b5e-7f3a-4c4e-9a53-1f0d9c2b4e11");
+
+        assert!(validator.into_result().is_ok());
+    }
+
+    #[test]
+    fn errors_of_every_field_are_collected() {
+        let mut validator = Validator::default();
+        validator
+            .name("name", " ")
+            .email("emailAddress", "not an email")
+            .password("password", "short");
+
+        match validator.into_result() {
+            Err(AppError::InvalidFields(d)) => {
+                let fields: Vec<&str> = d.iter().map(|e| e.field.as_str()).collect();
+                assert_eq!(fields, vec!["name", "emailAddress", "password"]);
+            }
+            _ => panic!("expected invalid fields"),
+        }
+    }
+
+    #[test]
+    fn empty_values_are_required_instead_of_malformed() {
+        assert_eq!(
+            codes(|v| {
+                v.name("name", "")
+                    .email("emailAddress", "")
+                    .username("username", "");
+            }),
+            vec![
+                (String::from("name"), "REQUIRED"),
+                (String::from("emailAddress"), "REQUIRED"),
+                (String::from("username"), "REQUIRED"),
+            ]
+        );
+    }
+
+    #[test]
+    fn lengths_count_characters_and_include_the_bounds() {
+        assert!(codes(|v| {
+            v.length("name", "äöü", 3, 3);
+        })
+        .is_empty());
+        assert!(codes(|v| {
+            v.name("name", &"a".repeat(MAX_NAME_LENGTH));
+        })
+        .is_empty());
+        assert_eq!(
+            codes(|v| {
+                v.name("name", &"a".repeat(MAX_NAME_LENGTH + 1));
+            }),
+            vec![(String::from("name"), "LENGTH")]
+        );
+        assert_eq!(
+            codes(|v| {
+                v.password("password", &"a".repeat(MIN_PASSWORD_LENGTH - 1));
+            }),
+            vec![(String::from("password"), "LENGTH")]
+        );
+    }
+
+    #[test]
+    fn usernames_report_every_broken_rule() {
+        assert_eq!(
+            codes(|v| {
+                v.username("username", "a!");
+            }),
+            vec![
+                (String::from("username"), "LENGTH"),
+                (String::from("username"), "USERNAME"),
+            ]
+        );
+    }
+
+    #[test]
+    fn uuids_report_the_index_of_invalid_references() {
+        let values = vec![
+            String::from("0b6a1b5e-7f3a-4c4e-9a53-1f0d9c2b4e11"),
+            String::from("not-a-uuid"),
+        ];
+
+        assert_eq!(
+            codes(|v| {
+                v.uuids("permissions", &values);
+            }),
+            vec![(String::from("permissions[1]"), "UUID")]
+        );
+    }
+
+    #[test]
+    fn custom_checks_use_their_own_code_and_message() {
+        let mut validator = Validator::default();
+        validator.check("duration", false, "RANGE", "Duration is out of range!");
+        validator.check("comment", true, "RANGE", "Never recorded");
+
+        assert_eq!(validator.errors.len(), 1);
+        assert_eq!(validator.errors[0].code, "RANGE");
+        assert_eq!(validator.errors[0].message, "Duration is out of range!");
+    }
+}
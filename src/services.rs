@@ -1,17 +1,72 @@
-use crate::{configuration::config::Config, persistence::Repositories};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use chrono::{Duration, Utc};
+use mongodb::{error::Error, Database};
+use uuid::Uuid;
+
+use crate::{
+    configuration::config::Config,
+    persistence::{
+        access_request::model::access_request::AccessRequestState,
+        event::model::event::{Event, ROLE_ASSIGNMENT_EXPIRED},
+        organization::model::organization::{Organization, DEFAULT_ORGANIZATION},
+        permission::model::permission::Permission,
+        transaction::Transactions,
+        user::model::role_assignment::parse_timestamp,
+        Repositories,
+    },
+};
 
 use self::{
-    permission::permission_service::PermissionService, role::role_service::RoleService,
+    access_request::access_request_service::{AccessRequestService, PENDING_REQUEST_TTL_HOURS},
+    access_review::access_review_service::AccessReviewService,
+    api_key::api_key_service::ApiKeyService,
+    constraint::constraint_service::ConstraintService,
+    event::event_service::EventService,
+    group::group_service::{GroupService, MEMBERS_FIELD, ROLES_FIELD},
+    login::{geo_ip::GeoIp, login_service::LoginService, notifier::LogNotifier},
+    organization::organization_service::OrganizationService,
+    permission::{permission_service::PermissionService, system_permission::SystemPermission},
+    policy::policy_service::PolicyService,
+    relationship::relationship_service::RelationshipService,
+    role::role_service::RoleService,
+    session::session_service::SessionService,
     user::user_service::UserService,
 };
 
+pub mod access_request;
+pub mod access_review;
+pub mod api_key;
+pub mod constraint;
+pub mod event;
+pub mod group;
+pub mod login;
+pub mod organization;
 pub mod permission;
+pub mod policy;
+pub mod relationship;
 pub mod role;
+pub mod seed;
+pub mod session;
 pub mod user;
 
 #[derive(Clone)]
 pub struct Services {
     pub permission_service: PermissionService,
+    pub policy_service: PolicyService,
+    pub relationship_service: RelationshipService,
+    pub organization_service: OrganizationService,
+    pub group_service: GroupService,
+    pub event_service: EventService,
+    pub access_request_service: AccessRequestService,
+    pub constraint_service: ConstraintService,
+    pub access_review_service: AccessReviewService,
+    pub api_key_service: ApiKeyService,
+    pub session_service: SessionService,
+    pub login_service: LoginService,
     pub role_service: RoleService,
     pub user_service: UserService,
 }
@@ -20,10 +75,364 @@ impl Services {
     pub fn new(config: &Config) -> Services {
         let repositories = Repositories::new(config);
 
+        let geo_ip = match &config.login_alerts.geoip_database {
+            Some(path) => match GeoIp::load(path) {
+                Ok(d) => Some(d),
+                Err(e) => {
+                    log::error!("Unable to load the GeoIP database {}: {}", path, e);
+                    None
+                }
+            },
+            None => None,
+        };
+
         Services {
             user_service: UserService::new(repositories.user_repository),
             permission_service: PermissionService::new(repositories.permission_repository),
             role_service: RoleService::new(repositories.role_repository),
+            policy_service: PolicyService::new(repositories.policy_repository),
+            relationship_service: RelationshipService::new(
+                repositories.relationship_repository,
+                repositories.namespace_repository,
+            ),
+            organization_service: OrganizationService::new(repositories.organization_repository),
+            group_service: GroupService::new(repositories.group_repository),
+            event_service: EventService::new(repositories.event_repository),
+            access_request_service: AccessRequestService::new(
+                repositories.access_request_repository,
+            ),
+            constraint_service: ConstraintService::new(repositories.constraint_repository),
+            access_review_service: AccessReviewService::new(repositories.access_review_repository),
+            api_key_service: ApiKeyService::new(repositories.api_key_repository),
+            session_service: SessionService::new(repositories.session_repository),
+            login_service: LoginService::new(
+                repositories.login_repository,
+                geo_ip,
+                config.login_alerts.travel_window,
+                Arc::new(LogNotifier),
+            ),
+        }
+    }
+
+    /// Make sure that the default organization exists and that every user, role and permission
+    /// that was created before organizations existed belongs to it
+    pub async fn initialize_organizations(&self, db: &Database) -> Result<(), Error> {
+        let default = self
+            .organization_service
+            .find_by_id(db, DEFAULT_ORGANIZATION)
+            .await?;
+
+        if default.is_none() {
+            let organization = Organization {
+                id: String::from(DEFAULT_ORGANIZATION),
+                name: String::from("Default"),
+                description: String::from("The default organization"),
+                enabled: true,
+                created_at: Utc::now().to_string(),
+            };
+            self.organization_service.create(organization, db).await?;
         }
+
+        self.user_service
+            .assign_default_organization(db, DEFAULT_ORGANIZATION)
+            .await?;
+        self.role_service
+            .assign_default_organization(db, DEFAULT_ORGANIZATION)
+            .await?;
+        self.permission_service
+            .assign_default_organization(db, DEFAULT_ORGANIZATION)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Make sure that every organization holds every system permission
+    pub async fn initialize_system_permissions(&self, db: &Database) -> Result<(), Error> {
+        for organization in self.organization_service.find_all(db).await? {
+            self.ensure_system_permissions(db, &organization.id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Create the system permissions that an organization does not hold yet. Existing ones are
+    /// left alone, so that their descriptions can be changed.
+    pub async fn ensure_system_permissions(
+        &self,
+        db: &Database,
+        organization: &str,
+    ) -> Result<(), Error> {
+        let existing: HashSet<String> = self
+            .permission_service
+            .find_all(db, organization)
+            .await?
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+
+        for permission in SystemPermission::ALL {
+            if existing.contains(permission.name()) {
+                continue;
+            }
+
+            let new_permission = Permission {
+                id: Uuid::new_v4().to_string(),
+                name: String::from(permission.name()),
+                description: String::from(permission.description()),
+                organization: String::from(organization),
+                version: 0,
+                managed: false,
+            };
+            self.permission_service
+                .create(new_permission, db, None)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Delete an organization together with its system permissions in a single transaction.
+    /// Returns the number of deleted organizations.
+    pub async fn delete_organization(
+        &self,
+        db: &Database,
+        transactions: &Transactions,
+        organization: &str,
+    ) -> Result<u64, Error> {
+        let mut transaction = transactions.begin().await?;
+
+        let deleted = self
+            .organization_service
+            .delete(db, organization, transaction.session())
+            .await?;
+        if deleted == 0 {
+            transaction.abort().await?;
+            return Ok(0);
+        }
+
+        let names: Vec<&str> = SystemPermission::ALL.iter().map(|p| p.name()).collect();
+        self.permission_service
+            .delete_by_names(db, organization, &names, transaction.session())
+            .await?;
+
+        transaction.commit().await?;
+        Ok(deleted)
+    }
+
+    /// Delete a permission and remove it from every role in a single transaction. When a version
+    /// is given, nothing changes unless the permission is still at that version. Returns the
+    /// number of deleted permissions.
+    pub async fn delete_permission(
+        &self,
+        db: &Database,
+        transactions: &Transactions,
+        organization: &str,
+        uuid: &str,
+        version: Option<i64>,
+    ) -> Result<u64, Error> {
+        let mut transaction = transactions.begin().await?;
+
+        // The versioned delete goes first, so that a mismatch changes nothing even without a
+        // transaction
+        let deleted = self
+            .permission_service
+            .delete(db, organization, uuid, version, transaction.session())
+            .await?;
+        if deleted == 0 {
+            transaction.abort().await?;
+            return Ok(0);
+        }
+
+        self.role_service
+            .pull_permission(db, organization, uuid, transaction.session())
+            .await?;
+
+        transaction.commit().await?;
+        Ok(deleted)
+    }
+
+    /// Delete a role together with every membership, assignment and constraint that refers to
+    /// it in a single transaction. When a version is given, nothing changes unless the role is
+    /// still at that version. Returns the number of deleted roles.
+    pub async fn delete_role(
+        &self,
+        db: &Database,
+        transactions: &Transactions,
+        organization: &str,
+        uuid: &str,
+        version: Option<i64>,
+    ) -> Result<u64, Error> {
+        let mut transaction = transactions.begin().await?;
+
+        let deleted = self
+            .role_service
+            .delete(db, organization, uuid, version, transaction.session())
+            .await?;
+        if deleted == 0 {
+            transaction.abort().await?;
+            return Ok(0);
+        }
+
+        self.user_service
+            .pull_role(db, organization, uuid, transaction.session())
+            .await?;
+        self.group_service
+            .pull_from_all(db, organization, ROLES_FIELD, uuid, transaction.session())
+            .await?;
+        self.user_service
+            .remove_role_assignments_by_role(db, organization, uuid, transaction.session())
+            .await?;
+        self.constraint_service
+            .remove_role(db, organization, uuid, transaction.session())
+            .await?;
+
+        transaction.commit().await?;
+        Ok(deleted)
+    }
+
+    /// Delete a user together with its group memberships, sessions and API keys in a single
+    /// transaction. When a version is given, nothing changes unless the user is still at that
+    /// version. Returns the number of deleted users.
+    pub async fn delete_user(
+        &self,
+        db: &Database,
+        transactions: &Transactions,
+        organization: &str,
+        uuid: &str,
+        version: Option<i64>,
+    ) -> Result<u64, Error> {
+        let mut transaction = transactions.begin().await?;
+
+        let deleted = self
+            .user_service
+            .delete(db, organization, uuid, version, transaction.session())
+            .await?;
+        if deleted == 0 {
+            transaction.abort().await?;
+            return Ok(0);
+        }
+
+        self.group_service
+            .pull_from_all(db, organization, MEMBERS_FIELD, uuid, transaction.session())
+            .await?;
+        self.session_service
+            .delete_by_user(db, organization, uuid, transaction.session())
+            .await?;
+        self.api_key_service
+            .delete_by_owner(db, organization, uuid, transaction.session())
+            .await?;
+
+        transaction.commit().await?;
+        Ok(deleted)
+    }
+
+    /// Run every periodic clean-up task
+    pub async fn sweep(&self, db: &Database) -> Result<(), Error> {
+        self.sweep_expired_role_assignments(db).await?;
+        self.sweep_access_requests(db).await?;
+        self.session_service
+            .delete_expired(db, &Utc::now().to_rfc3339())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Remove every time-bound role assignment whose validity window has ended and record an
+    /// event for each of them. Returns the number of assignments that were removed.
+    pub async fn sweep_expired_role_assignments(&self, db: &Database) -> Result<u64, Error> {
+        let now = Utc::now();
+        let users = self.user_service.find_with_role_assignments(db).await?;
+
+        let mut removed = 0;
+        for user in users {
+            for assignment in user.role_assignments.iter().filter(|a| a.has_lapsed(now)) {
+                // The assignment may have been renewed since the user was read
+                let modified = self
+                    .user_service
+                    .remove_lapsed_role_assignment(
+                        db,
+                        &user.organization,
+                        &user.id,
+                        &assignment.role_id,
+                        &assignment.valid_until,
+                    )
+                    .await?;
+                if modified == 0 {
+                    continue;
+                }
+
+                let mut data = HashMap::new();
+                data.insert(String::from("roleId"), assignment.role_id.clone());
+                data.insert(String::from("validUntil"), assignment.valid_until.clone());
+                data.insert(String::from("reason"), assignment.reason.clone());
+                data.insert(String::from("assignedBy"), assignment.assigned_by.clone());
+
+                let event = Event {
+                    id: Uuid::new_v4().to_string(),
+                    event_type: String::from(ROLE_ASSIGNMENT_EXPIRED),
+                    organization: user.organization.clone(),
+                    user_id: Some(user.id.clone()),
+                    message: format!(
+                        "Role assignment of {} to {} has expired",
+                        assignment.role_id, user.username
+                    ),
+                    data,
+                    timestamp: now.to_string(),
+                };
+                self.event_service.create(event, db).await?;
+
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Expire the approved access requests whose role assignment has ended and the pending
+    /// requests that were not decided upon in time. Returns the number of expired requests.
+    pub async fn sweep_access_requests(&self, db: &Database) -> Result<u64, Error> {
+        let now = Utc::now();
+        let mut expired = 0;
+
+        let approved = self
+            .access_request_service
+            .find_all_by_state(db, AccessRequestState::Approved)
+            .await?;
+        let pending = self
+            .access_request_service
+            .find_all_by_state(db, AccessRequestState::Pending)
+            .await?;
+
+        for request in approved.into_iter().chain(pending) {
+            let deadline = match request.state {
+                AccessRequestState::Approved => {
+                    request.valid_until.as_deref().and_then(parse_timestamp)
+                }
+                _ => parse_timestamp(&request.created_at)
+                    .map(|d| d + Duration::hours(PENDING_REQUEST_TTL_HOURS)),
+            };
+
+            if matches!(deadline, Some(d) if d > now) {
+                continue;
+            }
+
+            let from = request.state;
+            let mut request = request;
+            request.state = AccessRequestState::Expired;
+            if from == AccessRequestState::Pending {
+                request.decided_at = Some(now.to_rfc3339());
+            }
+
+            if self
+                .access_request_service
+                .transition(db, from, request, None)
+                .await?
+                .is_some()
+            {
+                expired += 1;
+            }
+        }
+
+        Ok(expired)
     }
 }
@@ -0,0 +1,90 @@
+use mongodb::{error::Error, ClientSession, Database};
+
+use crate::persistence::access_request::{
+    access_request_repository::AccessRequestRepository,
+    model::access_request::{AccessRequest, AccessRequestState},
+};
+
+/// The longest duration, in seconds, that a role can be requested for
+pub const MAX_ACCESS_DURATION: i64 = 30 * 24 * 60 * 60;
+
+/// The number of hours after which a request that was not decided upon expires
+pub const PENDING_REQUEST_TTL_HOURS: i64 = 72;
+
+#[derive(Clone)]
+pub struct AccessRequestService {
+    pub repository: AccessRequestRepository,
+}
+
+impl AccessRequestService {
+    pub fn new(repository: AccessRequestRepository) -> Self {
+        Self { repository }
+    }
+
+    pub async fn create(
+        &self,
+        request: AccessRequest,
+        db: &Database,
+    ) -> Result<Option<AccessRequest>, Error> {
+        self.repository.create(request, db).await
+    }
+
+    pub async fn find_by_uuid(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<Option<AccessRequest>, Error> {
+        self.repository.find_by_uuid(db, organization, uuid).await
+    }
+
+    pub async fn find_by_requester(
+        &self,
+        db: &Database,
+        organization: &str,
+        requester_id: &str,
+    ) -> Result<Vec<AccessRequest>, Error> {
+        self.repository
+            .find_by_requester(db, organization, requester_id)
+            .await
+    }
+
+    pub async fn find_by_state(
+        &self,
+        db: &Database,
+        organization: &str,
+        state: AccessRequestState,
+    ) -> Result<Vec<AccessRequest>, Error> {
+        self.repository.find_by_state(db, organization, state).await
+    }
+
+    pub async fn find_all_by_state(
+        &self,
+        db: &Database,
+        state: AccessRequestState,
+    ) -> Result<Vec<AccessRequest>, Error> {
+        self.repository.find_all_by_state(db, state).await
+    }
+
+    pub async fn find_pending(
+        &self,
+        db: &Database,
+        organization: &str,
+        requester_id: &str,
+        role_id: &str,
+    ) -> Result<Option<AccessRequest>, Error> {
+        self.repository
+            .find_pending(db, organization, requester_id, role_id)
+            .await
+    }
+
+    pub async fn transition(
+        &self,
+        db: &Database,
+        from: AccessRequestState,
+        request: AccessRequest,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Option<AccessRequest>, Error> {
+        self.repository.transition(db, from, request, session).await
+    }
+}
@@ -0,0 +1 @@
+pub mod access_review_service;
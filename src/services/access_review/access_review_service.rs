@@ -0,0 +1,147 @@
+use mongodb::{error::Error, Database};
+use serde::Serialize;
+
+use crate::persistence::access_review::{
+    access_review_repository::AccessReviewRepository,
+    model::access_review::{AccessReview, ReviewDecision, ReviewItem, ReviewState},
+};
+
+#[derive(Clone)]
+pub struct AccessReviewService {
+    pub repository: AccessReviewRepository,
+}
+
+impl AccessReviewService {
+    pub fn new(repository: AccessReviewRepository) -> Self {
+        Self { repository }
+    }
+
+    pub async fn create(
+        &self,
+        review: AccessReview,
+        db: &Database,
+    ) -> Result<Option<AccessReview>, Error> {
+        self.repository.create(review, db).await
+    }
+
+    pub async fn find_all(
+        &self,
+        db: &Database,
+        organization: &str,
+    ) -> Result<Vec<AccessReview>, Error> {
+        self.repository.find_all(db, organization).await
+    }
+
+    pub async fn find_by_uuid(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<Option<AccessReview>, Error> {
+        self.repository.find_by_uuid(db, organization, uuid).await
+    }
+
+    pub async fn decide_item(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        item: ReviewItem,
+    ) -> Result<Option<AccessReview>, Error> {
+        self.repository
+            .decide_item(db, organization, uuid, item)
+            .await
+    }
+
+    pub async fn close(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        closed_at: &str,
+    ) -> Result<Option<AccessReview>, Error> {
+        self.repository
+            .close(db, organization, uuid, closed_at)
+            .await
+    }
+}
+
+/// The outcome of a campaign
+#[derive(Serialize)]
+pub struct ReviewSummary {
+    #[serde(rename(serialize = "reviewId"))]
+    pub review_id: String,
+    pub name: String,
+    pub state: ReviewState,
+    #[serde(rename(serialize = "closedAt"))]
+    pub closed_at: Option<String>,
+    pub total: usize,
+    pub approved: usize,
+    pub revoked: usize,
+    pub pending: usize,
+    pub items: Vec<ReviewItem>,
+}
+
+pub fn summarize(review: AccessReview) -> ReviewSummary {
+    let count = |decision: ReviewDecision| {
+        review
+            .items
+            .iter()
+            .filter(|i| i.decision == decision)
+            .count()
+    };
+
+    ReviewSummary {
+        review_id: review.id.clone(),
+        name: review.name.clone(),
+        state: review.state,
+        closed_at: review.closed_at.clone(),
+        total: review.items.len(),
+        approved: count(ReviewDecision::Approved),
+        revoked: count(ReviewDecision::Revoked),
+        pending: count(ReviewDecision::Pending),
+        items: review.items,
+    }
+}
+
+/// Export the items of a campaign as CSV, one line per reviewed role
+pub fn to_csv(summary: &ReviewSummary) -> String {
+    let mut csv = String::from(
+        "itemId,userId,username,roleId,roleName,decision,reviewerId,decidedAt,comment\n",
+    );
+
+    for item in &summary.items {
+        let decision = match item.decision {
+            ReviewDecision::Pending => "PENDING",
+            ReviewDecision::Approved => "APPROVED",
+            ReviewDecision::Revoked => "REVOKED",
+        };
+
+        let fields = [
+            item.id.as_str(),
+            item.user_id.as_str(),
+            item.username.as_str(),
+            item.role_id.as_str(),
+            item.role_name.as_str(),
+            decision,
+            item.reviewer_id.as_deref().unwrap_or(""),
+            item.decided_at.as_deref().unwrap_or(""),
+            item.comment.as_deref().unwrap_or(""),
+        ];
+
+        let line: Vec<String> = fields.iter().map(|f| escape_csv(f)).collect();
+        csv.push_str(&line.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// Quote a CSV field when it contains a separator, a quote or a line break
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        String::from(field)
+    }
+}
@@ -0,0 +1,125 @@
+use mongodb::{error::Error, ClientSession, Database};
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
+
+use crate::persistence::api_key::{api_key_repository::ApiKeyRepository, model::api_key::ApiKey};
+
+/// The marker that every generated key starts with
+pub const API_KEY_MARKER: &str = "ak";
+const PREFIX_LENGTH: usize = 8;
+const SECRET_LENGTH: usize = 40;
+
+#[derive(Clone)]
+pub struct ApiKeyService {
+    pub repository: ApiKeyRepository,
+}
+
+impl ApiKeyService {
+    pub fn new(repository: ApiKeyRepository) -> Self {
+        Self { repository }
+    }
+
+    pub async fn create(&self, api_key: ApiKey, db: &Database) -> Result<Option<ApiKey>, Error> {
+        self.repository.create(api_key, db).await
+    }
+
+    pub async fn find_by_owner(
+        &self,
+        db: &Database,
+        organization: &str,
+        owner_id: &str,
+    ) -> Result<Vec<ApiKey>, Error> {
+        self.repository
+            .find_by_owner(db, organization, owner_id)
+            .await
+    }
+
+    pub async fn find_by_uuid(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<Option<ApiKey>, Error> {
+        self.repository.find_by_uuid(db, organization, uuid).await
+    }
+
+    pub async fn find_by_prefix(
+        &self,
+        db: &Database,
+        prefix: &str,
+    ) -> Result<Option<ApiKey>, Error> {
+        self.repository.find_by_prefix(db, prefix).await
+    }
+
+    pub async fn update_last_used(
+        &self,
+        db: &Database,
+        uuid: &str,
+        last_used: &str,
+    ) -> Result<u64, Error> {
+        self.repository.update_last_used(db, uuid, last_used).await
+    }
+
+    pub async fn revoke(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        revoked_at: &str,
+    ) -> Result<Option<ApiKey>, Error> {
+        self.repository
+            .revoke(db, organization, uuid, revoked_at)
+            .await
+    }
+
+    pub async fn delete_by_owner(
+        &self,
+        db: &Database,
+        organization: &str,
+        owner_id: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        self.repository
+            .delete_by_owner(db, organization, owner_id, session)
+            .await
+    }
+}
+
+fn random_string(length: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(length)
+        .map(char::from)
+        .collect()
+}
+
+/// Generate a new key of the form `ak_<prefix>_<secret>` and return the prefix and the full key
+pub fn generate_key() -> (String, String) {
+    let prefix = random_string(PREFIX_LENGTH);
+    let key = format!(
+        "{}_{}_{}",
+        API_KEY_MARKER,
+        prefix,
+        random_string(SECRET_LENGTH)
+    );
+
+    (prefix, key)
+}
+
+/// Extract the prefix of a key, if the key is well-formed
+pub fn parse_prefix(key: &str) -> Option<&str> {
+    let mut parts = key.splitn(3, '_');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(API_KEY_MARKER), Some(prefix), Some(secret))
+            if prefix.len() == PREFIX_LENGTH && !secret.is_empty() =>
+        {
+            Some(prefix)
+        }
+        _ => None,
+    }
+}
+
+/// Hash a key for storage. Keys are long and random, so a fast hash is sufficient.
+pub fn hash_key(key: &str) -> String {
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
@@ -0,0 +1,102 @@
+use std::collections::HashSet;
+
+use mongodb::{error::Error, ClientSession, Database};
+
+use crate::persistence::constraint::{
+    constraint_repository::ConstraintRepository, model::constraint::Constraint,
+};
+
+#[derive(Clone)]
+pub struct ConstraintService {
+    pub repository: ConstraintRepository,
+}
+
+impl ConstraintService {
+    pub fn new(repository: ConstraintRepository) -> Self {
+        Self { repository }
+    }
+
+    pub async fn create(
+        &self,
+        constraint: Constraint,
+        db: &Database,
+    ) -> Result<Option<Constraint>, Error> {
+        self.repository.create(constraint, db).await
+    }
+
+    pub async fn find_all(
+        &self,
+        db: &Database,
+        organization: &str,
+    ) -> Result<Vec<Constraint>, Error> {
+        self.repository.find_all(db, organization).await
+    }
+
+    pub async fn find_by_uuid(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<Option<Constraint>, Error> {
+        self.repository.find_by_uuid(db, organization, uuid).await
+    }
+
+    pub async fn find_by_name(
+        &self,
+        db: &Database,
+        organization: &str,
+        name: &str,
+    ) -> Result<Option<Constraint>, Error> {
+        self.repository.find_by_name(db, organization, name).await
+    }
+
+    pub async fn update(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        constraint: Constraint,
+    ) -> Result<Option<Constraint>, Error> {
+        self.repository
+            .update(db, organization, uuid, constraint)
+            .await
+    }
+
+    pub async fn remove_role(
+        &self,
+        db: &Database,
+        organization: &str,
+        role_id: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        self.repository
+            .remove_role(db, organization, role_id, session)
+            .await
+    }
+
+    pub async fn delete(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<u64, Error> {
+        self.repository.delete(db, organization, uuid).await
+    }
+}
+
+/// Find the roles of a constraint that are held together. Returns an empty list when at most one
+/// of the mutually exclusive roles is held.
+pub fn conflicting_roles(constraint: &Constraint, roles: &HashSet<String>) -> Vec<String> {
+    let held: Vec<String> = constraint
+        .roles
+        .iter()
+        .filter(|r| roles.contains(*r))
+        .cloned()
+        .collect();
+
+    if held.len() > 1 {
+        held
+    } else {
+        vec![]
+    }
+}
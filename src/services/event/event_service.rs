@@ -0,0 +1,22 @@
+use mongodb::{error::Error, Database};
+
+use crate::persistence::event::{event_repository::EventRepository, model::event::Event};
+
+#[derive(Clone)]
+pub struct EventService {
+    pub repository: EventRepository,
+}
+
+impl EventService {
+    pub fn new(repository: EventRepository) -> Self {
+        Self { repository }
+    }
+
+    pub async fn create(&self, event: Event, db: &Database) -> Result<(), Error> {
+        self.repository.create(event, db).await
+    }
+
+    pub async fn find_all(&self, db: &Database, organization: &str) -> Result<Vec<Event>, Error> {
+        self.repository.find_all(db, organization).await
+    }
+}
@@ -0,0 +1,221 @@
+use mongodb::{error::Error, ClientSession, Database};
+
+use crate::persistence::group::{group_repository::GroupRepository, model::group::Group};
+
+/// The maximum number of nesting levels that are followed when resolving group membership
+pub const MAX_GROUP_DEPTH: usize = 16;
+
+pub const MEMBERS_FIELD: &str = "members";
+pub const GROUPS_FIELD: &str = "groups";
+pub const ROLES_FIELD: &str = "roles";
+
+#[derive(Clone)]
+pub struct GroupService {
+    pub repository: GroupRepository,
+}
+
+impl GroupService {
+    pub fn new(repository: GroupRepository) -> Self {
+        Self { repository }
+    }
+
+    pub async fn create(&self, group: Group, db: &Database) -> Result<Option<Group>, Error> {
+        self.repository.create(group, db).await
+    }
+
+    pub async fn find_all(&self, db: &Database, organization: &str) -> Result<Vec<Group>, Error> {
+        self.repository.find_all(db, organization).await
+    }
+
+    pub async fn find_by_uuid(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<Option<Group>, Error> {
+        self.repository.find_by_uuid(db, organization, uuid).await
+    }
+
+    pub async fn find_by_name(
+        &self,
+        db: &Database,
+        organization: &str,
+        name: &str,
+    ) -> Result<Option<Group>, Error> {
+        self.repository.find_by_name(db, organization, name).await
+    }
+
+    pub async fn update(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        group: Group,
+    ) -> Result<Option<Group>, Error> {
+        self.repository.update(db, organization, uuid, group).await
+    }
+
+    pub async fn add_to_set(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        field: &str,
+        value: &str,
+    ) -> Result<Option<Group>, Error> {
+        self.repository
+            .add_to_set(db, organization, uuid, field, value)
+            .await
+    }
+
+    pub async fn pull(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        field: &str,
+        value: &str,
+    ) -> Result<Option<Group>, Error> {
+        self.repository
+            .pull(db, organization, uuid, field, value)
+            .await
+    }
+
+    pub async fn pull_from_all(
+        &self,
+        db: &Database,
+        organization: &str,
+        field: &str,
+        value: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        self.repository
+            .pull_from_all(db, organization, field, value, session)
+            .await
+    }
+
+    pub async fn delete(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        self.repository
+            .delete(db, organization, uuid, session)
+            .await
+    }
+
+    /// Find every group that a user is a member of, either directly or through nested groups.
+    /// Groups are returned in the order in which they were reached.
+    pub async fn find_effective_groups(
+        &self,
+        db: &Database,
+        organization: &str,
+        user_id: &str,
+    ) -> Result<Vec<Group>, Error> {
+        let direct = self
+            .repository
+            .find_by_member(db, organization, user_id)
+            .await?;
+
+        self.collect_ancestors(db, organization, direct).await
+    }
+
+    /// Find a group together with every group that contains it, directly or indirectly
+    pub async fn find_with_ancestors(
+        &self,
+        db: &Database,
+        organization: &str,
+        group_id: &str,
+    ) -> Result<Vec<Group>, Error> {
+        let start = match self
+            .repository
+            .find_by_uuid(db, organization, group_id)
+            .await?
+        {
+            Some(d) => vec![d],
+            None => vec![],
+        };
+
+        self.collect_ancestors(db, organization, start).await
+    }
+
+    /// Find a group together with every group that it contains, directly or indirectly
+    pub async fn find_with_descendants(
+        &self,
+        db: &Database,
+        organization: &str,
+        group_id: &str,
+    ) -> Result<Vec<Group>, Error> {
+        let mut found: Vec<Group> = vec![];
+        let mut frontier = self
+            .repository
+            .find_by_uuids(db, organization, &[String::from(group_id)])
+            .await?;
+
+        for _ in 0..MAX_GROUP_DEPTH {
+            frontier.retain(|g| !found.iter().any(|f| f.id == g.id));
+            if frontier.is_empty() {
+                break;
+            }
+
+            let ids: Vec<String> = frontier.iter().flat_map(|g| g.groups.clone()).collect();
+            found.append(&mut frontier);
+
+            frontier = self
+                .repository
+                .find_by_uuids(db, organization, &ids)
+                .await?;
+        }
+
+        Ok(found)
+    }
+
+    /// Find the ids of every group that contains the given group, directly or indirectly
+    pub async fn find_ancestor_ids(
+        &self,
+        db: &Database,
+        organization: &str,
+        group_id: &str,
+    ) -> Result<Vec<String>, Error> {
+        let parents = self
+            .repository
+            .find_by_child_groups(db, organization, &[String::from(group_id)])
+            .await?;
+
+        Ok(self
+            .collect_ancestors(db, organization, parents)
+            .await?
+            .into_iter()
+            .map(|g| g.id)
+            .collect())
+    }
+
+    async fn collect_ancestors(
+        &self,
+        db: &Database,
+        organization: &str,
+        start: Vec<Group>,
+    ) -> Result<Vec<Group>, Error> {
+        let mut found: Vec<Group> = vec![];
+        let mut frontier = start;
+
+        for _ in 0..MAX_GROUP_DEPTH {
+            frontier.retain(|g| !found.iter().any(|f| f.id == g.id));
+            if frontier.is_empty() {
+                break;
+            }
+
+            let ids: Vec<String> = frontier.iter().map(|g| g.id.clone()).collect();
+            found.append(&mut frontier);
+
+            frontier = self
+                .repository
+                .find_by_child_groups(db, organization, &ids)
+                .await?;
+        }
+
+        Ok(found)
+    }
+}
@@ -0,0 +1,3 @@
+pub mod geo_ip;
+pub mod login_service;
+pub mod notifier;
@@ -0,0 +1,62 @@
+use std::{fs, io, net::Ipv4Addr};
+
+/// A local GeoIP database that maps IPv4 ranges to countries. The database is a CSV file with one
+/// `start,end,country` range per line, where `start` and `end` are dotted addresses or integers.
+/// Empty lines and lines starting with `#` are ignored.
+pub struct GeoIp {
+    ranges: Vec<(u32, u32, String)>,
+}
+
+impl GeoIp {
+    pub fn load(path: &str) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+
+        let mut ranges = vec![];
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line
+                .split(',')
+                .map(|f| f.trim().trim_matches('"'))
+                .collect();
+            if fields.len() < 3 {
+                continue;
+            }
+
+            if let (Some(start), Some(end)) = (parse_address(fields[0]), parse_address(fields[1])) {
+                if start <= end && !fields[2].is_empty() {
+                    ranges.push((start, end, String::from(fields[2])));
+                }
+            }
+        }
+
+        ranges.sort_by_key(|r| r.0);
+
+        Ok(Self { ranges })
+    }
+
+    /// Find the country of an IPv4 address
+    pub fn lookup(&self, ip: &str) -> Option<&str> {
+        let address = u32::from(ip.parse::<Ipv4Addr>().ok()?);
+        let index = self.ranges.partition_point(|r| r.0 <= address);
+        if index == 0 {
+            return None;
+        }
+
+        let (_, end, country) = &self.ranges[index - 1];
+        if address <= *end {
+            Some(country)
+        } else {
+            None
+        }
+    }
+}
+
+fn parse_address(value: &str) -> Option<u32> {
+    match value.parse::<Ipv4Addr>() {
+        Ok(d) => Some(u32::from(d)),
+        Err(_) => value.parse::<u32>().ok(),
+    }
+}
@@ -0,0 +1,149 @@
+use std::{net::IpAddr, sync::Arc};
+
+use chrono::Duration;
+use mongodb::{error::Error, Database};
+use sha2::{Digest, Sha256};
+
+use crate::persistence::{
+    login::{
+        login_repository::LoginRepository,
+        model::login_attempt::{LoginAlert, LoginAttempt},
+    },
+    user::model::role_assignment::parse_timestamp,
+};
+
+use super::{geo_ip::GeoIp, notifier::Notifier};
+
+/// The number of earlier successful logins that a new login is compared with
+pub const LOGIN_HISTORY_DEPTH: i64 = 100;
+
+#[derive(Clone)]
+pub struct LoginService {
+    pub repository: LoginRepository,
+    geo_ip: Option<Arc<GeoIp>>,
+    notifier: Arc<dyn Notifier>,
+    travel_window: i64,
+}
+
+impl LoginService {
+    /// Create the service. Login alerts are delivered to the given notifier.
+    pub fn new(
+        repository: LoginRepository,
+        geo_ip: Option<GeoIp>,
+        travel_window: i64,
+        notifier: Arc<dyn Notifier>,
+    ) -> Self {
+        Self {
+            repository,
+            geo_ip: geo_ip.map(Arc::new),
+            notifier,
+            travel_window,
+        }
+    }
+
+    pub async fn create(&self, attempt: LoginAttempt, db: &Database) -> Result<(), Error> {
+        self.repository.create(attempt, db).await
+    }
+
+    pub async fn find_by_user(
+        &self,
+        db: &Database,
+        organization: &str,
+        user_id: &str,
+    ) -> Result<Vec<LoginAttempt>, Error> {
+        self.repository
+            .find_by_user(db, organization, user_id, LOGIN_HISTORY_DEPTH)
+            .await
+    }
+
+    /// Find the country of an address, if a GeoIP database was configured
+    pub fn locate(&self, ip: &str) -> Option<String> {
+        self.geo_ip
+            .as_ref()
+            .and_then(|g| g.lookup(ip))
+            .map(String::from)
+    }
+
+    /// Compare a successful login with the earlier successful logins of the user. The first login
+    /// of a user never raises alerts.
+    pub async fn detect(
+        &self,
+        db: &Database,
+        attempt: &LoginAttempt,
+    ) -> Result<Vec<LoginAlert>, Error> {
+        let user_id = match &attempt.user_id {
+            Some(d) => d,
+            None => return Ok(vec![]),
+        };
+
+        let previous = self
+            .repository
+            .find_successful_by_user(db, &attempt.organization, user_id, LOGIN_HISTORY_DEPTH)
+            .await?;
+
+        if previous.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut alerts = vec![];
+        if attempt.fingerprint.is_some()
+            && !previous
+                .iter()
+                .any(|p| p.fingerprint == attempt.fingerprint)
+        {
+            alerts.push(LoginAlert::NewDevice);
+        }
+
+        if attempt.network.is_some() && !previous.iter().any(|p| p.network == attempt.network) {
+            alerts.push(LoginAlert::NewNetwork);
+        }
+
+        let last = &previous[0];
+        if let (Some(country), Some(last_country)) = (&attempt.country, &last.country) {
+            let elapsed = match (
+                parse_timestamp(&attempt.timestamp),
+                parse_timestamp(&last.timestamp),
+            ) {
+                (Some(now), Some(then)) => Some(now - then),
+                _ => None,
+            };
+
+            if country != last_country
+                && matches!(elapsed, Some(d) if d < Duration::minutes(self.travel_window))
+            {
+                alerts.push(LoginAlert::ImpossibleTravel);
+            }
+        }
+
+        Ok(alerts)
+    }
+
+    pub fn notify(&self, attempt: &LoginAttempt, alert: LoginAlert) {
+        self.notifier.notify(attempt, alert);
+    }
+}
+
+/// Fingerprint a device by its user agent
+pub fn fingerprint(user_agent: Option<&str>) -> Option<String> {
+    match user_agent {
+        Some(d) if !d.is_empty() => {
+            let hash = format!("{:x}", Sha256::digest(d.as_bytes()));
+            Some(String::from(&hash[0..16]))
+        }
+        _ => None,
+    }
+}
+
+/// The range that an address belongs to: the /24 of an IPv4 address or the /64 of an IPv6 address
+pub fn network(ip: &str) -> Option<String> {
+    match ip.parse::<IpAddr>().ok()? {
+        IpAddr::V4(d) => {
+            let o = d.octets();
+            Some(format!("{}.{}.{}.0/24", o[0], o[1], o[2]))
+        }
+        IpAddr::V6(d) => {
+            let s = d.segments();
+            Some(format!("{:x}:{:x}:{:x}:{:x}::/64", s[0], s[1], s[2], s[3]))
+        }
+    }
+}
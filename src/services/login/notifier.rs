@@ -0,0 +1,23 @@
+use crate::persistence::login::model::login_attempt::{LoginAlert, LoginAttempt};
+
+/// Delivers login alerts to the outside world, for instance by mail or a chat webhook.
+/// Implementations must not block: slow deliveries should be spawned onto the runtime.
+pub trait Notifier: Send + Sync {
+    fn notify(&self, attempt: &LoginAttempt, alert: LoginAlert);
+}
+
+/// Writes login alerts to the log as warnings
+pub struct LogNotifier;
+
+impl Notifier for LogNotifier {
+    fn notify(&self, attempt: &LoginAttempt, alert: LoginAlert) {
+        log::warn!(
+            "Login alert {:?} for {} ({}) from {} ({})",
+            alert,
+            attempt.username,
+            attempt.organization,
+            attempt.ip.as_deref().unwrap_or("unknown address"),
+            attempt.country.as_deref().unwrap_or("unknown country")
+        );
+    }
+}
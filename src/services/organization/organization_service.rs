@@ -0,0 +1,50 @@
+use mongodb::{error::Error, ClientSession, Database};
+
+use crate::persistence::organization::{
+    model::organization::Organization, organization_repository::OrganizationRepository,
+};
+
+#[derive(Clone)]
+pub struct OrganizationService {
+    pub repository: OrganizationRepository,
+}
+
+impl OrganizationService {
+    pub fn new(repository: OrganizationRepository) -> Self {
+        Self { repository }
+    }
+
+    pub async fn create(
+        &self,
+        organization: Organization,
+        db: &Database,
+    ) -> Result<Option<Organization>, Error> {
+        self.repository.create(organization, db).await
+    }
+
+    pub async fn find_all(&self, db: &Database) -> Result<Vec<Organization>, Error> {
+        self.repository.find_all(db).await
+    }
+
+    pub async fn find_by_id(&self, db: &Database, id: &str) -> Result<Option<Organization>, Error> {
+        self.repository.find_by_id(db, id).await
+    }
+
+    pub async fn update(
+        &self,
+        db: &Database,
+        id: &str,
+        organization: Organization,
+    ) -> Result<Option<Organization>, Error> {
+        self.repository.update(db, id, organization).await
+    }
+
+    pub async fn delete(
+        &self,
+        db: &Database,
+        id: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        self.repository.delete(db, id, session).await
+    }
+}
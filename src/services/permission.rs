@@ -1 +1,2 @@
 pub mod permission_service;
+pub mod system_permission;
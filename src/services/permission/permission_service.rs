@@ -1,7 +1,8 @@
-use mongodb::{error::Error, Database};
+use mongodb::{error::Error, ClientSession, Database};
 
-use crate::persistence::permission::{
-    model::permission::Permission, permission_repository::PermissionRepository,
+use crate::persistence::{
+    page::{Page, PageRequest},
+    permission::{model::permission::Permission, permission_repository::PermissionRepository},
 };
 
 #[derive(Clone)]
@@ -14,44 +15,102 @@ impl PermissionService {
         Self { repository }
     }
 
+    pub async fn create_unique_index(&self, db: &Database) -> Result<(), Error> {
+        self.repository.create_unique_index(db).await
+    }
+
     pub async fn create(
         &self,
         permission: Permission,
         db: &Database,
+        session: Option<&mut ClientSession>,
     ) -> Result<Option<Permission>, Error> {
-        self.repository.create(permission, db).await
+        self.repository.create(permission, db, session).await
+    }
+
+    pub async fn find_all(
+        &self,
+        db: &Database,
+        organization: &str,
+    ) -> Result<Vec<Permission>, Error> {
+        self.repository.find_all(db, organization).await
     }
 
-    pub async fn find_all(&self, db: &Database) -> Result<Vec<Permission>, Error> {
-        self.repository.find_all(db).await
+    pub async fn find_page(
+        &self,
+        db: &Database,
+        organization: &str,
+        name: Option<&str>,
+        page: &PageRequest,
+    ) -> Result<Page<Permission>, Error> {
+        self.repository
+            .find_page(db, organization, name, page)
+            .await
     }
 
     pub async fn find_by_uuid(
         &self,
         db: &Database,
+        organization: &str,
         uuid: &str,
     ) -> Result<Option<Permission>, Error> {
-        self.repository.find_by_uuid(db, uuid).await
+        self.repository.find_by_uuid(db, organization, uuid).await
     }
 
     pub async fn find_by_name(
         &self,
         db: &Database,
+        organization: &str,
         name: &str,
     ) -> Result<Option<Permission>, Error> {
-        self.repository.find_by_name(db, name).await
+        self.repository.find_by_name(db, organization, name).await
     }
 
     pub async fn update(
         &self,
         db: &Database,
+        organization: &str,
         uuid: &str,
         permission: Permission,
+        session: Option<&mut ClientSession>,
     ) -> Result<Option<Permission>, Error> {
-        self.repository.update(db, uuid, permission).await
+        self.repository
+            .update(db, organization, uuid, permission, session)
+            .await
     }
 
-    pub async fn delete(&self, db: &Database, uuid: &str) -> Result<u64, Error> {
-        self.repository.delete(db, uuid).await
+    pub async fn delete(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        version: Option<i64>,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        self.repository
+            .delete(db, organization, uuid, version, session)
+            .await
+    }
+
+    pub async fn delete_by_names(
+        &self,
+        db: &Database,
+        organization: &str,
+        names: &[&str],
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        self.repository
+            .delete_by_names(db, organization, names, session)
+            .await
+    }
+
+    pub async fn assign_default_organization(
+        &self,
+        db: &Database,
+        organization: &str,
+    ) -> Result<u64, Error> {
+        self.repository
+            .assign_default_organization(db, organization)
+            .await
     }
 }
@@ -0,0 +1,73 @@
+macro_rules! system_permissions {
+    ($($variant:ident => ($name:literal, $description:literal),)*) => {
+        /// A permission that guards the endpoints of the service itself. Every organization holds
+        /// all of them, so that they can be granted through roles like any other permission.
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        pub enum SystemPermission {
+            $($variant,)*
+        }
+
+        impl SystemPermission {
+            pub const ALL: &'static [SystemPermission] = &[$(SystemPermission::$variant,)*];
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(SystemPermission::$variant => $name,)*
+                }
+            }
+
+            pub fn description(self) -> &'static str {
+                match self {
+                    $(SystemPermission::$variant => $description,)*
+                }
+            }
+        }
+    };
+}
+
+system_permissions! {
+    CreateUser => ("CAN_CREATE_USER", "Create users"),
+    ReadUser => ("CAN_READ_USER", "Read and search users"),
+    UpdateUser => ("CAN_UPDATE_USER", "Update users, their passwords and their roles"),
+    DeleteUser => ("CAN_DELETE_USER", "Delete users"),
+    ImpersonateUser => ("CAN_IMPERSONATE_USER", "Act as another user"),
+    ReadSession => ("CAN_READ_SESSION", "Read the sessions of users"),
+    RevokeSession => ("CAN_REVOKE_SESSION", "Revoke the sessions of users"),
+    ManageServiceAccount => ("CAN_MANAGE_SERVICE_ACCOUNT", "Create and manage service accounts"),
+    CreateApiKey => ("CAN_CREATE_API_KEY", "Create API keys"),
+    CreateRole => ("CAN_CREATE_ROLE", "Create roles"),
+    ReadRole => ("CAN_READ_ROLE", "Read roles"),
+    UpdateRole => ("CAN_UPDATE_ROLE", "Update roles and their permissions"),
+    DeleteRole => ("CAN_DELETE_ROLE", "Delete roles"),
+    CreatePermission => ("CAN_CREATE_PERMISSION", "Create permissions"),
+    ReadPermission => ("CAN_READ_PERMISSION", "Read permissions"),
+    UpdatePermission => ("CAN_UPDATE_PERMISSION", "Update permissions"),
+    DeletePermission => ("CAN_DELETE_PERMISSION", "Delete permissions"),
+    GrantAny => ("CAN_GRANT_ANY", "Grant roles and permissions that the caller does not hold"),
+    CreateGroup => ("CAN_CREATE_GROUP", "Create groups"),
+    ReadGroup => ("CAN_READ_GROUP", "Read groups"),
+    UpdateGroup => ("CAN_UPDATE_GROUP", "Update groups and their members"),
+    DeleteGroup => ("CAN_DELETE_GROUP", "Delete groups"),
+    RequestAccess => ("CAN_REQUEST_ACCESS", "Request access to roles"),
+    ManageAccessReview => ("CAN_MANAGE_ACCESS_REVIEW", "Start and close access reviews"),
+    ReadAccessReview => ("CAN_READ_ACCESS_REVIEW", "Read access reviews and their reports"),
+    ReadConstraint => ("CAN_READ_CONSTRAINT", "Read separation of duties constraints"),
+    ManageConstraint => ("CAN_MANAGE_CONSTRAINT", "Manage separation of duties constraints"),
+    ReadEvent => ("CAN_READ_EVENT", "Read the events of an organization"),
+    ExplainAuthorization => ("CAN_EXPLAIN_AUTHORIZATION", "Explain authorization decisions"),
+    ImportData => ("CAN_IMPORT_DATA", "Import users, roles and permissions"),
+    ExportData => ("CAN_EXPORT_DATA", "Export users, roles and permissions"),
+    ManagePolicy => ("CAN_MANAGE_POLICY", "Manage attribute-based policies"),
+    ReadRelationship => ("CAN_READ_RELATIONSHIP", "Check, expand and read relationships"),
+    WriteRelationship => ("CAN_WRITE_RELATIONSHIP", "Write relationships"),
+    ManageNamespace => ("CAN_MANAGE_NAMESPACE", "Manage relationship namespaces"),
+}
+
+impl SystemPermission {
+    pub fn from_name(name: &str) -> Option<SystemPermission> {
+        SystemPermission::ALL
+            .iter()
+            .copied()
+            .find(|p| p.name() == name)
+    }
+}
@@ -0,0 +1 @@
+pub mod policy_service;
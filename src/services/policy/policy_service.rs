@@ -0,0 +1,625 @@
+use std::net::IpAddr;
+
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use mongodb::{error::Error, Database};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::persistence::{
+    policy::{
+        model::policy::{Condition, Effect, Operator, Policy},
+        policy_repository::PolicyRepository,
+    },
+    user::model::user::User,
+};
+
+/// The attribute roots that a policy condition can refer to
+pub const ATTRIBUTE_ROOTS: [&str; 4] = ["subject", "resource", "action", "environment"];
+
+#[derive(Serialize, Clone)]
+pub struct PolicyEvaluation {
+    #[serde(rename(serialize = "policyId"))]
+    pub policy_id: String,
+    pub name: String,
+    pub effect: Effect,
+    pub applicable: bool,
+    pub matched: bool,
+}
+
+#[derive(Serialize, Clone)]
+pub struct PolicyDecision {
+    pub allowed: bool,
+    pub policies: Vec<PolicyEvaluation>,
+}
+
+#[derive(Clone)]
+pub struct PolicyService {
+    pub repository: PolicyRepository,
+}
+
+impl PolicyService {
+    pub fn new(repository: PolicyRepository) -> Self {
+        Self { repository }
+    }
+
+    pub async fn create(&self, policy: Policy, db: &Database) -> Result<Option<Policy>, Error> {
+        self.repository.create(policy, db).await
+    }
+
+    pub async fn find_all(&self, db: &Database, organization: &str) -> Result<Vec<Policy>, Error> {
+        self.repository.find_all(db, organization).await
+    }
+
+    pub async fn find_by_uuid(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<Option<Policy>, Error> {
+        self.repository.find_by_uuid(db, organization, uuid).await
+    }
+
+    pub async fn find_by_name(
+        &self,
+        db: &Database,
+        organization: &str,
+        name: &str,
+    ) -> Result<Option<Policy>, Error> {
+        self.repository.find_by_name(db, organization, name).await
+    }
+
+    pub async fn update(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        policy: Policy,
+    ) -> Result<Option<Policy>, Error> {
+        self.repository.update(db, organization, uuid, policy).await
+    }
+
+    pub async fn delete(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<u64, Error> {
+        self.repository.delete(db, organization, uuid).await
+    }
+
+    /// Evaluate every enabled policy of an organization for the action in the given context
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The `Database` that contains the policies
+    /// * `organization` - The organization whose policies apply
+    /// * `context` - The evaluation context, see `build_context`
+    pub async fn evaluate(
+        &self,
+        db: &Database,
+        organization: &str,
+        context: &Value,
+    ) -> Result<PolicyDecision, Error> {
+        let action = context["action"].as_str().unwrap_or_default();
+        let policies = self
+            .repository
+            .find_enabled_by_action(db, organization, action)
+            .await?;
+
+        Ok(evaluate_policies(&policies, context))
+    }
+}
+
+/// Combine the policies using deny-overrides: a matching `DENY` policy always denies. If any
+/// `ALLOW` policy applies, at least one of them has to match, otherwise the action is denied.
+/// Policies that refer to resource attributes are not applicable when there is no resource.
+pub fn evaluate_policies(policies: &[Policy], context: &Value) -> PolicyDecision {
+    let has_resource = context["resource"].is_object();
+    let mut evaluations = vec![];
+    let mut denied = false;
+    let mut allow_applicable = false;
+    let mut allow_matched = false;
+
+    for policy in policies {
+        let applicable = has_resource
+            || !policy
+                .conditions
+                .iter()
+                .any(|c| refers_to_resource(&c.attribute) || refers_to_resource_value(c));
+
+        let matched = applicable && policy.conditions.iter().all(|c| matches(c, context));
+
+        if applicable {
+            match policy.effect {
+                Effect::Deny => denied = denied || matched,
+                Effect::Allow => {
+                    allow_applicable = true;
+                    allow_matched = allow_matched || matched;
+                }
+            }
+        }
+
+        evaluations.push(PolicyEvaluation {
+            policy_id: policy.id.clone(),
+            name: policy.name.clone(),
+            effect: policy.effect,
+            applicable,
+            matched,
+        });
+    }
+
+    PolicyDecision {
+        allowed: !denied && (!allow_applicable || allow_matched),
+        policies: evaluations,
+    }
+}
+
+/// Build the evaluation context for a policy check
+///
+/// # Arguments
+///
+/// * `subject` - The `User` that performs the action
+/// * `action` - The name of the permission that is being checked
+/// * `resource` - The attributes of the resource that is being accessed, if any
+/// * `client_ip` - The IP address of the client, if known
+/// * `now` - The moment at which the action is performed
+pub fn build_context(
+    subject: &User,
+    action: &str,
+    resource: Option<Value>,
+    client_ip: Option<String>,
+    now: DateTime<Utc>,
+) -> Value {
+    json!({
+        "subject": user_attributes(subject),
+        "resource": resource.unwrap_or(Value::Null),
+        "action": action,
+        "environment": {
+            "time": format!("{:02}:{:02}", now.hour(), now.minute()),
+            "hour": now.hour(),
+            "weekday": now.weekday().number_from_monday(),
+            "date": now.format("%Y-%m-%d").to_string(),
+            "timestamp": now.timestamp(),
+            "ip": client_ip,
+        }
+    })
+}
+
+/// Collect the attributes of a user that can be used in policy conditions. Custom attributes
+/// cannot shadow the fields of the user document itself.
+pub fn user_attributes(user: &User) -> Value {
+    let mut attributes = Map::new();
+    for (key, value) in &user.attributes {
+        attributes.insert(key.clone(), value.clone());
+    }
+
+    attributes.insert(String::from("id"), json!(user.id));
+    attributes.insert(String::from("username"), json!(user.username));
+    attributes.insert(String::from("emailAddress"), json!(user.email_address));
+    attributes.insert(String::from("firstName"), json!(user.first_name));
+    attributes.insert(String::from("lastName"), json!(user.last_name));
+    attributes.insert(String::from("enabled"), json!(user.enabled));
+    attributes.insert(String::from("roles"), json!(user.roles));
+    attributes.insert(String::from("createdAt"), json!(user.created_at));
+    attributes.insert(String::from("organization"), json!(user.organization));
+
+    Value::Object(attributes)
+}
+
+/// Check whether a condition is well formed
+pub fn validate_condition(condition: &Condition) -> Result<(), String> {
+    if !is_valid_attribute(&condition.attribute) {
+        return Err(format!("Invalid attribute {}", condition.attribute));
+    }
+
+    if let Some(x) = &condition.value_attribute {
+        if !is_valid_attribute(x) {
+            return Err(format!("Invalid attribute {}", x));
+        }
+        if condition.value.is_some() {
+            return Err(format!(
+                "Condition on {} cannot have both a value and a value attribute!",
+                condition.attribute
+            ));
+        }
+        return Ok(());
+    }
+
+    let value = match &condition.value {
+        Some(d) => d,
+        None => {
+            if condition.operator == Operator::Exists {
+                return Ok(());
+            }
+            return Err(format!(
+                "Condition on {} requires a value!",
+                condition.attribute
+            ));
+        }
+    };
+
+    match condition.operator {
+        Operator::In | Operator::NotIn if !value.is_array() => Err(format!(
+            "Condition on {} requires an array value!",
+            condition.attribute
+        )),
+        Operator::Between if value.as_array().map(|a| a.len()) != Some(2) => Err(format!(
+            "Condition on {} requires an array with a lower and an upper bound!",
+            condition.attribute
+        )),
+        Operator::InCidr if value.as_str().and_then(parse_cidr).is_none() => Err(format!(
+            "Condition on {} requires a CIDR range!",
+            condition.attribute
+        )),
+        Operator::Exists if !value.is_boolean() => Err(format!(
+            "Condition on {} requires a boolean value!",
+            condition.attribute
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn is_valid_attribute(attribute: &str) -> bool {
+    let root = attribute.split('.').next().unwrap_or_default();
+    ATTRIBUTE_ROOTS.contains(&root) && !attribute.split('.').any(|p| p.is_empty())
+}
+
+fn refers_to_resource(attribute: &str) -> bool {
+    attribute == "resource" || attribute.starts_with("resource.")
+}
+
+fn refers_to_resource_value(condition: &Condition) -> bool {
+    condition
+        .value_attribute
+        .as_deref()
+        .map(refers_to_resource)
+        .unwrap_or(false)
+}
+
+fn resolve<'a>(context: &'a Value, attribute: &str) -> Option<&'a Value> {
+    let mut current = context;
+    for part in attribute.split('.') {
+        current = current.get(part)?;
+    }
+
+    if current.is_null() {
+        None
+    } else {
+        Some(current)
+    }
+}
+
+fn matches(condition: &Condition, context: &Value) -> bool {
+    let actual = resolve(context, &condition.attribute);
+
+    let expected = match &condition.value_attribute {
+        Some(x) => match resolve(context, x) {
+            Some(d) => Some(d),
+            None => return false,
+        },
+        None => condition.value.as_ref(),
+    };
+
+    if condition.operator == Operator::Exists {
+        let should_exist = expected.and_then(|v| v.as_bool()).unwrap_or(true);
+        return actual.is_some() == should_exist;
+    }
+
+    let (actual, expected) = match (actual, expected) {
+        (Some(a), Some(e)) => (a, e),
+        _ => return false,
+    };
+
+    match condition.operator {
+        Operator::Equals => values_equal(actual, expected),
+        Operator::NotEquals => !values_equal(actual, expected),
+        Operator::In => expected
+            .as_array()
+            .map(|a| a.iter().any(|v| values_equal(actual, v)))
+            .unwrap_or(false),
+        Operator::NotIn => expected
+            .as_array()
+            .map(|a| !a.iter().any(|v| values_equal(actual, v)))
+            .unwrap_or(false),
+        Operator::Contains => match actual {
+            Value::Array(a) => a.iter().any(|v| values_equal(v, expected)),
+            Value::String(s) => expected.as_str().map(|e| s.contains(e)).unwrap_or(false),
+            _ => false,
+        },
+        Operator::GreaterThan => compare(actual, expected) == Some(std::cmp::Ordering::Greater),
+        Operator::GreaterThanOrEqual => matches!(
+            compare(actual, expected),
+            Some(std::cmp::Ordering::Greater | std::cmp::Ordering::Equal)
+        ),
+        Operator::LessThan => compare(actual, expected) == Some(std::cmp::Ordering::Less),
+        Operator::LessThanOrEqual => matches!(
+            compare(actual, expected),
+            Some(std::cmp::Ordering::Less | std::cmp::Ordering::Equal)
+        ),
+        Operator::Between => match expected.as_array() {
+            Some(bounds) if bounds.len() == 2 => {
+                matches!(
+                    compare(actual, &bounds[0]),
+                    Some(std::cmp::Ordering::Greater | std::cmp::Ordering::Equal)
+                ) && matches!(
+                    compare(actual, &bounds[1]),
+                    Some(std::cmp::Ordering::Less | std::cmp::Ordering::Equal)
+                )
+            }
+            _ => false,
+        },
+        Operator::StartsWith => match (actual.as_str(), expected.as_str()) {
+            (Some(a), Some(e)) => a.starts_with(e),
+            _ => false,
+        },
+        Operator::InCidr => match (actual.as_str(), expected.as_str().and_then(parse_cidr)) {
+            (Some(a), Some((network, prefix))) => match a.parse::<IpAddr>() {
+                Ok(ip) => ip_in_network(ip, network, prefix),
+                Err(_) => false,
+            },
+            _ => false,
+        },
+        Operator::Exists => false,
+    }
+}
+
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(x), Some(y)) => x == y,
+        _ => a == b,
+    }
+}
+
+fn compare(a: &Value, b: &Value) -> Option<std::cmp::Ordering> {
+    if let (Some(x), Some(y)) = (a.as_f64(), b.as_f64()) {
+        return x.partial_cmp(&y);
+    }
+
+    match (a.as_str(), b.as_str()) {
+        (Some(x), Some(y)) => Some(x.cmp(y)),
+        _ => None,
+    }
+}
+
+fn parse_cidr(cidr: &str) -> Option<(IpAddr, u8)> {
+    let (address, prefix) = match cidr.split_once('/') {
+        Some(d) => d,
+        None => (cidr, if cidr.contains(':') { "128" } else { "32" }),
+    };
+
+    let address = address.parse::<IpAddr>().ok()?;
+    let prefix = prefix.parse::<u8>().ok()?;
+    let max = if address.is_ipv4() { 32 } else { 128 };
+
+    if prefix > max {
+        return None;
+    }
+
+    Some((address, prefix))
+}
+
+fn ip_in_network(ip: IpAddr, network: IpAddr, prefix: u8) -> bool {
+    match (ip, network) {
+        (IpAddr::V4(a), IpAddr::V4(n)) => {
+            let mask = if prefix == 0 {
+                0
+            } else {
+                u32::MAX << (32 - prefix)
+            };
+            (u32::from(a) & mask) == (u32::from(n) & mask)
+        }
+        (IpAddr::V6(a), IpAddr::V6(n)) => {
+            let mask = if prefix == 0 {
+                0
+            } else {
+                u128::MAX << (128 - prefix)
+            };
+            (u128::from(a) & mask) == (u128::from(n) & mask)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn condition(attribute: &str, operator: Operator, value: Value) -> Condition {
+        Condition {
+            attribute: String::from(attribute),
+            operator,
+            value: Some(value),
+            value_attribute: None,
+        }
+    }
+
+    fn policy(name: &str, effect: Effect, conditions: Vec<Condition>) -> Policy {
+        Policy {
+            id: String::from(name),
+            name: String::from(name),
+            description: String::from(""),
+            effect,
+            actions: vec![String::from("CAN_READ_DOCUMENT")],
+            conditions,
+            enabled: true,
+            organization: String::from("acme"),
+        }
+    }
+
+    fn context(resource: Value) -> Value {
+        json!({
+            "subject": { "department": "sales", "level": 3, "roles": ["editor"] },
+            "resource": resource,
+            "action": "CAN_READ_DOCUMENT",
+            "environment": { "hour": 10, "ip": "10.1.2.3" },
+        })
+    }
+
+    fn ip(address: &str) -> IpAddr {
+        address.parse().unwrap()
+    }
+
+    #[test]
+    fn matching_deny_overrides_matching_allow() {
+        let policies = vec![
+            policy(
+                "allow-sales",
+                Effect::Allow,
+                vec![condition(
+                    "subject.department",
+                    Operator::Equals,
+                    json!("sales"),
+                )],
+            ),
+            policy(
+                "deny-office-hours",
+                Effect::Deny,
+                vec![condition("environment.hour", Operator::LessThan, json!(12))],
+            ),
+        ];
+
+        let decision = evaluate_policies(&policies, &context(Value::Null));
+
+        assert!(!decision.allowed);
+        assert!(decision.policies.iter().all(|p| p.applicable && p.matched));
+    }
+
+    #[test]
+    fn applicable_allow_has_to_match() {
+        let allow = |department: &str| {
+            policy(
+                "allow",
+                Effect::Allow,
+                vec![condition(
+                    "subject.department",
+                    Operator::Equals,
+                    json!(department),
+                )],
+            )
+        };
+
+        assert!(evaluate_policies(&[allow("sales")], &context(Value::Null)).allowed);
+        assert!(!evaluate_policies(&[allow("finance")], &context(Value::Null)).allowed);
+        assert!(evaluate_policies(&[], &context(Value::Null)).allowed);
+    }
+
+    #[test]
+    fn resource_policies_only_apply_with_a_resource() {
+        let policies = vec![policy(
+            "owner-only",
+            Effect::Allow,
+            vec![Condition {
+                attribute: String::from("subject.department"),
+                operator: Operator::Equals,
+                value: None,
+                value_attribute: Some(String::from("resource.department")),
+            }],
+        )];
+
+        let decision = evaluate_policies(&policies, &context(Value::Null));
+        assert!(decision.allowed);
+        assert!(!decision.policies[0].applicable);
+
+        let decision = evaluate_policies(&policies, &context(json!({ "department": "finance" })));
+        assert!(!decision.allowed);
+        assert!(decision.policies[0].applicable);
+
+        let decision = evaluate_policies(&policies, &context(json!({ "department": "sales" })));
+        assert!(decision.allowed);
+    }
+
+    #[test]
+    fn numbers_compare_numerically_and_strings_lexically() {
+        let ctx = json!({ "subject": { "level": 3, "name": "b", "code": "10" } });
+
+        assert!(matches(
+            &condition("subject.level", Operator::Equals, json!(3.0)),
+            &ctx
+        ));
+        assert!(matches(
+            &condition("subject.level", Operator::LessThan, json!(10)),
+            &ctx
+        ));
+        assert!(matches(
+            &condition("subject.level", Operator::Between, json!([1, 3])),
+            &ctx
+        ));
+        assert!(matches(
+            &condition("subject.name", Operator::GreaterThan, json!("a")),
+            &ctx
+        ));
+        // A numeric string is not a number, so "10" sorts before "9"
+        assert!(matches(
+            &condition("subject.code", Operator::LessThan, json!("9")),
+            &ctx
+        ));
+        assert!(!matches(
+            &condition("subject.code", Operator::Equals, json!(10)),
+            &ctx
+        ));
+        assert!(!matches(
+            &condition("subject.level", Operator::GreaterThan, json!("1")),
+            &ctx
+        ));
+    }
+
+    #[test]
+    fn missing_attributes_only_match_exists_false() {
+        let ctx = json!({ "subject": { "department": "sales" } });
+
+        assert!(!matches(
+            &condition("subject.manager", Operator::NotEquals, json!("bob")),
+            &ctx
+        ));
+        assert!(matches(
+            &condition("subject.manager", Operator::Exists, json!(false)),
+            &ctx
+        ));
+        assert!(matches(
+            &condition("subject.department", Operator::Exists, json!(true)),
+            &ctx
+        ));
+    }
+
+    #[test]
+    fn parse_cidr_accepts_prefixes_up_to_the_address_size() {
+        assert_eq!(parse_cidr("10.0.0.0/8"), Some((ip("10.0.0.0"), 8)));
+        assert_eq!(parse_cidr("10.0.0.1"), Some((ip("10.0.0.1"), 32)));
+        assert_eq!(parse_cidr("::1"), Some((ip("::1"), 128)));
+        assert_eq!(parse_cidr("0.0.0.0/0"), Some((ip("0.0.0.0"), 0)));
+        assert_eq!(parse_cidr("10.0.0.0/33"), None);
+        assert_eq!(parse_cidr("fd00::/129"), None);
+        assert_eq!(parse_cidr("10.0.0/8"), None);
+        assert_eq!(parse_cidr("10.0.0.0/x"), None);
+    }
+
+    #[test]
+    fn ip_in_network_handles_the_edge_prefixes() {
+        assert!(ip_in_network(ip("203.0.113.7"), ip("0.0.0.0"), 0));
+        assert!(ip_in_network(ip("10.1.2.3"), ip("10.0.0.0"), 8));
+        assert!(!ip_in_network(ip("11.1.2.3"), ip("10.0.0.0"), 8));
+        assert!(ip_in_network(ip("10.1.2.3"), ip("10.1.2.3"), 32));
+        assert!(!ip_in_network(ip("10.1.2.4"), ip("10.1.2.3"), 32));
+
+        assert!(ip_in_network(ip("2001:db8::1"), ip("::"), 0));
+        assert!(ip_in_network(ip("2001:db8::1"), ip("2001:db8::"), 32));
+        assert!(ip_in_network(ip("2001:db8::1"), ip("2001:db8::1"), 128));
+        assert!(!ip_in_network(ip("2001:db8::2"), ip("2001:db8::1"), 128));
+    }
+
+    #[test]
+    fn ip_in_network_never_mixes_address_families() {
+        assert!(!ip_in_network(ip("10.0.0.1"), ip("::"), 0));
+        assert!(!ip_in_network(ip("::ffff:10.0.0.1"), ip("10.0.0.0"), 8));
+
+        let ctx = json!({ "environment": { "ip": "2001:db8::1" } });
+        assert!(!matches(
+            &condition("environment.ip", Operator::InCidr, json!("0.0.0.0/0")),
+            &ctx
+        ));
+        assert!(matches(
+            &condition("environment.ip", Operator::InCidr, json!("2001:db8::/32")),
+            &ctx
+        ));
+    }
+}
@@ -0,0 +1 @@
+pub mod relationship_service;
@@ -0,0 +1,1027 @@
+use std::collections::{HashMap, HashSet};
+
+use futures::future::{BoxFuture, FutureExt};
+use mongodb::{bson::doc, error::Error, Database};
+use serde::Serialize;
+
+use crate::persistence::{
+    relationship::{
+        model::{
+            namespace::{Namespace, Rewrite},
+            relation_tuple::RelationTuple,
+        },
+        namespace_repository::NamespaceRepository,
+        relationship_repository::RelationshipRepository,
+    },
+    transaction::Transactions,
+};
+
+/// The maximum number of relations that are followed while evaluating a check or an expand
+pub const MAX_DEPTH: u32 = 25;
+
+pub enum RelationshipError {
+    Storage(Error),
+    DepthExceeded,
+    UnknownNamespace(String),
+    UnknownRelation(String),
+}
+
+impl From<Error> for RelationshipError {
+    fn from(e: Error) -> Self {
+        RelationshipError::Storage(e)
+    }
+}
+
+impl std::fmt::Display for RelationshipError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RelationshipError::Storage(e) => write!(f, "{}", e),
+            RelationshipError::DepthExceeded => write!(
+                f,
+                "Maximum depth of {} exceeded while following relations!",
+                MAX_DEPTH
+            ),
+            RelationshipError::UnknownNamespace(x) => write!(f, "Unknown namespace {}", x),
+            RelationshipError::UnknownRelation(x) => write!(f, "Unknown relation {}", x),
+        }
+    }
+}
+
+/// A subject of a relation: either an object such as `user:alice`, or a set of subjects such as
+/// `group:eng#member`
+#[derive(Clone, PartialEq, Eq)]
+pub struct SubjectRef {
+    pub namespace: String,
+    pub id: String,
+    pub relation: Option<String>,
+}
+
+impl SubjectRef {
+    /// Parse a subject in `namespace:id` or `namespace:id#relation` notation
+    pub fn parse(subject: &str) -> Option<Self> {
+        let (object, relation) = match subject.split_once('#') {
+            Some((o, r)) => (o, Some(r)),
+            None => (subject, None),
+        };
+
+        let (namespace, id) = parse_object(object)?;
+        if relation.map(|r| r.is_empty()).unwrap_or(false) {
+            return None;
+        }
+
+        Some(Self {
+            namespace,
+            id,
+            relation: relation.map(String::from),
+        })
+    }
+
+    fn matches(&self, tuple: &RelationTuple) -> bool {
+        self.namespace == tuple.subject_namespace
+            && self.id == tuple.subject_id
+            && self.relation == tuple.subject_relation
+    }
+}
+
+/// Parse an object in `namespace:id` notation
+pub fn parse_object(object: &str) -> Option<(String, String)> {
+    let (namespace, id) = object.split_once(':')?;
+    if namespace.is_empty() || id.is_empty() || id.contains('#') {
+        return None;
+    }
+
+    Some((String::from(namespace), String::from(id)))
+}
+
+#[derive(Serialize)]
+pub struct ExpandNode {
+    pub object: String,
+    pub relation: String,
+    #[serde(rename(serialize = "type"))]
+    pub kind: String,
+    pub subjects: Vec<String>,
+    pub children: Vec<ExpandNode>,
+}
+
+type Namespaces = HashMap<String, Namespace>;
+
+#[derive(Clone)]
+pub struct RelationshipService {
+    pub repository: RelationshipRepository,
+    pub namespace_repository: NamespaceRepository,
+}
+
+impl RelationshipService {
+    pub fn new(
+        repository: RelationshipRepository,
+        namespace_repository: NamespaceRepository,
+    ) -> Self {
+        Self {
+            repository,
+            namespace_repository,
+        }
+    }
+
+    pub async fn find_all_namespaces(
+        &self,
+        db: &Database,
+        organization: &str,
+    ) -> Result<Vec<Namespace>, Error> {
+        self.namespace_repository.find_all(db, organization).await
+    }
+
+    pub async fn find_namespace_by_name(
+        &self,
+        db: &Database,
+        organization: &str,
+        name: &str,
+    ) -> Result<Option<Namespace>, Error> {
+        self.namespace_repository
+            .find_by_name(db, organization, name)
+            .await
+    }
+
+    pub async fn save_namespace(
+        &self,
+        db: &Database,
+        namespace: Namespace,
+    ) -> Result<Option<Namespace>, Error> {
+        self.namespace_repository.save(db, namespace).await
+    }
+
+    pub async fn delete_namespace(
+        &self,
+        db: &Database,
+        organization: &str,
+        name: &str,
+    ) -> Result<u64, Error> {
+        self.namespace_repository
+            .delete(db, organization, name)
+            .await
+    }
+
+    /// Read the stored tuples of an organization that match every given part
+    pub async fn read(
+        &self,
+        db: &Database,
+        organization: &str,
+        namespace: Option<&str>,
+        object_id: Option<&str>,
+        relation: Option<&str>,
+        subject: Option<&SubjectRef>,
+    ) -> Result<Vec<RelationTuple>, Error> {
+        let mut filter = doc! { "organization": organization };
+        if let Some(x) = namespace {
+            filter.insert("namespace", x);
+        }
+        if let Some(x) = object_id {
+            filter.insert("objectId", x);
+        }
+        if let Some(x) = relation {
+            filter.insert("relation", x);
+        }
+        if let Some(x) = subject {
+            filter.insert("subjectNamespace", &x.namespace);
+            filter.insert("subjectId", &x.id);
+            match &x.relation {
+                Some(r) => filter.insert("subjectRelation", r),
+                None => filter.insert("subjectRelation", mongodb::bson::Bson::Null),
+            };
+        }
+
+        self.repository.find(db, filter).await
+    }
+
+    /// Validate and apply a batch of tuple writes and deletes in one transaction. Nothing is
+    /// written when any of the tuples refers to an unknown namespace or relation.
+    pub async fn write(
+        &self,
+        db: &Database,
+        transactions: &Transactions,
+        organization: &str,
+        writes: Vec<RelationTuple>,
+        deletes: Vec<RelationTuple>,
+    ) -> Result<(), RelationshipError> {
+        let namespaces = self.load_namespaces(db, organization).await?;
+
+        for tuple in &writes {
+            let namespace = find_namespace(&namespaces, &tuple.namespace)?;
+            if namespace.find_relation(&tuple.relation).is_none() {
+                return Err(RelationshipError::UnknownRelation(format!(
+                    "{}#{}",
+                    tuple.namespace, tuple.relation
+                )));
+            }
+
+            if let Some(r) = &tuple.subject_relation {
+                let subject_namespace = find_namespace(&namespaces, &tuple.subject_namespace)?;
+                if subject_namespace.find_relation(r).is_none() {
+                    return Err(RelationshipError::UnknownRelation(format!(
+                        "{}#{}",
+                        tuple.subject_namespace, r
+                    )));
+                }
+            }
+        }
+
+        let mut transaction = transactions.begin().await?;
+
+        for tuple in writes {
+            self.repository
+                .write(db, tuple, transaction.session())
+                .await?;
+        }
+
+        for tuple in deletes {
+            self.repository
+                .delete(db, organization, &tuple.id, transaction.session())
+                .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Check whether the subject has the relation to the object
+    pub async fn check(
+        &self,
+        db: &Database,
+        organization: &str,
+        namespace: &str,
+        object_id: &str,
+        relation: &str,
+        subject: &SubjectRef,
+    ) -> Result<bool, RelationshipError> {
+        let namespaces = self.load_namespaces(db, organization).await?;
+        let tuples = StoredTuples {
+            repository: &self.repository,
+            db,
+            organization,
+        };
+
+        Evaluator::new(&namespaces, &tuples)
+            .check(namespace, object_id, relation, subject)
+            .await
+    }
+
+    /// Expand the relation of an object into the tree of subjects that hold it
+    pub async fn expand(
+        &self,
+        db: &Database,
+        organization: &str,
+        namespace: &str,
+        object_id: &str,
+        relation: &str,
+    ) -> Result<ExpandNode, RelationshipError> {
+        let namespaces = self.load_namespaces(db, organization).await?;
+        let tuples = StoredTuples {
+            repository: &self.repository,
+            db,
+            organization,
+        };
+
+        Evaluator::new(&namespaces, &tuples)
+            .expand(namespace, object_id, relation)
+            .await
+    }
+
+    async fn load_namespaces(
+        &self,
+        db: &Database,
+        organization: &str,
+    ) -> Result<Namespaces, Error> {
+        let namespaces = self.namespace_repository.find_all(db, organization).await?;
+        Ok(namespaces
+            .into_iter()
+            .map(|n| (n.name.clone(), n))
+            .collect())
+    }
+}
+
+/// Finds the tuples that a check or an expand follows
+trait TupleSource: Sync {
+    fn find<'a>(
+        &'a self,
+        namespace: &'a str,
+        object_id: &'a str,
+        relation: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<RelationTuple>, Error>>;
+}
+
+/// The stored tuples of an organization
+struct StoredTuples<'a> {
+    repository: &'a RelationshipRepository,
+    db: &'a Database,
+    organization: &'a str,
+}
+
+impl TupleSource for StoredTuples<'_> {
+    fn find<'a>(
+        &'a self,
+        namespace: &'a str,
+        object_id: &'a str,
+        relation: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<RelationTuple>, Error>> {
+        self.repository
+            .find_by_object_and_relation(self.db, self.organization, namespace, object_id, relation)
+            .boxed()
+    }
+}
+
+/// A relation of an object, such as `doc:readme#viewer`
+type Userset = (String, String, String);
+
+fn userset(namespace: &str, object_id: &str, relation: &str) -> Userset {
+    (
+        String::from(namespace),
+        String::from(object_id),
+        String::from(relation),
+    )
+}
+
+/// Evaluates checks and expands against a set of namespaces. Every relation of an object is
+/// followed at most once, so cycles between relations end as soon as they are detected.
+struct Evaluator<'a, T: TupleSource> {
+    namespaces: &'a Namespaces,
+    tuples: &'a T,
+}
+
+impl<'a, T: TupleSource> Evaluator<'a, T> {
+    fn new(namespaces: &'a Namespaces, tuples: &'a T) -> Self {
+        Self { namespaces, tuples }
+    }
+
+    async fn check(
+        &self,
+        namespace: &str,
+        object_id: &str,
+        relation: &str,
+        subject: &SubjectRef,
+    ) -> Result<bool, RelationshipError> {
+        let mut visited = HashSet::new();
+        self.check_relation(
+            namespace,
+            object_id,
+            relation,
+            subject,
+            MAX_DEPTH,
+            &mut visited,
+        )
+        .await
+    }
+
+    async fn expand(
+        &self,
+        namespace: &str,
+        object_id: &str,
+        relation: &str,
+    ) -> Result<ExpandNode, RelationshipError> {
+        let mut path = HashSet::new();
+        self.expand_relation(namespace, object_id, relation, MAX_DEPTH, &mut path)
+            .await
+    }
+
+    fn check_relation<'b>(
+        &'b self,
+        namespace: &'b str,
+        object_id: &'b str,
+        relation: &'b str,
+        subject: &'b SubjectRef,
+        depth: u32,
+        visited: &'b mut HashSet<Userset>,
+    ) -> BoxFuture<'b, Result<bool, RelationshipError>> {
+        async move {
+            if depth == 0 {
+                return Err(RelationshipError::DepthExceeded);
+            }
+
+            // A set of subjects always contains itself
+            if subject.namespace == namespace
+                && subject.id == object_id
+                && subject.relation.as_deref() == Some(relation)
+            {
+                return Ok(true);
+            }
+
+            // A relation that was followed before did not contain the subject, or is still being
+            // followed further up, in which case it is part of a cycle
+            if !visited.insert(userset(namespace, object_id, relation)) {
+                return Ok(false);
+            }
+
+            let rewrite = find_rewrite(self.namespaces, namespace, relation)?;
+            self.check_rewrite(
+                namespace, object_id, relation, &rewrite, subject, depth, visited,
+            )
+            .await
+        }
+        .boxed()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn check_rewrite<'b>(
+        &'b self,
+        namespace: &'b str,
+        object_id: &'b str,
+        relation: &'b str,
+        rewrite: &'b Rewrite,
+        subject: &'b SubjectRef,
+        depth: u32,
+        visited: &'b mut HashSet<Userset>,
+    ) -> BoxFuture<'b, Result<bool, RelationshipError>> {
+        async move {
+            match rewrite {
+                Rewrite::This => {
+                    let tuples = self.tuples.find(namespace, object_id, relation).await?;
+
+                    for tuple in &tuples {
+                        if subject.matches(tuple) {
+                            return Ok(true);
+                        }
+
+                        if let Some(r) = &tuple.subject_relation {
+                            if self
+                                .check_relation(
+                                    &tuple.subject_namespace,
+                                    &tuple.subject_id,
+                                    r,
+                                    subject,
+                                    depth - 1,
+                                    visited,
+                                )
+                                .await?
+                            {
+                                return Ok(true);
+                            }
+                        }
+                    }
+
+                    Ok(false)
+                }
+                Rewrite::ComputedUserset { relation: r } => {
+                    self.check_relation(namespace, object_id, r, subject, depth - 1, visited)
+                        .await
+                }
+                Rewrite::TupleToUserset {
+                    tupleset,
+                    computed_relation,
+                } => {
+                    let tuples = self.tuples.find(namespace, object_id, tupleset).await?;
+
+                    for tuple in &tuples {
+                        if self
+                            .check_relation(
+                                &tuple.subject_namespace,
+                                &tuple.subject_id,
+                                computed_relation,
+                                subject,
+                                depth - 1,
+                                visited,
+                            )
+                            .await?
+                        {
+                            return Ok(true);
+                        }
+                    }
+
+                    Ok(false)
+                }
+                Rewrite::Union { children } => {
+                    for child in children {
+                        if self
+                            .check_rewrite(
+                                namespace, object_id, relation, child, subject, depth, visited,
+                            )
+                            .await?
+                        {
+                            return Ok(true);
+                        }
+                    }
+
+                    Ok(false)
+                }
+            }
+        }
+        .boxed()
+    }
+
+    fn expand_relation<'b>(
+        &'b self,
+        namespace: &'b str,
+        object_id: &'b str,
+        relation: &'b str,
+        depth: u32,
+        path: &'b mut HashSet<Userset>,
+    ) -> BoxFuture<'b, Result<ExpandNode, RelationshipError>> {
+        async move {
+            if depth == 0 {
+                return Err(RelationshipError::DepthExceeded);
+            }
+
+            let rewrite = find_rewrite(self.namespaces, namespace, relation)?;
+
+            let key = userset(namespace, object_id, relation);
+            path.insert(key.clone());
+            let node = self
+                .expand_rewrite(namespace, object_id, relation, &rewrite, depth, path)
+                .await;
+            path.remove(&key);
+
+            node
+        }
+        .boxed()
+    }
+
+    /// Expand a relation that a tuple refers to, unless it is already being expanded further up
+    /// the tree
+    fn expand_child<'b>(
+        &'b self,
+        namespace: &'b str,
+        object_id: &'b str,
+        relation: &'b str,
+        depth: u32,
+        path: &'b mut HashSet<Userset>,
+    ) -> BoxFuture<'b, Result<Option<ExpandNode>, RelationshipError>> {
+        async move {
+            if path.contains(&userset(namespace, object_id, relation)) {
+                return Ok(None);
+            }
+
+            let node = self
+                .expand_relation(namespace, object_id, relation, depth, path)
+                .await?;
+            Ok(Some(node))
+        }
+        .boxed()
+    }
+
+    fn expand_rewrite<'b>(
+        &'b self,
+        namespace: &'b str,
+        object_id: &'b str,
+        relation: &'b str,
+        rewrite: &'b Rewrite,
+        depth: u32,
+        path: &'b mut HashSet<Userset>,
+    ) -> BoxFuture<'b, Result<ExpandNode, RelationshipError>> {
+        async move {
+            let mut node = ExpandNode {
+                object: format!("{}:{}", namespace, object_id),
+                relation: String::from(relation),
+                kind: String::new(),
+                subjects: vec![],
+                children: vec![],
+            };
+
+            match rewrite {
+                Rewrite::This => {
+                    node.kind = String::from("THIS");
+                    let tuples = self.tuples.find(namespace, object_id, relation).await?;
+
+                    for tuple in &tuples {
+                        node.subjects.push(tuple.subject());
+                        if let Some(r) = &tuple.subject_relation {
+                            let child = self
+                                .expand_child(
+                                    &tuple.subject_namespace,
+                                    &tuple.subject_id,
+                                    r,
+                                    depth - 1,
+                                    path,
+                                )
+                                .await?;
+                            node.children.extend(child);
+                        }
+                    }
+                }
+                Rewrite::ComputedUserset { relation: r } => {
+                    node.kind = String::from("COMPUTED_USERSET");
+                    let child = self
+                        .expand_child(namespace, object_id, r, depth - 1, path)
+                        .await?;
+                    node.children.extend(child);
+                }
+                Rewrite::TupleToUserset {
+                    tupleset,
+                    computed_relation,
+                } => {
+                    node.kind = String::from("TUPLE_TO_USERSET");
+                    let tuples = self.tuples.find(namespace, object_id, tupleset).await?;
+
+                    for tuple in &tuples {
+                        let child = self
+                            .expand_child(
+                                &tuple.subject_namespace,
+                                &tuple.subject_id,
+                                computed_relation,
+                                depth - 1,
+                                path,
+                            )
+                            .await?;
+                        node.children.extend(child);
+                    }
+                }
+                Rewrite::Union { children } => {
+                    node.kind = String::from("UNION");
+                    for child in children {
+                        node.children.push(
+                            self.expand_rewrite(namespace, object_id, relation, child, depth, path)
+                                .await?,
+                        );
+                    }
+                }
+            }
+
+            Ok(node)
+        }
+        .boxed()
+    }
+}
+
+/// Check that every relation that a namespace refers to is defined in the namespace itself
+pub fn validate_namespace(namespace: &Namespace) -> Result<(), String> {
+    if namespace.name.is_empty() || namespace.name.contains(':') || namespace.name.contains('#') {
+        return Err(format!("Invalid namespace name {}", namespace.name));
+    }
+
+    for (i, relation) in namespace.relations.iter().enumerate() {
+        if relation.name.is_empty() || relation.name.contains('#') || relation.name.contains('@') {
+            return Err(format!("Invalid relation name {}", relation.name));
+        }
+
+        if namespace.relations[..i]
+            .iter()
+            .any(|r| r.name == relation.name)
+        {
+            return Err(format!("Duplicate relation {}", relation.name));
+        }
+
+        if let Some(rewrite) = &relation.rewrite {
+            validate_rewrite(namespace, rewrite)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_rewrite(namespace: &Namespace, rewrite: &Rewrite) -> Result<(), String> {
+    match rewrite {
+        Rewrite::This => Ok(()),
+        Rewrite::ComputedUserset { relation } => {
+            if namespace.find_relation(relation).is_none() {
+                return Err(format!("Unknown relation {}", relation));
+            }
+            Ok(())
+        }
+        Rewrite::TupleToUserset {
+            tupleset,
+            computed_relation,
+        } => {
+            if namespace.find_relation(tupleset).is_none() {
+                return Err(format!("Unknown relation {}", tupleset));
+            }
+            if computed_relation.is_empty() {
+                return Err(String::from("Computed relation cannot be empty!"));
+            }
+            Ok(())
+        }
+        Rewrite::Union { children } => {
+            for child in children {
+                validate_rewrite(namespace, child)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn find_namespace<'a>(
+    namespaces: &'a Namespaces,
+    name: &str,
+) -> Result<&'a Namespace, RelationshipError> {
+    match namespaces.get(name) {
+        Some(d) => Ok(d),
+        None => Err(RelationshipError::UnknownNamespace(String::from(name))),
+    }
+}
+
+fn find_rewrite(
+    namespaces: &Namespaces,
+    namespace: &str,
+    relation: &str,
+) -> Result<Rewrite, RelationshipError> {
+    let config = find_namespace(namespaces, namespace)?;
+    match config.find_relation(relation) {
+        Some(d) => Ok(d.rewrite.clone().unwrap_or(Rewrite::This)),
+        None => Err(RelationshipError::UnknownRelation(format!(
+            "{}#{}",
+            namespace, relation
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use crate::persistence::relationship::model::namespace::Relation;
+
+    use super::*;
+
+    struct MemoryTuples(Vec<RelationTuple>);
+
+    impl TupleSource for MemoryTuples {
+        fn find<'a>(
+            &'a self,
+            namespace: &'a str,
+            object_id: &'a str,
+            relation: &'a str,
+        ) -> BoxFuture<'a, Result<Vec<RelationTuple>, Error>> {
+            let tuples = self
+                .0
+                .iter()
+                .filter(|t| {
+                    t.namespace == namespace && t.object_id == object_id && t.relation == relation
+                })
+                .cloned()
+                .collect();
+
+            futures::future::ready(Ok(tuples)).boxed()
+        }
+    }
+
+    fn relation(name: &str, rewrite: Option<Rewrite>) -> Relation {
+        Relation {
+            name: String::from(name),
+            rewrite,
+        }
+    }
+
+    fn namespaces() -> Namespaces {
+        let doc = Namespace::new(
+            "acme",
+            "doc",
+            vec![
+                relation("parent", None),
+                relation("owner", None),
+                relation(
+                    "editor",
+                    Some(Rewrite::Union {
+                        children: vec![
+                            Rewrite::This,
+                            Rewrite::ComputedUserset {
+                                relation: String::from("owner"),
+                            },
+                        ],
+                    }),
+                ),
+                relation(
+                    "viewer",
+                    Some(Rewrite::Union {
+                        children: vec![
+                            Rewrite::This,
+                            Rewrite::ComputedUserset {
+                                relation: String::from("editor"),
+                            },
+                            Rewrite::TupleToUserset {
+                                tupleset: String::from("parent"),
+                                computed_relation: String::from("viewer"),
+                            },
+                        ],
+                    }),
+                ),
+            ],
+        );
+        let folder = Namespace::new("acme", "folder", vec![relation("viewer", None)]);
+        let group = Namespace::new("acme", "group", vec![relation("member", None)]);
+
+        [doc, folder, group]
+            .into_iter()
+            .map(|n| (n.name.clone(), n))
+            .collect()
+    }
+
+    fn tuple(object: &str, relation: &str, subject: &str) -> RelationTuple {
+        let (namespace, object_id) = parse_object(object).unwrap();
+        let subject = SubjectRef::parse(subject).unwrap();
+        RelationTuple::new(
+            "acme",
+            &namespace,
+            &object_id,
+            relation,
+            &subject.namespace,
+            &subject.id,
+            subject.relation.as_deref(),
+        )
+    }
+
+    fn check(
+        tuples: Vec<RelationTuple>,
+        object: &str,
+        relation: &str,
+        subject: &str,
+    ) -> Result<bool, RelationshipError> {
+        let namespaces = namespaces();
+        let tuples = MemoryTuples(tuples);
+        let (namespace, object_id) = parse_object(object).unwrap();
+        let subject = SubjectRef::parse(subject).unwrap();
+
+        block_on(
+            Evaluator::new(&namespaces, &tuples).check(&namespace, &object_id, relation, &subject),
+        )
+    }
+
+    fn expand(
+        tuples: Vec<RelationTuple>,
+        object: &str,
+        relation: &str,
+    ) -> Result<ExpandNode, RelationshipError> {
+        let namespaces = namespaces();
+        let tuples = MemoryTuples(tuples);
+        let (namespace, object_id) = parse_object(object).unwrap();
+
+        block_on(Evaluator::new(&namespaces, &tuples).expand(&namespace, &object_id, relation))
+    }
+
+    #[test]
+    fn check_finds_direct_tuples() {
+        let tuples = vec![tuple("doc:readme", "viewer", "user:alice")];
+
+        assert!(matches!(
+            check(tuples.clone(), "doc:readme", "viewer", "user:alice"),
+            Ok(true)
+        ));
+        assert!(matches!(
+            check(tuples, "doc:readme", "viewer", "user:bob"),
+            Ok(false)
+        ));
+    }
+
+    #[test]
+    fn check_follows_union_and_computed_usersets() {
+        let tuples = vec![tuple("doc:readme", "owner", "user:alice")];
+
+        assert!(matches!(
+            check(tuples.clone(), "doc:readme", "editor", "user:alice"),
+            Ok(true)
+        ));
+        assert!(matches!(
+            check(tuples, "doc:readme", "viewer", "user:alice"),
+            Ok(true)
+        ));
+    }
+
+    #[test]
+    fn check_follows_tuple_to_userset() {
+        let tuples = vec![
+            tuple("doc:readme", "parent", "folder:docs"),
+            tuple("folder:docs", "viewer", "user:alice"),
+        ];
+
+        assert!(matches!(
+            check(tuples.clone(), "doc:readme", "viewer", "user:alice"),
+            Ok(true)
+        ));
+        assert!(matches!(
+            check(tuples, "doc:readme", "editor", "user:alice"),
+            Ok(false)
+        ));
+    }
+
+    #[test]
+    fn check_follows_subject_sets() {
+        let tuples = vec![
+            tuple("doc:readme", "viewer", "group:eng#member"),
+            tuple("group:eng", "member", "user:alice"),
+        ];
+
+        assert!(matches!(
+            check(tuples.clone(), "doc:readme", "viewer", "user:alice"),
+            Ok(true)
+        ));
+        assert!(matches!(
+            check(tuples, "doc:readme", "viewer", "group:eng#member"),
+            Ok(true)
+        ));
+    }
+
+    #[test]
+    fn check_stops_at_cycles() {
+        let tuples = vec![
+            tuple("group:a", "member", "group:b#member"),
+            tuple("group:b", "member", "group:a#member"),
+            tuple("group:b", "member", "user:alice"),
+        ];
+
+        assert!(matches!(
+            check(tuples.clone(), "group:a", "member", "user:alice"),
+            Ok(true)
+        ));
+        assert!(matches!(
+            check(tuples, "group:a", "member", "user:bob"),
+            Ok(false)
+        ));
+    }
+
+    #[test]
+    fn check_fails_when_the_depth_is_exhausted() {
+        let mut tuples: Vec<RelationTuple> = (0..MAX_DEPTH)
+            .map(|i| {
+                tuple(
+                    &format!("group:g{}", i),
+                    "member",
+                    &format!("group:g{}#member", i + 1),
+                )
+            })
+            .collect();
+        tuples.push(tuple(
+            &format!("group:g{}", MAX_DEPTH),
+            "member",
+            "user:alice",
+        ));
+
+        assert!(matches!(
+            check(tuples, "group:g0", "member", "user:alice"),
+            Err(RelationshipError::DepthExceeded)
+        ));
+    }
+
+    #[test]
+    fn check_rejects_unknown_relations() {
+        assert!(matches!(
+            check(vec![], "doc:readme", "commenter", "user:alice"),
+            Err(RelationshipError::UnknownRelation(_))
+        ));
+        assert!(matches!(
+            check(vec![], "wiki:home", "viewer", "user:alice"),
+            Err(RelationshipError::UnknownNamespace(_))
+        ));
+    }
+
+    #[test]
+    fn expand_builds_the_tree_of_a_relation() {
+        let tuples = vec![
+            tuple("doc:readme", "viewer", "group:eng#member"),
+            tuple("doc:readme", "owner", "user:alice"),
+            tuple("doc:readme", "parent", "folder:docs"),
+            tuple("group:eng", "member", "user:bob"),
+            tuple("folder:docs", "viewer", "user:carol"),
+        ];
+
+        let tree = match expand(tuples, "doc:readme", "viewer") {
+            Ok(d) => d,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(tree.kind, "UNION");
+        let kinds: Vec<&str> = tree.children.iter().map(|c| c.kind.as_str()).collect();
+        assert_eq!(kinds, ["THIS", "COMPUTED_USERSET", "TUPLE_TO_USERSET"]);
+
+        let this = &tree.children[0];
+        assert_eq!(this.subjects, ["group:eng#member"]);
+        assert_eq!(this.children[0].subjects, ["user:bob"]);
+
+        let editor = &tree.children[1].children[0];
+        assert_eq!(editor.relation, "editor");
+        assert_eq!(editor.children[1].children[0].subjects, ["user:alice"]);
+
+        let parent = &tree.children[2].children[0];
+        assert_eq!(parent.object, "folder:docs");
+        assert_eq!(parent.subjects, ["user:carol"]);
+    }
+
+    #[test]
+    fn expand_stops_at_cycles() {
+        let tuples = vec![
+            tuple("group:a", "member", "group:b#member"),
+            tuple("group:b", "member", "group:a#member"),
+        ];
+
+        let tree = match expand(tuples, "group:a", "member") {
+            Ok(d) => d,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(tree.subjects, ["group:b#member"]);
+        assert_eq!(tree.children[0].subjects, ["group:a#member"]);
+        assert!(tree.children[0].children.is_empty());
+    }
+
+    #[test]
+    fn expand_fails_when_the_depth_is_exhausted() {
+        let tuples: Vec<RelationTuple> = (0..MAX_DEPTH)
+            .map(|i| {
+                tuple(
+                    &format!("group:g{}", i),
+                    "member",
+                    &format!("group:g{}#member", i + 1),
+                )
+            })
+            .collect();
+
+        assert!(matches!(
+            expand(tuples, "group:g0", "member"),
+            Err(RelationshipError::DepthExceeded)
+        ));
+    }
+}
@@ -1,6 +1,9 @@
-use mongodb::{error::Error, Database};
+use mongodb::{error::Error, ClientSession, Database};
 
-use crate::persistence::role::{model::role::Role, role_repository::RoleRepository};
+use crate::persistence::{
+    page::{Page, PageRequest},
+    role::{model::role::Role, role_repository::RoleRepository},
+};
 
 #[derive(Clone)]
 pub struct RoleService {
@@ -12,42 +15,134 @@ impl RoleService {
         Self { repository }
     }
 
-    pub async fn create(&self, role: Role, db: &Database) -> Result<Option<Role>, Error> {
-        self.repository.create(role, db).await
+    pub async fn create_unique_index(&self, db: &Database) -> Result<(), Error> {
+        self.repository.create_unique_index(db).await
     }
 
-    pub async fn find_all(&self, db: &Database) -> Result<Vec<Role>, Error> {
-        self.repository.find_all(db).await
+    pub async fn create(
+        &self,
+        role: Role,
+        db: &Database,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Option<Role>, Error> {
+        self.repository.create(role, db, session).await
+    }
+
+    pub async fn find_all(&self, db: &Database, organization: &str) -> Result<Vec<Role>, Error> {
+        self.repository.find_all(db, organization).await
+    }
+
+    pub async fn find_page(
+        &self,
+        db: &Database,
+        organization: &str,
+        name: Option<&str>,
+        page: &PageRequest,
+    ) -> Result<Page<Role>, Error> {
+        self.repository
+            .find_page(db, organization, name, page)
+            .await
+    }
+
+    pub async fn find_by_uuid(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<Option<Role>, Error> {
+        self.repository.find_by_uuid(db, organization, uuid).await
+    }
+
+    pub async fn find_by_name(
+        &self,
+        db: &Database,
+        organization: &str,
+        name: &str,
+    ) -> Result<Option<Role>, Error> {
+        self.repository.find_by_name(db, organization, name).await
     }
 
-    pub async fn find_by_uuid(&self, db: &Database, uuid: &str) -> Result<Option<Role>, Error> {
-        self.repository.find_by_uuid(db, uuid).await
+    pub async fn update(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        role: Role,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Option<Role>, Error> {
+        self.repository
+            .update(db, organization, uuid, role, session)
+            .await
     }
 
-    pub async fn find_by_name(&self, db: &Database, name: &str) -> Result<Option<Role>, Error> {
-        self.repository.find_by_name(db, name).await
+    pub async fn count_by_uuids(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuids: &[String],
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        self.repository
+            .count_by_uuids(db, organization, uuids, session)
+            .await
     }
 
-    pub async fn find_by_permission_id(
+    pub async fn add_permission(
         &self,
         db: &Database,
+        organization: &str,
+        uuid: &str,
         permission_id: &str,
-    ) -> Result<Vec<Role>, Error> {
+    ) -> Result<Option<Role>, Error> {
         self.repository
-            .find_by_permission_id(db, permission_id)
+            .add_permission(db, organization, uuid, permission_id)
             .await
     }
 
-    pub async fn update(
+    pub async fn remove_permission(
         &self,
         db: &Database,
+        organization: &str,
         uuid: &str,
-        role: Role,
+        permission_id: &str,
     ) -> Result<Option<Role>, Error> {
-        self.repository.update(db, uuid, role).await
+        self.repository
+            .remove_permission(db, organization, uuid, permission_id)
+            .await
+    }
+
+    pub async fn pull_permission(
+        &self,
+        db: &Database,
+        organization: &str,
+        permission_id: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        self.repository
+            .pull_permission(db, organization, permission_id, session)
+            .await
     }
 
-    pub async fn delete(&self, db: &Database, uuid: &str) -> Result<u64, Error> {
-        self.repository.delete(db, uuid).await
+    pub async fn delete(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        version: Option<i64>,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        self.repository
+            .delete(db, organization, uuid, version, session)
+            .await
+    }
+
+    pub async fn assign_default_organization(
+        &self,
+        db: &Database,
+        organization: &str,
+    ) -> Result<u64, Error> {
+        self.repository
+            .assign_default_organization(db, organization)
+            .await
     }
 }
@@ -0,0 +1,129 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use serde::Deserialize;
+
+use crate::{
+    persistence::organization::model::organization::default_organization,
+    services::{
+        permission::system_permission::SystemPermission, user::password::is_supported_hash,
+    },
+};
+
+/// The permissions, roles and bootstrap administrator of an organization. Roles refer to
+/// permissions by name, which includes the system permissions, and the administrator refers to
+/// roles by name.
+#[derive(Deserialize)]
+pub struct Manifest {
+    #[serde(default = "default_organization")]
+    pub organization: String,
+    #[serde(default)]
+    pub permissions: Vec<ManifestPermission>,
+    #[serde(default)]
+    pub roles: Vec<ManifestRole>,
+    pub admin: Option<ManifestAdmin>,
+}
+
+#[derive(Deserialize)]
+pub struct ManifestPermission {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Deserialize)]
+pub struct ManifestRole {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+/// A user that is created when it does not exist yet and left alone afterwards, so that its
+/// password can be changed without changing the manifest
+#[derive(Deserialize)]
+pub struct ManifestAdmin {
+    pub username: String,
+    #[serde(rename = "emailAddress")]
+    pub email_address: String,
+    /// A bcrypt hash or an argon2 PHC string, so that the manifest does not contain the password
+    #[serde(rename = "passwordHash")]
+    pub password_hash: String,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// Whether the administrator may manage all organizations, such as inspecting their drift
+    #[serde(rename = "platformAdmin", default)]
+    pub platform_admin: bool,
+}
+
+impl Manifest {
+    /// Read a manifest from a JSON file or, when the extension is not `.json`, a YAML file
+    pub fn load(path: &str) -> Result<Manifest, String> {
+        let content = match fs::read_to_string(path) {
+            Ok(d) => d,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let manifest: Manifest = if Path::new(path).extension().is_some_and(|e| e == "json") {
+            serde_json::from_str(&content).map_err(|e| e.to_string())?
+        } else {
+            serde_yaml::from_str(&content).map_err(|e| e.to_string())?
+        };
+
+        manifest.check()?;
+        Ok(manifest)
+    }
+
+    /// Make sure that every name is unique and that every reference can be resolved
+    fn check(&self) -> Result<(), String> {
+        let permissions = unique_names("permission", self.permissions.iter().map(|p| &p.name))?;
+        let roles = unique_names("role", self.roles.iter().map(|r| &r.name))?;
+
+        for role in &self.roles {
+            if let Some(d) = role
+                .permissions
+                .iter()
+                .find(|p| !permissions.contains(p) && SystemPermission::from_name(p).is_none())
+            {
+                return Err(format!(
+                    "Role {} refers to unknown permission {}",
+                    role.name, d
+                ));
+            }
+        }
+
+        if let Some(admin) = &self.admin {
+            if let Some(d) = admin.roles.iter().find(|r| !roles.contains(r)) {
+                return Err(format!(
+                    "Administrator {} refers to unknown role {}",
+                    admin.username, d
+                ));
+            }
+            if !is_supported_hash(&admin.password_hash) {
+                return Err(format!(
+                    "The password hash of {} must be a bcrypt hash or an argon2 PHC string",
+                    admin.username
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn unique_names<'a>(
+    entity: &str,
+    names: impl Iterator<Item = &'a String>,
+) -> Result<HashSet<&'a String>, String> {
+    let mut seen = HashSet::new();
+    for name in names {
+        if name.trim().is_empty() {
+            return Err(format!("Every {} needs a name", entity));
+        }
+        if !seen.insert(name) {
+            return Err(format!("The {} {} is listed more than once", entity, name));
+        }
+    }
+
+    Ok(seen)
+}
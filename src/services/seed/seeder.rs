@@ -0,0 +1,429 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use mongodb::{error::Error, Database};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    persistence::{
+        event::model::event::{Event, SEED_APPLIED},
+        organization::model::organization::Organization,
+        permission::model::permission::Permission,
+        role::model::role::Role,
+        transaction::Transactions,
+        user::model::user::User,
+    },
+    services::{permission::system_permission::SystemPermission, Services},
+};
+
+use super::manifest::Manifest;
+
+/// How an object of an organization differs from the seed manifest
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DriftStatus {
+    /// Described by the manifest, but it does not exist
+    Missing,
+    /// Differs from the manifest in one or more fields
+    Changed,
+    /// Exists, but is not described by the manifest
+    Unlisted,
+}
+
+/// A difference between an organization and the seed manifest
+pub struct Drift {
+    pub entity_type: &'static str,
+    pub name: String,
+    pub status: DriftStatus,
+    pub fields: Vec<String>,
+}
+
+/// An object of the manifest in the state that seeding leaves it in
+struct Seeded<T> {
+    entity: T,
+    status: Option<DriftStatus>,
+    fields: Vec<String>,
+}
+
+impl<T> Seeded<T> {
+    fn missing(entity: T) -> Self {
+        Seeded {
+            entity,
+            status: Some(DriftStatus::Missing),
+            fields: vec![],
+        }
+    }
+
+    fn existing(entity: T, fields: Vec<String>) -> Self {
+        Seeded {
+            entity,
+            status: (!fields.is_empty()).then_some(DriftStatus::Changed),
+            fields,
+        }
+    }
+}
+
+/// The differences between an organization and the seed manifest
+struct SeedPlan {
+    permissions: Vec<Seeded<Permission>>,
+    roles: Vec<Seeded<Role>>,
+    unlisted_permissions: Vec<Permission>,
+    unlisted_roles: Vec<Role>,
+    /// The bootstrap administrator, when it does not exist yet
+    admin: Option<User>,
+}
+
+impl SeedPlan {
+    fn drift(&self) -> Vec<Drift> {
+        let mut drift = vec![];
+
+        let mut seeded = |entity_type: &'static str, name: &str, status, fields: &[String]| {
+            if let Some(status) = status {
+                drift.push(Drift {
+                    entity_type,
+                    name: String::from(name),
+                    status,
+                    fields: fields.to_vec(),
+                });
+            }
+        };
+        for p in &self.permissions {
+            seeded("permission", &p.entity.name, p.status, &p.fields);
+        }
+        for r in &self.roles {
+            seeded("role", &r.entity.name, r.status, &r.fields);
+        }
+        if let Some(d) = &self.admin {
+            seeded("user", &d.username, Some(DriftStatus::Missing), &[]);
+        }
+
+        let unlisted = |entity_type: &'static str, name: &str| Drift {
+            entity_type,
+            name: String::from(name),
+            status: DriftStatus::Unlisted,
+            fields: vec![],
+        };
+        drift.extend(
+            self.unlisted_permissions
+                .iter()
+                .map(|p| unlisted("permission", &p.name)),
+        );
+        drift.extend(
+            self.unlisted_roles
+                .iter()
+                .map(|r| unlisted("role", &r.name)),
+        );
+
+        drift
+    }
+}
+
+/// Compare the roles and permissions of the organization of a manifest with the manifest itself
+async fn plan_seed(
+    services: &Services,
+    db: &Database,
+    manifest: &Manifest,
+) -> Result<SeedPlan, Error> {
+    let organization = &manifest.organization;
+
+    let mut existing_permissions: HashMap<String, Permission> = services
+        .permission_service
+        .find_all(db, organization)
+        .await?
+        .into_iter()
+        .map(|p| (p.name.clone(), p))
+        .collect();
+
+    let system_permissions: Vec<(String, String)> = existing_permissions
+        .values()
+        .filter(|p| SystemPermission::from_name(&p.name).is_some())
+        .map(|p| (p.name.clone(), p.id.clone()))
+        .collect();
+
+    let mut permissions = vec![];
+    for permission in &manifest.permissions {
+        let seeded = match existing_permissions.remove(&permission.name) {
+            Some(mut current) => {
+                let mut fields = vec![];
+                if current.description != permission.description {
+                    current.description = permission.description.clone();
+                    fields.push(String::from("description"));
+                }
+                if !current.managed {
+                    current.managed = true;
+                    fields.push(String::from("managed"));
+                }
+                Seeded::existing(current, fields)
+            }
+            None => Seeded::missing(Permission {
+                id: Uuid::new_v4().to_string(),
+                name: permission.name.clone(),
+                description: permission.description.clone(),
+                organization: organization.clone(),
+                version: 0,
+                managed: true,
+            }),
+        };
+        permissions.push(seeded);
+    }
+
+    // System permissions are never unlisted, roles can refer to them without describing them
+    existing_permissions.retain(|name, _| SystemPermission::from_name(name).is_none());
+    let permission_ids: HashMap<String, String> = permissions
+        .iter()
+        .map(|p| (p.entity.name.clone(), p.entity.id.clone()))
+        .chain(system_permissions)
+        .collect();
+
+    let mut existing_roles: HashMap<String, Role> = services
+        .role_service
+        .find_all(db, organization)
+        .await?
+        .into_iter()
+        .map(|r| (r.name.clone(), r))
+        .collect();
+
+    let mut roles = vec![];
+    for role in &manifest.roles {
+        let ids: Vec<String> = role
+            .permissions
+            .iter()
+            .filter_map(|p| permission_ids.get(p).cloned())
+            .collect();
+
+        let seeded = match existing_roles.remove(&role.name) {
+            Some(mut current) => {
+                let mut fields = vec![];
+                if current.description != role.description {
+                    current.description = role.description.clone();
+                    fields.push(String::from("description"));
+                }
+                if current.permissions.iter().collect::<HashSet<_>>()
+                    != ids.iter().collect::<HashSet<_>>()
+                {
+                    current.permissions = ids;
+                    fields.push(String::from("permissions"));
+                }
+                if !current.managed {
+                    current.managed = true;
+                    fields.push(String::from("managed"));
+                }
+                Seeded::existing(current, fields)
+            }
+            None => Seeded::missing(Role {
+                id: Uuid::new_v4().to_string(),
+                name: role.name.clone(),
+                description: role.description.clone(),
+                permissions: ids,
+                approver_permissions: vec![],
+                organization: organization.clone(),
+                version: 0,
+                managed: true,
+            }),
+        };
+        roles.push(seeded);
+    }
+
+    let admin = match &manifest.admin {
+        Some(d) => match services
+            .user_service
+            .find_by_username(db, organization, &d.username)
+            .await?
+        {
+            Some(_) => None,
+            None => {
+                let role_ids: HashMap<&str, &str> = roles
+                    .iter()
+                    .map(|r| (r.entity.name.as_str(), r.entity.id.as_str()))
+                    .collect();
+
+                Some(User {
+                    id: Uuid::new_v4().to_string(),
+                    username: d.username.clone(),
+                    email_address: d.email_address.clone(),
+                    password: d.password_hash.clone(),
+                    first_name: String::from(""),
+                    last_name: String::from(""),
+                    enabled: true,
+                    platform_admin: d.platform_admin,
+                    service_account: false,
+                    roles: d
+                        .roles
+                        .iter()
+                        .filter_map(|r| role_ids.get(r.as_str()).map(|id| String::from(*id)))
+                        .collect(),
+                    role_assignments: vec![],
+                    created_at: Utc::now().to_string(),
+                    last_active: String::from(""),
+                    attributes: HashMap::new(),
+                    organization: organization.clone(),
+                    version: 0,
+                })
+            }
+        },
+        None => None,
+    };
+
+    let mut unlisted_permissions: Vec<Permission> = existing_permissions.into_values().collect();
+    unlisted_permissions.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut unlisted_roles: Vec<Role> = existing_roles.into_values().collect();
+    unlisted_roles.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(SeedPlan {
+        permissions,
+        roles,
+        unlisted_permissions,
+        unlisted_roles,
+        admin,
+    })
+}
+
+/// The differences between the organization of a manifest and the manifest itself
+pub async fn drift(
+    services: &Services,
+    db: &Database,
+    manifest: &Manifest,
+) -> Result<Vec<Drift>, Error> {
+    Ok(plan_seed(services, db, manifest).await?.drift())
+}
+
+/// Make the organization of a manifest match the manifest. The roles and permissions that it
+/// describes are created or updated and marked as managed, and the bootstrap administrator is
+/// created when it does not exist yet. The roles and permissions that it does not describe are
+/// deleted when pruning, or otherwise released when they were managed before. Applying the same
+/// manifest again changes nothing. Returns the number of changes.
+pub async fn apply(
+    services: &Services,
+    db: &Database,
+    transactions: &Transactions,
+    manifest: &Manifest,
+    prune: bool,
+) -> Result<usize, Error> {
+    let organization = &manifest.organization;
+
+    if services
+        .organization_service
+        .find_by_id(db, organization)
+        .await?
+        .is_none()
+    {
+        let new_organization = Organization {
+            id: organization.clone(),
+            name: organization.clone(),
+            description: String::from("Created by the seed manifest"),
+            enabled: true,
+            created_at: Utc::now().to_string(),
+        };
+        services
+            .organization_service
+            .create(new_organization, db)
+            .await?;
+        services.ensure_system_permissions(db, organization).await?;
+    }
+
+    let plan = plan_seed(services, db, manifest).await?;
+    let mut changes = 0;
+
+    // Conditional updates only fail when another instance applies the same manifest at the same
+    // time, so those are not treated as errors
+    for seeded in plan.permissions {
+        match seeded.status {
+            Some(DriftStatus::Missing) => {
+                services
+                    .permission_service
+                    .create(seeded.entity, db, None)
+                    .await?;
+            }
+            Some(_) => {
+                let id = seeded.entity.id.clone();
+                services
+                    .permission_service
+                    .update(db, organization, &id, seeded.entity, None)
+                    .await?;
+            }
+            None => continue,
+        }
+        changes += 1;
+    }
+
+    for seeded in plan.roles {
+        match seeded.status {
+            Some(DriftStatus::Missing) => {
+                services
+                    .role_service
+                    .create(seeded.entity, db, None)
+                    .await?;
+            }
+            Some(_) => {
+                let id = seeded.entity.id.clone();
+                services
+                    .role_service
+                    .update(db, organization, &id, seeded.entity, None)
+                    .await?;
+            }
+            None => continue,
+        }
+        changes += 1;
+    }
+
+    for mut role in plan.unlisted_roles {
+        if prune {
+            services
+                .delete_role(db, transactions, organization, &role.id, None)
+                .await?;
+        } else if role.managed {
+            let id = role.id.clone();
+            role.managed = false;
+            services
+                .role_service
+                .update(db, organization, &id, role, None)
+                .await?;
+        } else {
+            continue;
+        }
+        changes += 1;
+    }
+
+    for mut permission in plan.unlisted_permissions {
+        if prune {
+            services
+                .delete_permission(db, transactions, organization, &permission.id, None)
+                .await?;
+        } else if permission.managed {
+            let id = permission.id.clone();
+            permission.managed = false;
+            services
+                .permission_service
+                .update(db, organization, &id, permission, None)
+                .await?;
+        } else {
+            continue;
+        }
+        changes += 1;
+    }
+
+    if let Some(admin) = plan.admin {
+        services.user_service.create(admin, db, None).await?;
+        changes += 1;
+    }
+
+    if changes > 0 {
+        let mut data = HashMap::new();
+        data.insert(String::from("changes"), changes.to_string());
+        data.insert(String::from("prune"), prune.to_string());
+
+        let event = Event {
+            id: Uuid::new_v4().to_string(),
+            event_type: String::from(SEED_APPLIED),
+            organization: organization.clone(),
+            user_id: None,
+            message: format!("Seed manifest was applied with {} changes", changes),
+            data,
+            timestamp: Utc::now().to_string(),
+        };
+        services.event_service.create(event, db).await?;
+    }
+
+    Ok(changes)
+}
@@ -0,0 +1,117 @@
+use mongodb::{error::Error, ClientSession, Database};
+
+use crate::persistence::session::{model::session::Session, session_repository::SessionRepository};
+
+#[derive(Clone)]
+pub struct SessionService {
+    pub repository: SessionRepository,
+}
+
+impl SessionService {
+    pub fn new(repository: SessionRepository) -> Self {
+        Self { repository }
+    }
+
+    pub async fn create(&self, session: Session, db: &Database) -> Result<Option<Session>, Error> {
+        self.repository.create(session, db).await
+    }
+
+    pub async fn find_by_uuid(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<Option<Session>, Error> {
+        self.repository.find_by_uuid(db, organization, uuid).await
+    }
+
+    pub async fn find_by_user(
+        &self,
+        db: &Database,
+        organization: &str,
+        user_id: &str,
+    ) -> Result<Vec<Session>, Error> {
+        self.repository
+            .find_by_user(db, organization, user_id)
+            .await
+    }
+
+    pub async fn update_last_seen(
+        &self,
+        db: &Database,
+        uuid: &str,
+        last_seen: &str,
+    ) -> Result<u64, Error> {
+        self.repository.update_last_seen(db, uuid, last_seen).await
+    }
+
+    pub async fn delete(
+        &self,
+        db: &Database,
+        organization: &str,
+        user_id: &str,
+        uuid: &str,
+    ) -> Result<u64, Error> {
+        self.repository
+            .delete(db, organization, user_id, uuid)
+            .await
+    }
+
+    pub async fn delete_by_user(
+        &self,
+        db: &Database,
+        organization: &str,
+        user_id: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        self.repository
+            .delete_by_user(db, organization, user_id, session)
+            .await
+    }
+
+    pub async fn delete_expired(&self, db: &Database, now: &str) -> Result<u64, Error> {
+        self.repository.delete_expired(db, now).await
+    }
+}
+
+/// Derive a readable label such as "Firefox on Windows" from a user agent
+pub fn device_label(user_agent: Option<&str>) -> String {
+    let user_agent = match user_agent {
+        Some(d) if !d.is_empty() => d,
+        _ => return String::from("Unknown device"),
+    };
+
+    // The order matters: Edge and Opera mention Chrome, Chrome mentions Safari
+    let browsers = [
+        ("Edg/", "Edge"),
+        ("OPR/", "Opera"),
+        ("Firefox/", "Firefox"),
+        ("Chrome/", "Chrome"),
+        ("Safari/", "Safari"),
+        ("curl/", "curl"),
+    ];
+    let systems = [
+        ("Android", "Android"),
+        ("iPhone", "iOS"),
+        ("iPad", "iPadOS"),
+        ("Windows", "Windows"),
+        ("Mac OS X", "macOS"),
+        ("Linux", "Linux"),
+    ];
+
+    let browser = browsers
+        .iter()
+        .find(|(marker, _)| user_agent.contains(marker))
+        .map(|(_, name)| *name);
+    let system = systems
+        .iter()
+        .find(|(marker, _)| user_agent.contains(marker))
+        .map(|(_, name)| *name);
+
+    match (browser, system) {
+        (Some(b), Some(s)) => format!("{} on {}", b, s),
+        (Some(b), None) => String::from(b),
+        (None, Some(s)) => String::from(s),
+        (None, None) => String::from("Unknown device"),
+    }
+}
@@ -1 +1,3 @@
+pub mod password;
+pub mod user_search;
 pub mod user_service;
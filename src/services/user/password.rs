@@ -0,0 +1,46 @@
+use argon2::{
+    password_hash::{Error as HashError, PasswordHash},
+    Argon2, PasswordVerifier,
+};
+use bcrypt::{HashParts, DEFAULT_COST};
+use once_cell::sync::Lazy;
+
+const ARGON2_PREFIX: &str = "$argon2";
+
+/// A hash with the cost of new passwords that no password is verified against successfully
+static DUMMY_HASH: Lazy<String> =
+    Lazy::new(|| bcrypt::hash(uuid::Uuid::new_v4().to_string(), DEFAULT_COST).unwrap());
+
+/// Whether a password hash can be used to sign in. Passwords are hashed with bcrypt, argon2 PHC
+/// strings are accepted as well so that imported credentials keep working.
+pub fn is_supported_hash(hash: &str) -> bool {
+    if hash.starts_with(ARGON2_PREFIX) {
+        PasswordHash::new(hash).is_ok()
+    } else {
+        hash.parse::<HashParts>().is_ok()
+    }
+}
+
+/// Verify a password against a bcrypt hash or an argon2 PHC string
+pub fn verify_password(password: &str, hash: &str) -> Result<bool, String> {
+    if !hash.starts_with(ARGON2_PREFIX) {
+        return bcrypt::verify(password, hash).map_err(|e| e.to_string());
+    }
+
+    let parsed = match PasswordHash::new(hash) {
+        Ok(d) => d,
+        Err(e) => return Err(e.to_string()),
+    };
+
+    match Argon2::default().verify_password(password.as_bytes(), &parsed) {
+        Ok(()) => Ok(true),
+        Err(HashError::Password) => Ok(false),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Spend as long as verifying a password of an existing user would, so that the response time of
+/// a failed sign in does not reveal whether the user exists
+pub fn verify_dummy_password(password: &str) {
+    let _ = bcrypt::verify(password, &DUMMY_HASH);
+}
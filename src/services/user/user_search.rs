@@ -0,0 +1,216 @@
+use std::cmp::Ordering;
+
+use crate::persistence::user::model::user::User;
+
+/// The maximum number of words of a query that are taken into account
+pub const MAX_TERMS: usize = 8;
+
+/// Split a query into distinct, lower case terms
+pub fn terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = vec![];
+    for term in query.split_whitespace().map(str::to_lowercase) {
+        if !terms.contains(&term) {
+            terms.push(term);
+        }
+        if terms.len() == MAX_TERMS {
+            break;
+        }
+    }
+    terms
+}
+
+/// The prefixes that a word needs to start with to be a candidate for a term. Fuzzy matches are
+/// only found for words that share the first two characters of a term.
+pub fn candidate_prefixes(terms: &[String]) -> Vec<String> {
+    let mut prefixes: Vec<String> = vec![];
+    for term in terms {
+        let prefix: String = term.chars().take(2).collect();
+        if !prefixes.contains(&prefix) {
+            prefixes.push(prefix);
+        }
+    }
+    prefixes
+}
+
+/// Score how well a user matches the terms of a query. Every term has to match one of the
+/// searched fields, either exactly, as a prefix or with a few typos. Returns `None` when the user
+/// does not match.
+pub fn score(user: &User, terms: &[String]) -> Option<f64> {
+    let fields = [
+        (user.username.to_lowercase(), 4.0),
+        (user.email_address.to_lowercase(), 3.0),
+        (user.first_name.to_lowercase(), 2.0),
+        (user.last_name.to_lowercase(), 2.0),
+    ];
+
+    let mut total = 0.0;
+    for term in terms {
+        let mut best: f64 = 0.0;
+        for (value, weight) in &fields {
+            let words = value
+                .split(|c: char| !c.is_alphanumeric())
+                .chain(std::iter::once(value.as_str()));
+            for word in words.filter(|w| !w.is_empty()) {
+                best = best.max(match_term(term, word) * weight);
+            }
+        }
+
+        if best == 0.0 {
+            return None;
+        }
+        total += best;
+    }
+
+    Some(total)
+}
+
+/// Rank scored users by their score, then by their text score and lastly by their username
+pub fn rank(users: &mut [(User, f64, f64)]) {
+    users.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1)
+            .unwrap_or(Ordering::Equal)
+            .then(b.2.partial_cmp(&a.2).unwrap_or(Ordering::Equal))
+            .then_with(|| a.0.username.cmp(&b.0.username))
+    });
+}
+
+fn match_term(term: &str, word: &str) -> f64 {
+    if word == term {
+        return 1.0;
+    }
+    if word.starts_with(term) {
+        return 0.75;
+    }
+
+    let length = term.chars().count();
+    let allowed = match length {
+        0..=3 => return 0.0,
+        4..=7 => 1,
+        _ => 2,
+    };
+
+    // Compare against the whole word and against its start, so that partial words with a typo match
+    let start: String = word.chars().take(length).collect();
+    let distance = levenshtein(term, word).min(levenshtein(term, &start));
+    if distance > allowed {
+        return 0.0;
+    }
+
+    0.5 - 0.1 * distance as f64
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn user(username: &str, email_address: &str, first_name: &str, last_name: &str) -> User {
+        User {
+            id: String::from(username),
+            username: String::from(username),
+            email_address: String::from(email_address),
+            password: String::from(""),
+            first_name: String::from(first_name),
+            last_name: String::from(last_name),
+            enabled: true,
+            platform_admin: false,
+            service_account: false,
+            roles: vec![],
+            role_assignments: vec![],
+            created_at: String::from(""),
+            last_active: String::from(""),
+            attributes: HashMap::new(),
+            organization: String::from("acme"),
+            version: 0,
+        }
+    }
+
+    #[test]
+    fn terms_are_distinct_lower_case_and_limited() {
+        assert_eq!(terms("  John  SMITH john "), vec!["john", "smith"]);
+        assert!(terms("   ").is_empty());
+        assert_eq!(terms("a b c d e f g h i j").len(), MAX_TERMS);
+    }
+
+    #[test]
+    fn candidate_prefixes_are_the_distinct_first_two_characters() {
+        let terms = vec![
+            String::from("john"),
+            String::from("jones"),
+            String::from("x"),
+            String::from("émile"),
+        ];
+
+        assert_eq!(candidate_prefixes(&terms), vec!["jo", "x", "ém"]);
+    }
+
+    #[test]
+    fn exact_matches_beat_prefixes_and_typos() {
+        let john = user("jsmith", "john@example.com", "John", "Smith");
+
+        let exact = score(&john, &[String::from("john")]).unwrap();
+        let prefix = score(&john, &[String::from("joh")]).unwrap();
+        let typo = score(&john, &[String::from("jahn")]).unwrap();
+
+        assert!(exact > prefix);
+        assert!(prefix > typo);
+    }
+
+    #[test]
+    fn every_term_has_to_match() {
+        let john = user("jsmith", "john@example.com", "John", "Smith");
+
+        assert!(score(&john, &[String::from("john"), String::from("smith")]).is_some());
+        assert!(score(&john, &[String::from("john"), String::from("doe")]).is_none());
+    }
+
+    #[test]
+    fn short_terms_do_not_match_with_typos() {
+        let ann = user("ann", "ann@example.com", "Ann", "Lee");
+
+        assert!(score(&ann, &[String::from("anm")]).is_none());
+        assert!(score(&ann, &[String::from("lea")]).is_none());
+    }
+
+    #[test]
+    fn rank_orders_by_score_then_text_score_then_username() {
+        let mut users = vec![
+            (user("carol", "", "", ""), 1.0, 0.0),
+            (user("bob", "", "", ""), 2.0, 0.0),
+            (user("dave", "", "", ""), 1.0, 0.5),
+            (user("alice", "", "", ""), 1.0, 0.0),
+        ];
+
+        rank(&mut users);
+
+        let usernames: Vec<&str> = users.iter().map(|u| u.0.username.as_str()).collect();
+        assert_eq!(usernames, vec!["bob", "dave", "alice", "carol"]);
+    }
+
+    #[test]
+    fn levenshtein_counts_edits() {
+        assert_eq!(levenshtein("smith", "smith"), 0);
+        assert_eq!(levenshtein("smith", "smyth"), 1);
+        assert_eq!(levenshtein("smith", "smit"), 1);
+        assert_eq!(levenshtein("", "abc"), 3);
+    }
+}
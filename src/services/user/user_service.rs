@@ -1,6 +1,19 @@
-use mongodb::{error::Error, Database};
+use std::collections::HashMap;
 
-use crate::persistence::user::{model::user::User, user_repository::UserRepository};
+use mongodb::{error::Error, ClientSession, Database};
+
+use crate::persistence::{
+    page::{Page, PageRequest},
+    user::{
+        model::{role_assignment::RoleAssignment, user::User, user_filter::UserFilter},
+        user_repository::UserRepository,
+    },
+};
+
+use super::user_search;
+
+/// The maximum number of users that are fetched from the database and ranked for a search
+pub const SEARCH_CANDIDATE_LIMIT: i64 = 1000;
 
 #[derive(Clone)]
 pub struct UserService {
@@ -12,70 +25,300 @@ impl UserService {
         Self { repository }
     }
 
-    pub async fn create(&self, user: User, db: &Database) -> Result<Option<User>, Error> {
-        self.repository.create(user, db).await
+    pub async fn create(
+        &self,
+        user: User,
+        db: &Database,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Option<User>, Error> {
+        self.repository.create(user, db, session).await
+    }
+
+    pub async fn find_all(&self, db: &Database, organization: &str) -> Result<Vec<User>, Error> {
+        self.repository.find_all(db, organization).await
+    }
+
+    pub async fn find_page(
+        &self,
+        db: &Database,
+        organization: &str,
+        filter: &UserFilter,
+        page: &PageRequest,
+    ) -> Result<Page<User>, Error> {
+        self.repository
+            .find_page(db, organization, filter, page)
+            .await
     }
 
-    pub async fn find_all(&self, db: &Database) -> Result<Vec<User>, Error> {
-        self.repository.find_all(db).await
+    pub async fn create_search_index(&self, db: &Database) -> Result<(), Error> {
+        self.repository.create_search_index(db).await
     }
 
-    pub async fn find_by_uuid(&self, db: &Database, uuid: &str) -> Result<Option<User>, Error> {
-        self.repository.find_by_uuid(db, uuid).await
+    pub async fn create_unique_indexes(&self, db: &Database) -> Result<(), Error> {
+        self.repository.create_unique_indexes(db).await
+    }
+
+    /// Search the users of an organization by their username, email address and names. Candidates
+    /// are fetched with the text index and by the start of their words, and are ranked here so that
+    /// the ranking does not depend on the database.
+    pub async fn search(
+        &self,
+        db: &Database,
+        organization: &str,
+        query: &str,
+        page: &PageRequest,
+    ) -> Result<Page<User>, Error> {
+        let terms = user_search::terms(query);
+        if terms.is_empty() {
+            return Ok(Page {
+                items: vec![],
+                total: 0,
+            });
+        }
+
+        let mut candidates: HashMap<String, (User, f64)> = HashMap::new();
+        for (user, text_score) in self
+            .repository
+            .search_text(db, organization, &terms.join(" "), SEARCH_CANDIDATE_LIMIT)
+            .await?
+        {
+            candidates.insert(user.id.clone(), (user, text_score));
+        }
+        for user in self
+            .repository
+            .find_by_word_prefixes(
+                db,
+                organization,
+                &user_search::candidate_prefixes(&terms),
+                SEARCH_CANDIDATE_LIMIT,
+            )
+            .await?
+        {
+            candidates.entry(user.id.clone()).or_insert((user, 0.0));
+        }
+
+        let mut ranked = vec![];
+        for (user, text_score) in candidates.into_values() {
+            if let Some(score) = user_search::score(&user, &terms) {
+                ranked.push((user, score, text_score));
+            }
+        }
+        user_search::rank(&mut ranked);
+
+        let total = ranked.len() as u64;
+        let items = ranked
+            .into_iter()
+            .skip(page.offset as usize)
+            .take(page.limit as usize)
+            .map(|(user, _, _)| user)
+            .collect();
+
+        Ok(Page { items, total })
+    }
+
+    pub async fn find_by_uuid(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+    ) -> Result<Option<User>, Error> {
+        self.repository.find_by_uuid(db, organization, uuid).await
     }
 
     pub async fn find_by_username(
         &self,
         db: &Database,
+        organization: &str,
         username: &str,
     ) -> Result<Option<User>, Error> {
-        self.repository.find_by_username(db, username).await
+        self.repository
+            .find_by_username(db, organization, username)
+            .await
     }
 
     pub async fn find_by_email_address(
         &self,
         db: &Database,
+        organization: &str,
         email_address: &str,
     ) -> Result<Option<User>, Error> {
         self.repository
-            .find_by_email_address(db, email_address)
+            .find_by_email_address(db, organization, email_address)
             .await
     }
 
-    pub async fn find_by_role_id(&self, db: &Database, role_id: &str) -> Result<Vec<User>, Error> {
-        self.repository.find_by_role_id(db, role_id).await
+    pub async fn update(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        user: User,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Option<User>, Error> {
+        self.repository
+            .update(db, organization, uuid, user, session)
+            .await
     }
 
-    pub async fn update(
+    pub async fn update_with_password(
         &self,
         db: &Database,
+        organization: &str,
         uuid: &str,
         user: User,
+        session: Option<&mut ClientSession>,
     ) -> Result<Option<User>, Error> {
-        self.repository.update(db, uuid, user).await
+        self.repository
+            .update_with_password(db, organization, uuid, user, session)
+            .await
     }
 
     pub async fn update_password(
         &self,
         db: &Database,
+        organization: &str,
         uuid: &str,
         password: &str,
     ) -> Result<Option<User>, Error> {
-        self.repository.update_password(db, uuid, password).await
+        self.repository
+            .update_password(db, organization, uuid, password)
+            .await
+    }
+
+    pub async fn grant_platform_admin(
+        &self,
+        db: &Database,
+        organization: &str,
+        username: &str,
+    ) -> Result<Option<User>, Error> {
+        self.repository
+            .grant_platform_admin(db, organization, username)
+            .await
     }
 
     pub async fn update_last_active(
         &self,
         db: &Database,
+        organization: &str,
         uuid: &str,
         last_active: &str,
     ) -> Result<Option<User>, Error> {
         self.repository
-            .update_last_active(db, uuid, last_active)
+            .update_last_active(db, organization, uuid, last_active)
+            .await
+    }
+
+    pub async fn add_role_assignment(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        assignment: RoleAssignment,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Option<User>, Error> {
+        self.repository
+            .add_role_assignment(db, organization, uuid, assignment, session)
+            .await
+    }
+
+    pub async fn remove_role_assignment(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        role_id: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Option<User>, Error> {
+        self.repository
+            .remove_role_assignment(db, organization, uuid, role_id, session)
+            .await
+    }
+
+    pub async fn add_role(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        role_id: &str,
+    ) -> Result<Option<User>, Error> {
+        self.repository
+            .add_role(db, organization, uuid, role_id)
+            .await
+    }
+
+    pub async fn remove_role(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        role_id: &str,
+    ) -> Result<Option<User>, Error> {
+        self.repository
+            .remove_role(db, organization, uuid, role_id)
+            .await
+    }
+
+    pub async fn pull_role(
+        &self,
+        db: &Database,
+        organization: &str,
+        role_id: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        self.repository
+            .pull_role(db, organization, role_id, session)
             .await
     }
 
-    pub async fn delete(&self, db: &Database, uuid: &str) -> Result<u64, Error> {
-        self.repository.delete(db, uuid).await
+    pub async fn remove_lapsed_role_assignment(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        role_id: &str,
+        valid_until: &str,
+    ) -> Result<u64, Error> {
+        self.repository
+            .remove_lapsed_role_assignment(db, organization, uuid, role_id, valid_until)
+            .await
+    }
+
+    pub async fn remove_role_assignments_by_role(
+        &self,
+        db: &Database,
+        organization: &str,
+        role_id: &str,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        self.repository
+            .remove_role_assignments_by_role(db, organization, role_id, session)
+            .await
+    }
+
+    pub async fn find_with_role_assignments(&self, db: &Database) -> Result<Vec<User>, Error> {
+        self.repository.find_with_role_assignments(db).await
+    }
+
+    pub async fn delete(
+        &self,
+        db: &Database,
+        organization: &str,
+        uuid: &str,
+        version: Option<i64>,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64, Error> {
+        self.repository
+            .delete(db, organization, uuid, version, session)
+            .await
+    }
+
+    pub async fn assign_default_organization(
+        &self,
+        db: &Database,
+        organization: &str,
+    ) -> Result<u64, Error> {
+        self.repository
+            .assign_default_organization(db, organization)
+            .await
     }
 }